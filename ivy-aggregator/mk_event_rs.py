@@ -26,6 +26,18 @@ PUMP_FUN_EVENTS = [
         "variant": "PaSell",
         "display_name": "paSellEvent",
         "discriminator_const": "PA_SELL_EVENT_TAG"
+    },
+    {
+        "name": "PaDepositEvent",
+        "variant": "PaDeposit",
+        "display_name": "paDepositEvent",
+        "discriminator_const": "PA_DEPOSIT_EVENT_TAG"
+    },
+    {
+        "name": "PaWithdrawEvent",
+        "variant": "PaWithdraw",
+        "display_name": "paWithdrawEvent",
+        "discriminator_const": "PA_WITHDRAW_EVENT_TAG"
     }
 ]
 
@@ -114,6 +126,8 @@ def generate_sol_price_event():
     rust_code += "#[derive(Debug, Clone, Serialize, Deserialize)]\n"
     rust_code += "pub struct SolPriceEvent {\n"
     rust_code += "    pub price: f64,\n"
+    rust_code += "    #[serde(default)]\n"
+    rust_code += "    pub source: PriceSource,\n"
     rust_code += "}\n"
     rust_code += "impl_event_type!(SolPriceEvent, \"solPriceEvent\", SolPrice);\n"
     return rust_code
@@ -135,6 +149,9 @@ def generate_hydrate_event():
     rust_code += "    pub description: String,\n"
     rust_code += "    #[serde(rename = \"iconUrl\")]\n"
     rust_code += "    pub icon_url: String,\n"
+    for field in ["website", "twitter", "telegram"]:
+        rust_code += "    #[serde(default)]\n"
+        rust_code += f"    pub {field}: String,\n"
     rust_code += "}\n"
     rust_code += "impl_event_type!(HydrateEvent, \"hydrateEvent\", Hydrate);\n"
     return rust_code
@@ -217,7 +234,7 @@ def generate_event_enum_with_from_bytes(event_info, include_pump_fun=True):
         for pf_event in PUMP_FUN_EVENTS:
             if pf_event['variant'] in ['PfTrade', 'PfMigration']:
                 rust_code += f"            EventData::{pf_event['variant']}(..) => Source::Pf,\n"
-            elif pf_event['variant'] in ['PaBuy', 'PaSell']:
+            elif pf_event['variant'] in ['PaBuy', 'PaSell', 'PaDeposit', 'PaWithdraw']:
                 rust_code += f"            EventData::{pf_event['variant']}(..) => Source::Pa,\n"
 
     # Add match arm for SolPriceEvent
@@ -362,7 +379,7 @@ def main(script_name, input_file):
     rust_code += "use borsh::BorshDeserialize;\n"
     rust_code += "use crate::pf;  // Import Pump.fun events and discriminators\n"
     rust_code += "use crate::types::public::Public;\n"
-    rust_code += "use crate::types::source::Source;\n"
+    rust_code += "use crate::types::source::{PriceSource, Source};\n"
     rust_code += "use crate::types::signature::Signature;\n"
     rust_code += "use serde::ser::SerializeStruct;\n"
     rust_code += "use serde::{de, Deserialize, Deserializer, Serialize, Serializer};\n"
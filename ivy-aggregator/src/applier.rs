@@ -10,7 +10,7 @@ use crate::state::State;
 use crate::types::event::{Event, EventData, InitializeEvent, SolPriceEvent}; // CHANGED
 use crate::types::jsonl::{JsonReader, JsonWriter};
 use crate::types::signature::Signature;
-use crate::types::source::Source;
+use crate::types::source::{PriceSource, Source};
 use crate::util::unix_timestamp;
 
 // Generic Cursor struct to handle file operations for any serializable type T
@@ -75,10 +75,10 @@ pub struct Applier {
     ivy_cursor: Cursor<Signature>,
     pf_cursor: Cursor<Signature>,
     pa_cursor: Cursor<Signature>,
-    fx_cursor: Cursor<(f64, u64)>,
+    fx_cursor: Cursor<Vec<(PriceSource, f64, u64)>>,
 
     // SOL price caching and dedupe
-    last_fx_price: Option<(f64, u64)>, // cached from FX feed (price, ts)
+    last_fx_prices: Vec<(PriceSource, f64, u64)>, // cached from FX feed, one per source
 
     // For fetching by others
     ivy_last_signature: Option<Signature>,
@@ -94,7 +94,7 @@ impl Applier {
         ivy_cursor_path: &str,
         pf_cursor_path: &str,
         pa_cursor_path: &str,
-        fx_last_price_path: &str,
+        fx_last_prices_path: &str,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let events_path = PathBuf::from(events_path);
         if let Some(parent) = events_path.parent() {
@@ -106,13 +106,13 @@ impl Applier {
         let mut ivy_cursor = Cursor::new(ivy_cursor_path)?;
         let mut pf_cursor = Cursor::new(pf_cursor_path)?;
         let mut pa_cursor = Cursor::new(pa_cursor_path)?;
-        let mut fx_cursor = Cursor::new(fx_last_price_path)?;
+        let mut fx_cursor = Cursor::new(fx_last_prices_path)?;
 
         // Read last signatures (could be None if file is empty)
         let ivy_last_signature = ivy_cursor.read()?;
         let pf_last_signature = pf_cursor.read()?;
         let pa_last_signature = pa_cursor.read()?;
-        let last_fx_price = fx_cursor.read()?;
+        let last_fx_prices = fx_cursor.read()?.unwrap_or_default();
 
         // Load existing events and apply them to the state
         let mut reader = JsonReader::<Event>::new(&events_path)?;
@@ -136,10 +136,6 @@ impl Applier {
             }
         }
 
-        // Update hot game list
-        let hl = sg.assets.calculate_hot_list(&sg.games, &sg.syncs);
-        sg.assets.update_hot_list(hl);
-
         // Signal initialization complete to state components
         sg.on_event(&Event {
             data: EventData::Initialize(InitializeEvent {}),
@@ -157,7 +153,7 @@ impl Applier {
             pf_cursor,
             pa_cursor,
             fx_cursor,
-            last_fx_price,
+            last_fx_prices,
             ivy_last_signature,
             pf_last_signature,
             pa_last_signature,
@@ -189,20 +185,20 @@ impl Applier {
         let mut state = self.state.write().unwrap();
 
         for event in events {
-            if let EventData::SolPrice(SolPriceEvent { price, .. }) = event.data {
-                self.last_fx_price = Some((price, event.timestamp));
+            if let EventData::SolPrice(SolPriceEvent { price, source }) = event.data {
+                self.last_fx_prices.retain(|&(s, _, _)| s != source);
+                self.last_fx_prices.push((source, price, event.timestamp));
                 continue;
             }
 
-            if let Some((price, ts)) = self.last_fx_price {
+            for (source, price, ts) in self.last_fx_prices.drain(..) {
                 let fx_event = Event {
-                    data: EventData::SolPrice(SolPriceEvent { price }),
+                    data: EventData::SolPrice(SolPriceEvent { price, source }),
                     signature: Signature::zero(),
                     timestamp: ts,
                 };
                 state.on_event(&fx_event);
                 write_queue.push(fx_event);
-                self.last_fx_price = None;
             }
 
             let source = event.data.get_source();
@@ -233,9 +229,7 @@ impl Applier {
         if let Some(sig) = &self.pf_last_signature {
             self.pf_cursor.write(sig)?;
         }
-        if let Some(val) = self.last_fx_price {
-            self.fx_cursor.write(&val)?;
-        }
+        self.fx_cursor.write(&self.last_fx_prices)?;
 
         Ok(())
     }
@@ -1,89 +1,253 @@
-use std::sync::mpsc::{Receiver, Sender};
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
 use std::{thread, time::Duration};
 
 use serde::Deserialize;
+use serde_json::Value;
 use ureq::Agent;
 
+use crate::net::public_agent;
+use crate::state::components::hydrate::MAX_RUNNING;
+use crate::state::State;
 use crate::types::event::{Event, EventData, HydrateEvent};
 use crate::types::public::Public;
 use crate::types::signature::Signature;
 use crate::util::unix_timestamp;
 
-/// Worker/process that fetches metadata (with infinite retry)
-/// and emits HydrateEvent into the normal events pipeline.
-pub struct Hydrator {
-    rx: Receiver<(Public, String)>, // (asset, metadata_url)
-    events_tx: Sender<Vec<Event>>,  // events stream (same channel used by Retriever->Applier)
-    api_url: String,                // backend base URL (e.g. http://127.0.0.1:4000)
-    agent: Agent,                   // HTTP client
+// Longest time to wait for new jobs when nothing is due
+const IDLE_WAIT: Duration = Duration::from_secs(60);
+// Time allowed to fetch a metadata URL
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Metadata fetching settings
+#[derive(Clone)]
+pub struct MetadataConfig {
+    /// Gateway that `ipfs://` URIs are fetched through
+    pub ipfs_gateway: String,
+    /// Gateway that `ar://` URIs are fetched through
+    pub arweave_gateway: String,
+    /// Largest metadata document accepted, in bytes
+    pub max_bytes: u64,
+    /// Ask the backend's `/web-metadata` for metadata that can't be fetched directly,
+    /// such as documents served without a JSON content type
+    pub backend_fallback: bool,
+    /// Fetch from the local machine and private networks, and follow redirects,
+    /// for local development or a gateway on the local network
+    pub allow_private_hosts: bool,
 }
 
-impl Hydrator {
-    pub fn new(
-        api_url: String,
-        rx: Receiver<(Public, String)>,
-        events_tx: Sender<Vec<Event>>,
-        agent: Agent,
-    ) -> Self {
+impl Default for MetadataConfig {
+    fn default() -> Self {
         Self {
-            rx,
-            events_tx,
-            api_url,
-            agent,
+            ipfs_gateway: "https://ipfs.io/ipfs".to_string(),
+            arweave_gateway: "https://arweave.net".to_string(),
+            max_bytes: 256 * 1024,
+            backend_fallback: true,
+            allow_private_hosts: false,
         }
     }
+}
 
-    pub fn run(self) {
-        let Hydrator {
-            rx,
-            events_tx,
-            api_url,
-            agent,
-        } = self;
-
-        while let Ok((asset, metadata_url)) = rx.recv() {
-            // Infinite retry loop
-            loop {
-                match Self::fetch_metadata(&agent, &api_url, &metadata_url) {
-                    Ok(metadata) => {
-                        // metadata is None means invalid (non-retryable),
-                        // Some means we got valid data
-                        let (description, icon_url) =
-                            metadata.unwrap_or((String::new(), String::new()));
-
-                        let event = Event {
-                            data: EventData::Hydrate(HydrateEvent {
-                                asset,
-                                metadata_url: metadata_url.clone(),
-                                description,
-                                icon_url,
-                            }),
-                            signature: Signature::zero(),
-                            timestamp: unix_timestamp(),
-                        };
-                        if let Err(e) = events_tx.send(vec![event]) {
-                            eprintln!("Hydrator: failed to send HydrateEvent to events tx: {}", e);
-                        }
-                        break;
-                    }
-                    Err(e) => {
-                        // Only retry on actual errors (network issues, etc.)
-                        eprintln!(
-                            "Hydrator: failed to fetch metadata for {}: {}. Retrying...",
-                            asset, e
-                        );
-                        thread::sleep(Duration::from_millis(500));
-                    }
-                }
+impl MetadataConfig {
+    /// Read the config from environment variables,
+    /// using the default for each one that's missing or invalid
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let max_bytes = match std::env::var("METADATA_MAX_BYTES") {
+            Ok(v) => v.parse().unwrap_or_else(|_| {
+                eprintln!("warning: Can't parse METADATA_MAX_BYTES, using default");
+                default.max_bytes
+            }),
+            Err(_) => default.max_bytes,
+        };
+        Self {
+            ipfs_gateway: std::env::var("IPFS_GATEWAY").unwrap_or(default.ipfs_gateway),
+            arweave_gateway: std::env::var("ARWEAVE_GATEWAY").unwrap_or(default.arweave_gateway),
+            max_bytes,
+            backend_fallback: std::env::var("METADATA_BACKEND_FALLBACK")
+                .map_or(default.backend_fallback, |v| v != "false"),
+            allow_private_hosts: std::env::var("METADATA_ALLOW_PRIVATE_HOSTS")
+                .is_ok_and(|v| v == "true"),
+        }
+    }
+}
+
+/// What's taken from a metadata document, with empty strings for what it doesn't have
+#[derive(Default)]
+struct Metadata {
+    description: String,
+    image: String,
+    website: String,
+    twitter: String,
+    telegram: String,
+}
+
+/// The first non-empty string among `keys` of `value`, or of its `extensions`
+fn string_field(value: &Value, keys: &[&str]) -> String {
+    [Some(value), value.get("extensions")]
+        .into_iter()
+        .flatten()
+        .flat_map(|object| keys.iter().filter_map(|&key| object.get(key)))
+        .filter_map(Value::as_str)
+        .map(str::trim)
+        .find(|s| !s.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Fetches metadata documents, directly or through the backend
+#[derive(Clone)]
+struct MetadataFetcher {
+    config: MetadataConfig,
+    api_url: String, // backend base URL (e.g. http://127.0.0.1:4000)
+    // HTTP client for metadata URLs, which come from on-chain data, so it only
+    // connects to public addresses and doesn't follow redirects. Both clients
+    // have a timeout so that slow hosts free their workers.
+    agent: Agent,
+    backend_agent: Agent, // HTTP client for the backend
+}
+
+impl MetadataFetcher {
+    /// Fetch the metadata at `url`. `None` means there's no usable metadata there
+    /// (non-retryable), and errors are worth retrying.
+    fn fetch(&self, url: &str) -> Result<Option<Metadata>, Box<dyn std::error::Error>> {
+        let native = self.fetch_native(url);
+        if !self.config.backend_fallback || matches!(native, Ok(Some(_))) {
+            return native;
+        }
+        match self.fetch_backend(url) {
+            Ok(Some(metadata)) => Ok(Some(metadata)),
+            Ok(None) => native,
+            // Retry unless the native error already is
+            Err(e) => match native {
+                Ok(None) => Err(e),
+                native => native,
+            },
+        }
+    }
+
+    /// Resolve `ipfs://` and `ar://` URIs through their gateways, keeping HTTP(S) URLs
+    /// as they are, or `None` for anything else. Whether the host is public is
+    /// checked by the agent, on the addresses it connects to.
+    fn resolve(&self, url: &str) -> Option<String> {
+        let url = url.trim();
+        if let Some(path) = url.strip_prefix("ipfs://") {
+            let path = path.strip_prefix("ipfs/").unwrap_or(path);
+            let gateway = self.config.ipfs_gateway.trim_end_matches('/');
+            return Some(format!("{}/{}", gateway, path));
+        }
+        if let Some(path) = url.strip_prefix("ar://") {
+            let gateway = self.config.arweave_gateway.trim_end_matches('/');
+            return Some(format!("{}/{}", gateway, path));
+        }
+        let (scheme, rest) = url.split_once("://")?;
+        if !scheme.eq_ignore_ascii_case("https") && !scheme.eq_ignore_ascii_case("http") {
+            return None;
+        }
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let host_port = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let host = match host_port.rsplit_once(':') {
+            // Not the colons of an IPv6 address
+            Some((host, port)) if !port.contains(']') => host,
+            _ => host_port,
+        };
+        if host.is_empty() {
+            return None;
+        }
+        Some(url.to_string())
+    }
+
+    fn fetch_native(&self, url: &str) -> Result<Option<Metadata>, Box<dyn std::error::Error>> {
+        let Some(resolved) = self.resolve(url) else {
+            return Ok(None);
+        };
+        let resp = match self
+            .agent
+            .get(&resolved)
+            .header("Accept", "application/json")
+            .call()
+        {
+            Ok(resp) => resp,
+            // Client errors won't go away on retry, except timeouts and rate limiting
+            Err(ureq::Error::StatusCode(code))
+                if (400..500).contains(&code) && code != 408 && code != 429 =>
+            {
+                return Ok(None);
+            }
+            // The host only resolves to private addresses
+            Err(ureq::Error::Io(e)) if e.kind() == io::ErrorKind::PermissionDenied => {
+                return Ok(None);
             }
+            Err(e) => return Err(e.into()),
+        };
+        if resp.status().is_redirection() {
+            return Err(
+                format!("HTTP status {} (redirects aren't followed)", resp.status()).into(),
+            );
+        }
+
+        // Gateways often serve JSON as plain text or bytes, but never as HTML or images
+        let content_type = resp
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        if !(mime.is_empty()
+            || mime.ends_with("json")
+            || mime == "text/plain"
+            || mime == "application/octet-stream")
+        {
+            return Ok(None);
         }
+        let length = resp
+            .headers()
+            .get("content-length")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        if length.is_some_and(|length| length > self.config.max_bytes) {
+            return Ok(None);
+        }
+
+        let mut body = resp.into_body();
+        let text = match body
+            .with_config()
+            .limit(self.config.max_bytes)
+            .read_to_string()
+        {
+            Ok(text) => text,
+            Err(ureq::Error::BodyExceedsLimit(_)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let Ok(value) = serde_json::from_str::<Value>(&text) else {
+            return Ok(None);
+        };
+        if !value.is_object() {
+            return Ok(None);
+        }
+
+        let image = string_field(&value, &["image", "image_url", "icon"]);
+        Ok(Some(Metadata {
+            description: string_field(&value, &["description"]),
+            // Images on IPFS or Arweave need a gateway to be shown
+            image: self.resolve(&image).unwrap_or_default(),
+            website: string_field(&value, &["website", "external_url"]),
+            twitter: string_field(&value, &["twitter", "x"]),
+            telegram: string_field(&value, &["telegram"]),
+        }))
     }
 
-    fn fetch_metadata(
-        agent: &Agent,
-        api_url: &str,
-        url: &str,
-    ) -> Result<Option<(String, String)>, Box<dyn std::error::Error>> {
+    /// Fetch metadata through the backend's `/web-metadata`,
+    /// which also reads it from web pages but only gives the description and image
+    fn fetch_backend(&self, url: &str) -> Result<Option<Metadata>, Box<dyn std::error::Error>> {
         #[derive(Deserialize)]
         struct WebMetadata {
             description: Option<String>,
@@ -97,8 +261,12 @@ impl Hydrator {
             Err { msg: String },
         }
 
-        let resp = agent
-            .post(&format!("{}/web-metadata", api_url.trim_end_matches('/')))
+        let resp = self
+            .backend_agent
+            .post(&format!(
+                "{}/web-metadata",
+                self.api_url.trim_end_matches('/')
+            ))
             .send_json(serde_json::json!({ "url": url }))?;
 
         let body = resp.into_body().read_json::<ApiResponse>()?;
@@ -112,10 +280,11 @@ impl Hydrator {
                 data: Some(metadata),
             } => {
                 // Valid response with metadata
-                Ok(Some((
-                    metadata.description.unwrap_or_default(),
-                    metadata.image.unwrap_or_default(),
-                )))
+                Ok(Some(Metadata {
+                    description: metadata.description.unwrap_or_default(),
+                    image: metadata.image.unwrap_or_default(),
+                    ..Default::default()
+                }))
             }
             ApiResponse::Err { msg } => {
                 // Error from upstream (retryable)
@@ -124,3 +293,149 @@ impl Hydrator {
         }
     }
 }
+
+/// Worker pool that fetches metadata for the jobs queued in the state's
+/// hydrate component and emits HydrateEvent into the normal events pipeline.
+/// Failed jobs are retried by the component with exponential backoff.
+pub struct Hydrator {
+    rx: Receiver<()>,              // wakeups, when jobs are queued or finish
+    tx: Sender<()>,                // for workers to wake the pool when they finish
+    state: Arc<State>,             // holds the job queue
+    events_tx: Sender<Vec<Event>>, // events stream (same channel used by Retriever->Applier)
+    fetcher: MetadataFetcher,
+}
+
+impl Hydrator {
+    pub fn new(
+        api_url: String,
+        config: MetadataConfig,
+        rx: Receiver<()>,
+        tx: Sender<()>,
+        state: Arc<State>,
+        events_tx: Sender<Vec<Event>>,
+    ) -> Self {
+        let backend_agent: Agent = Agent::config_builder()
+            .timeout_global(Some(REQUEST_TIMEOUT))
+            .build()
+            .into();
+        let agent = if config.allow_private_hosts {
+            backend_agent.clone()
+        } else {
+            public_agent(REQUEST_TIMEOUT)
+        };
+        Self {
+            rx,
+            tx,
+            state,
+            events_tx,
+            fetcher: MetadataFetcher {
+                config,
+                api_url,
+                agent,
+                backend_agent,
+            },
+        }
+    }
+
+    pub fn run(self) {
+        // Workers share a channel of (asset, metadata_url) jobs
+        let (jobs_tx, jobs_rx) = mpsc::channel::<(Public, String)>();
+        let jobs_rx = Arc::new(Mutex::new(jobs_rx));
+        for _ in 0..MAX_RUNNING {
+            let jobs_rx = jobs_rx.clone();
+            let tx = self.tx.clone();
+            let state = self.state.clone();
+            let events_tx = self.events_tx.clone();
+            let fetcher = self.fetcher.clone();
+            thread::spawn(move || loop {
+                let job = jobs_rx.lock().unwrap().recv();
+                let Ok((asset, metadata_url)) = job else {
+                    return;
+                };
+                Self::hydrate(&state, &events_tx, &fetcher, asset, metadata_url);
+                let _ = tx.send(());
+            });
+        }
+
+        loop {
+            let now = unix_timestamp();
+            let (due, next_due, next_flush) = {
+                let mut data = self.state.write().unwrap();
+                let next_flush = data.hydrator.flush(now);
+                let (due, next_due) = data.hydrator.take_due(now);
+                (due, next_due, next_flush)
+            };
+            for job in due {
+                if jobs_tx.send(job).is_err() {
+                    return;
+                }
+            }
+
+            // Wait for a job to be queued or finish, for the next one to be due,
+            // or for the queue to be written
+            let wait = [next_due, next_flush]
+                .into_iter()
+                .flatten()
+                .min()
+                .map(|t| Duration::from_secs(t.saturating_sub(now)))
+                .unwrap_or(IDLE_WAIT)
+                .min(IDLE_WAIT);
+            match self.rx.recv_timeout(wait) {
+                Ok(()) => {
+                    // Coalesce wakeups
+                    while self.rx.try_recv().is_ok() {}
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+
+    /// Fetch a job's metadata and record the outcome
+    fn hydrate(
+        state: &State,
+        events_tx: &Sender<Vec<Event>>,
+        fetcher: &MetadataFetcher,
+        asset: Public,
+        metadata_url: String,
+    ) {
+        match fetcher.fetch(&metadata_url) {
+            Ok(metadata) => {
+                // metadata is None means invalid (non-retryable),
+                // Some means we got valid data
+                let metadata = metadata.unwrap_or_default();
+
+                let event = Event {
+                    data: EventData::Hydrate(HydrateEvent {
+                        asset,
+                        metadata_url: metadata_url.clone(),
+                        description: metadata.description,
+                        icon_url: metadata.image,
+                        website: metadata.website,
+                        twitter: metadata.twitter,
+                        telegram: metadata.telegram,
+                    }),
+                    signature: Signature::zero(),
+                    timestamp: unix_timestamp(),
+                };
+                if let Err(e) = events_tx.send(vec![event]) {
+                    eprintln!("Hydrator: failed to send HydrateEvent to events tx: {}", e);
+                }
+                state
+                    .write()
+                    .unwrap()
+                    .hydrator
+                    .on_success(asset, &metadata_url);
+            }
+            Err(e) => {
+                // Only retry on actual errors (network issues, etc.)
+                eprintln!("Hydrator: failed to fetch metadata for {}: {}", asset, e);
+                state
+                    .write()
+                    .unwrap()
+                    .hydrator
+                    .on_failure(asset, &metadata_url, e.to_string());
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 mod applier;
 mod hydrator;
+mod net;
 mod pf;
 mod pricer;
 mod retriever;
@@ -11,24 +12,30 @@ mod state;
 mod types;
 mod util;
 mod volume;
+mod webhooker;
 
 use crate::applier::Applier;
-use crate::hydrator::Hydrator;
+use crate::hydrator::{Hydrator, MetadataConfig};
 use crate::pricer::Pricer;
 use crate::retriever::Retriever;
+use crate::routes::limits::LimitsConfig;
 use crate::scanner::Scanner;
 use crate::server::Server;
-use crate::state::StateData;
+use crate::state::components::pnl::CostBasis;
+use crate::state::components::prices::PriceConfig;
+use crate::state::hot::HotConfig;
+use crate::state::{StateConfig, StateData};
 use crate::types::public::Public;
+use crate::webhooker::Webhooker;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::process::exit;
 use std::sync::{mpsc, RwLock};
 use std::thread;
-use std::{str::FromStr, sync::Arc, time::Duration};
+use std::{str::FromStr, sync::Arc};
 use ureq::Agent;
 
-// Hot list update interval
-const HOT_LIST_UPDATE_INTERVAL: Duration = Duration::from_secs(10);
+const EVENTS_PATH: &str = "./priv/events.jsonl";
 
 #[tokio::main]
 async fn main() {
@@ -63,21 +70,47 @@ async fn main() {
     let (scanner_tx, scanner_rx) = mpsc::channel();
     let (retriever_tx, retriever_rx) = mpsc::channel();
 
-    // NEW: hydration channel (asset, metadata_url)
-    let (hydration_tx, hydration_rx) = mpsc::channel::<(Public, String)>();
+    // Hydration wakeups, when jobs are queued or finish
+    let (hydration_tx, hydration_rx) = mpsc::channel::<()>();
+
+    // Webhook channel, from state to the webhook worker
+    let (webhook_tx, webhook_rx) = mpsc::channel();
 
     // Create state (CHANGED: pass hydration_tx)
-    let state = Arc::new(RwLock::new(StateData::new(hydration_tx)));
+    let state_config = StateConfig {
+        events_path: PathBuf::from(EVENTS_PATH),
+        webhooks_path: "./priv/webhooks.json".to_string(),
+        alerts_path: "./priv/alerts.json".to_string(),
+        hydration_queue_path: "./priv/hydration_queue.json".to_string(),
+        sol_corrections_path: "./priv/sol_price_corrections.json".to_string(),
+        hot: HotConfig::from_env(),
+        cost_basis: CostBasis::from_env(),
+        prices: PriceConfig::from_env(),
+    };
+    let state = match StateData::new(hydration_tx.clone(), webhook_tx, state_config) {
+        Ok(x) => Arc::new(RwLock::new(x)),
+        Err(err) => {
+            eprintln!("Can't create state: {}", err);
+            exit(1);
+        }
+    };
+
+    // Start webhook worker
+    let webhooker = Webhooker::new(webhook_rx, state.clone(), "./priv/webhook_outbox.json")
+        .expect("Failed to create webhook worker");
+    thread::spawn(move || {
+        webhooker.run();
+    });
 
     // Create applier first
     let applier = Applier::new(
         state.clone(),
         retriever_rx,
-        "./priv/events.jsonl",
+        EVENTS_PATH,
         "./priv/ivy_cursor.json",
         "./priv/pf_cursor.json",
         "./priv/pa_cursor.json",
-        "./priv/fx_last_price.json",
+        "./priv/fx_last_prices.json",
     )
     .expect("Failed to create applier");
 
@@ -93,12 +126,14 @@ async fn main() {
 
     let agent = Agent::new_with_defaults();
 
-    // NEW: Hydrator worker - fetch web metadata, emit HydrateEvent
+    // Hydrator workers - fetch web metadata of queued jobs, emit HydrateEvent
     let hydrator = Hydrator::new(
         api_url.clone(),
+        MetadataConfig::from_env(),
         hydration_rx,
+        hydration_tx,
+        state.clone(),
         retriever_tx.clone(),
-        agent.clone(),
     );
     thread::spawn(move || {
         hydrator.run();
@@ -144,34 +179,23 @@ async fn main() {
         pa_scanner.run();
     });
 
-    // Create and start retriever
-    let retriever = Retriever::new(&rpc_url, scanner_rx, retriever_tx.clone(), agent.clone());
+    // Create and start the SOL price fetcher, queueing a first price
+    // before any trades so that they aren't valued at $0
+    let price_file = std::env::var("SOL_PRICE_FILE").ok().map(PathBuf::from);
+    let pricer = Pricer::new(api_url, price_file, retriever_tx.clone(), agent.clone());
+    pricer.fetch_prices();
     thread::spawn(move || {
-        retriever.run();
-    });
-
-    // Spawn the hot list update task
-    let state_clone = state.clone();
-    thread::spawn(move || loop {
-        thread::sleep(HOT_LIST_UPDATE_INTERVAL);
-        // 1. Acquire read lock and get readonly reference to game list
-        let hot_list = {
-            let sr = state_clone.read().unwrap();
-            sr.assets.calculate_hot_list(&sr.games, &sr.syncs)
-        };
-        // 2. Acquire write lock and update the hot game list
-        let mut sw = state_clone.write().unwrap();
-        sw.assets.update_hot_list(hot_list);
+        pricer.run();
     });
 
-    // Create and start the SOL price fetcher
-    let pricer = Pricer::new(api_url, retriever_tx, agent.clone());
+    // Create and start retriever
+    let retriever = Retriever::new(&rpc_url, scanner_rx, retriever_tx, agent.clone());
     thread::spawn(move || {
-        pricer.run();
+        retriever.run();
     });
 
     // Create and start the HTTP server
-    let server = Server::new(listen_addr, state);
+    let server = Server::new(listen_addr, state, LimitsConfig::from_env());
     if let Err(e) = server.run().await {
         eprintln!("Server error: {}", e);
         exit(1);
@@ -0,0 +1,113 @@
+use std::io;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::time::Duration;
+
+use ureq::config::Config;
+use ureq::http::Uri;
+use ureq::unversioned::resolver::{DefaultResolver, ResolvedSocketAddrs, Resolver};
+use ureq::unversioned::transport::{DefaultConnector, NextTimeout};
+use ureq::Agent;
+
+/// Whether `ip` is on the public internet, rather than the local machine,
+/// a private network, or a range reserved for special uses (like the
+/// link-local cloud metadata address 169.254.169.254)
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0 // "this network"
+                || (a == 100 && (64..128).contains(&b)) // carrier-grade NAT
+                || (a == 192 && b == 0) // IETF protocol assignments
+                || (a == 198 && (18..20).contains(&b)) // benchmarking
+                || a >= 240) // reserved
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(ip));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                || (first & 0xfe00) == 0xfc00 // unique local
+                || (first & 0xffc0) == 0xfe80 // link-local
+                || first == 0x64 // IPv4/IPv6 translation
+                || first == 0x2002) // 6to4, which embeds an IPv4 address
+        }
+    }
+}
+
+/// Check that `url` is an http(s) URL whose host only resolves to public addresses.
+/// This resolves the host, so it blocks.
+pub fn check_public_url(url: &str) -> Result<(), String> {
+    let uri: Uri = url.parse().map_err(|_| "Invalid URL".to_string())?;
+    let port = match uri.scheme_str() {
+        Some("http") => 80,
+        Some("https") => 443,
+        _ => return Err("URL must start with http:// or https://".to_string()),
+    };
+    let Some(host) = uri.host().filter(|h| !h.is_empty()) else {
+        return Err("URL has no host".to_string());
+    };
+    // IPv6 literals are bracketed in URLs
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<_> = (host, uri.port_u16().unwrap_or(port))
+        .to_socket_addrs()
+        .map_err(|_| format!("Can't resolve {}", host))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(format!("Can't resolve {}", host));
+    }
+    if !addrs.iter().all(|addr| is_public_ip(addr.ip())) {
+        return Err(format!("{} is not a public host", host));
+    }
+    Ok(())
+}
+
+/// Resolves hosts like the default resolver, but drops non-public addresses.
+/// Connections are made to the addresses it returns, so a host can't pass a
+/// check with one address and then be connected to at another.
+#[derive(Debug, Default)]
+struct PublicResolver(DefaultResolver);
+
+impl Resolver for PublicResolver {
+    fn resolve(
+        &self,
+        uri: &Uri,
+        config: &Config,
+        timeout: NextTimeout,
+    ) -> Result<ResolvedSocketAddrs, ureq::Error> {
+        let mut addrs = self.empty();
+        for addr in self.0.resolve(uri, config, timeout)?.iter() {
+            if is_public_ip(addr.ip()) {
+                addrs.push(*addr);
+            }
+        }
+        if addrs.is_empty() {
+            return Err(ureq::Error::Io(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "host doesn't resolve to a public address",
+            )));
+        }
+        Ok(addrs)
+    }
+}
+
+/// An HTTP client for user-supplied URLs: it only connects to public addresses,
+/// doesn't follow redirects (3xx responses are returned as is), and doesn't go
+/// through a proxy, which would resolve hosts itself.
+pub fn public_agent(timeout: Duration) -> Agent {
+    let config = Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .max_redirects(0)
+        .proxy(None)
+        .build();
+    Agent::with_parts(config, DefaultConnector::new(), PublicResolver::default())
+}
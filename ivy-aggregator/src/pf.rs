@@ -15,6 +15,8 @@ pub const PF_TRADE_EVENT_TAG: u64 = 0xee61e64ed37fdbbd;
 pub const PF_MIGRATE_EVENT_TAG: u64 = 0x94ea945cb95de9bd;
 pub const PA_BUY_EVENT_TAG: u64 = 0x7777f52c1f52f467;
 pub const PA_SELL_EVENT_TAG: u64 = 0x2adc03a50a372f3e;
+pub const PA_DEPOSIT_EVENT_TAG: u64 = 0x906b8e1f533df878;
+pub const PA_WITHDRAW_EVENT_TAG: u64 = 0xc0472ca01a850916;
 
 #[derive(BorshDeserialize, Serialize, Deserialize, Debug, Clone, Copy)]
 pub struct PfTradeEvent {
@@ -111,3 +113,43 @@ pub struct PaSellEvent {
     pub coin_creator_fee_basis_points: u64,
     pub coin_creator_fee: u64,
 }
+
+#[derive(BorshDeserialize, Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct PaDepositEvent {
+    pub timestamp: i64,
+    pub lp_token_amount_out: u64,
+    pub max_base_amount_in: u64,
+    pub max_quote_amount_in: u64,
+    pub user_base_token_reserves: u64,
+    pub user_quote_token_reserves: u64,
+    pub pool_base_token_reserves: u64,
+    pub pool_quote_token_reserves: u64,
+    pub base_amount_in: u64,
+    pub quote_amount_in: u64,
+    pub lp_mint_supply: u64,
+    pub pool: Public,
+    pub user: Public,
+    pub user_base_token_account: Public,
+    pub user_quote_token_account: Public,
+    pub user_pool_token_account: Public,
+}
+
+#[derive(BorshDeserialize, Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct PaWithdrawEvent {
+    pub timestamp: i64,
+    pub lp_token_amount_in: u64,
+    pub min_base_amount_out: u64,
+    pub min_quote_amount_out: u64,
+    pub user_base_token_reserves: u64,
+    pub user_quote_token_reserves: u64,
+    pub pool_base_token_reserves: u64,
+    pub pool_quote_token_reserves: u64,
+    pub base_amount_out: u64,
+    pub quote_amount_out: u64,
+    pub lp_mint_supply: u64,
+    pub pool: Public,
+    pub user: Public,
+    pub user_base_token_account: Public,
+    pub user_quote_token_account: Public,
+    pub user_pool_token_account: Public,
+}
@@ -1,5 +1,7 @@
 use crate::types::event::{Event, EventData, SolPriceEvent};
 use crate::types::signature::Signature;
+use crate::types::source::PriceSource;
+use std::path::PathBuf;
 use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
@@ -9,38 +11,72 @@ const SOL_PRICE_INTERVAL: Duration = Duration::from_secs(60);
 
 pub struct Pricer {
     api_url: String,
+    // File with a fixed SOL price, for tests
+    price_file: Option<PathBuf>,
     retriever_tx: mpsc::Sender<Vec<Event>>,
     agent: Agent,
 }
 
 impl Pricer {
-    pub fn new(api_url: String, retriever_tx: mpsc::Sender<Vec<Event>>, agent: Agent) -> Self {
+    pub fn new(
+        api_url: String,
+        price_file: Option<PathBuf>,
+        retriever_tx: mpsc::Sender<Vec<Event>>,
+        agent: Agent,
+    ) -> Self {
         Self {
             api_url,
+            price_file,
             retriever_tx,
             agent,
         }
     }
 
+    /// Fetch prices every 60 seconds, after the initial `fetch_prices`
     pub fn run(self) {
-        // Fetch initial price
-        self.fetch_and_send_price();
-
-        // Then fetch every 60 seconds
         loop {
             thread::sleep(SOL_PRICE_INTERVAL);
-            self.fetch_and_send_price();
+            self.fetch_prices();
+        }
+    }
+
+    /// Fetch the SOL price from each source and send the ones that succeed
+    pub fn fetch_prices(&self) {
+        if let Some(price) = self.fetch_backend_price() {
+            self.send_price(price, PriceSource::Backend);
+        }
+        if let Some(path) = &self.price_file {
+            match std::fs::read_to_string(path).map(|s| s.trim().parse::<f64>()) {
+                Ok(Ok(price)) => self.send_price(price, PriceSource::File),
+                Ok(Err(e)) => eprintln!("Pricer: Invalid price in {}: {}", path.display(), e),
+                Err(e) => eprintln!("Pricer: Failed to read {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    fn send_price(&self, price: f64, source: PriceSource) {
+        let event = Event {
+            data: EventData::SolPrice(SolPriceEvent { price, source }),
+            signature: Signature::zero(), // No signature for price events
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        };
+
+        if let Err(e) = self.retriever_tx.send(vec![event]) {
+            eprintln!("Pricer: Failed to send price event: {}", e);
         }
     }
 
-    fn fetch_and_send_price(&self) {
+    fn fetch_backend_price(&self) -> Option<f64> {
         let url = format!("{}/sol-price", self.api_url);
 
         let response = match self.agent.get(&url).call() {
             Ok(response) => response,
             Err(e) => {
                 eprintln!("Pricer: Failed to fetch SOL price: {}", e);
-                return;
+                return None;
             }
         };
 
@@ -48,7 +84,7 @@ impl Pricer {
             Ok(json) => json,
             Err(e) => {
                 eprintln!("Pricer: Failed to parse JSON response: {}", e);
-                return;
+                return None;
             }
         };
 
@@ -56,35 +92,23 @@ impl Pricer {
             Some(status) => status,
             None => {
                 eprintln!("Pricer: Invalid response format");
-                return;
+                return None;
             }
         };
 
         if status == "ok" {
-            let price = match json.get("data").and_then(|d| d.as_f64()) {
-                Some(price) => price,
-                None => {
-                    eprintln!("Pricer: Invalid price data in response");
-                    return;
-                }
-            };
-
-            let event = Event {
-                data: EventData::SolPrice(SolPriceEvent { price }),
-                signature: Signature::zero(), // No signature for price events
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs(),
-            };
-
-            if let Err(e) = self.retriever_tx.send(vec![event]) {
-                eprintln!("Pricer: Failed to send price event: {}", e);
+            let price = json.get("data").and_then(|d| d.as_f64());
+            if price.is_none() {
+                eprintln!("Pricer: Invalid price data in response");
             }
-        } else if let Some(msg) = json.get("msg").and_then(|m| m.as_str()) {
-            eprintln!("Pricer: API error: {}", msg);
+            price
         } else {
-            eprintln!("Pricer: Unknown API error");
+            if let Some(msg) = json.get("msg").and_then(|m| m.as_str()) {
+                eprintln!("Pricer: API error: {}", msg);
+            } else {
+                eprintln!("Pricer: Unknown API error");
+            }
+            None
         }
     }
 }
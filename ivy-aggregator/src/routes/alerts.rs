@@ -0,0 +1,193 @@
+use crate::routes::auth::is_admin;
+use crate::routes::limits::ApiKey;
+use crate::routes::types::{error, success, ApiResult, AppError};
+use crate::routes::webhooks::validate_webhook;
+use crate::state::components::alerts::{AlertCondition, AlertDelivery, AlertRuleInfo};
+use crate::state::State;
+use axum::{
+    extract::{Path, Query, State as AxumState},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event as SseEvent, KeepAlive, Sse},
+    Extension, Json,
+};
+use futures::stream::Stream;
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt as _;
+
+// Longest client name we accept
+const MAX_CLIENT_LEN: usize = 64;
+// Most rules a client may register, as every trade is checked against
+// the rules of its asset while events are applied
+const MAX_RULES_PER_CLIENT: usize = 100;
+
+// Request and query parameter structs
+#[derive(Deserialize)]
+pub struct AlertRequest {
+    /// Only admins name the client, other rules belong to the caller's API key
+    client: Option<String>,
+    condition: AlertCondition,
+    delivery: AlertDelivery,
+}
+
+#[derive(Deserialize)]
+pub struct ClientParams {
+    client: Option<String>,
+}
+
+/// Who is managing alert rules
+enum Caller {
+    /// An admin, who may manage the rules of any client
+    Admin,
+    /// A client with an API key, who may only manage the rules it registered
+    Key(String),
+}
+
+impl Caller {
+    fn new(headers: &HeaderMap, api_key: Option<Extension<ApiKey>>) -> Result<Self, AppError> {
+        if is_admin(headers) {
+            return Ok(Caller::Admin);
+        }
+        match api_key {
+            Some(Extension(ApiKey(key))) => Ok(Caller::Key(key)),
+            None => Err(error(
+                "API key or admin key required",
+                StatusCode::UNAUTHORIZED,
+            )),
+        }
+    }
+
+    /// The client the caller acts as: its API key, or the one an admin names
+    fn client(self, named: Option<String>) -> Result<String, AppError> {
+        match (self, named) {
+            (Caller::Key(key), None) => Ok(key),
+            (Caller::Key(key), Some(named)) if named == key => Ok(key),
+            (Caller::Key(_), Some(_)) => Err(error(
+                "Only admins can act as another client",
+                StatusCode::FORBIDDEN,
+            )),
+            (Caller::Admin, Some(named)) => Ok(named),
+            (Caller::Admin, None) => Err(error("Client required", StatusCode::BAD_REQUEST)),
+        }
+    }
+}
+
+// Handler functions
+
+/// List the caller's alert rules. Admins list every client's rules,
+/// or those of the client they name.
+pub async fn list_alerts(
+    AxumState(state): AxumState<Arc<State>>,
+    headers: HeaderMap,
+    api_key: Option<Extension<ApiKey>>,
+    Query(params): Query<ClientParams>,
+) -> ApiResult<Vec<AlertRuleInfo>> {
+    let client = match Caller::new(&headers, api_key)? {
+        Caller::Admin => params.client,
+        caller => Some(caller.client(params.client)?),
+    };
+    let data = state.read().unwrap();
+    Ok(success(data.alerts.get_rules(client.as_deref())))
+}
+
+/// Create an alert rule for the caller
+pub async fn create_alert(
+    AxumState(state): AxumState<Arc<State>>,
+    headers: HeaderMap,
+    api_key: Option<Extension<ApiKey>>,
+    Json(request): Json<AlertRequest>,
+) -> ApiResult<AlertRuleInfo> {
+    let client = Caller::new(&headers, api_key)?.client(request.client)?;
+    if client.is_empty() || client.len() > MAX_CLIENT_LEN {
+        return Err(error(
+            &format!("Client must be 1 to {} characters", MAX_CLIENT_LEN),
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+    if request
+        .condition
+        .threshold()
+        .is_some_and(|t| !t.is_finite())
+    {
+        return Err(error("Threshold must be finite", StatusCode::BAD_REQUEST));
+    }
+    if let AlertDelivery::Webhook { url, secret } = &request.delivery {
+        validate_webhook(url, secret).await?;
+    }
+    let mut data = state.write().unwrap();
+    if data.alerts.rule_count(&client) >= MAX_RULES_PER_CLIENT {
+        return Err(error(
+            &format!(
+                "Clients can have at most {} alert rules",
+                MAX_RULES_PER_CLIENT
+            ),
+            StatusCode::TOO_MANY_REQUESTS,
+        ));
+    }
+    Ok(success(data.alerts.add_rule(
+        client,
+        request.condition,
+        request.delivery,
+    )))
+}
+
+/// Delete one of the caller's alert rules, or any rule for admins
+pub async fn delete_alert(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(id): Path<u64>,
+    headers: HeaderMap,
+    api_key: Option<Extension<ApiKey>>,
+) -> ApiResult<bool> {
+    let client = match Caller::new(&headers, api_key)? {
+        Caller::Admin => None,
+        Caller::Key(key) => Some(key),
+    };
+    let mut data = state.write().unwrap();
+    Ok(success(data.alerts.remove_rule(id, client.as_deref())))
+}
+
+/// Stream the alerts of the caller's rules with SSE delivery,
+/// or those of the client an admin names
+pub async fn stream_alerts(
+    AxumState(state): AxumState<Arc<State>>,
+    headers: HeaderMap,
+    api_key: Option<Extension<ApiKey>>,
+    Query(params): Query<ClientParams>,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, axum::Error>>>, AppError> {
+    let client = Caller::new(&headers, api_key)?.client(params.client)?;
+    // Get a receiver for the client's alerts
+    let alerts_rx = {
+        let mut data = state.write().unwrap();
+        data.alerts.subscribe(&client)
+    };
+
+    // Create the SSE stream
+    let stream = async_stream::stream! {
+        let mut rx = BroadcastStream::new(alerts_rx);
+
+        loop {
+            match rx.next().await {
+                Some(Ok(alert)) => {
+                    yield Ok(SseEvent::default()
+                        .event("alert")
+                        .data(serde_json::to_string(&alert).unwrap()));
+                }
+                Some(Err(_)) => {
+                    // Lagged receiver, continue
+                    continue;
+                }
+                None => {
+                    // Channel closed
+                    break;
+                }
+            }
+        }
+    };
+
+    Ok(Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(std::time::Duration::from_secs(30))
+            .text("keep-alive"),
+    ))
+}
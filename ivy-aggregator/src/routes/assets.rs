@@ -1,10 +1,17 @@
-use crate::routes::types::{error, success, ApiResponse, ApiResult};
-use crate::state::types::PnlResponse;
-use crate::state::State;
+use crate::routes::types::{
+    default_count, default_neighbors, error, success, ApiResponse, ApiResult, PaginationParams,
+    MAX_NEIGHBORS,
+};
+use crate::routes::volume::{expire_volume, VolumeBoardParams, VolumeRankParams};
+use crate::state::components::assets::{AssetFilter, AssetKind, AssetOrder};
+use crate::state::components::pnl::PnlSort;
+use crate::state::types::{BoardRank, HotBreakdown, PnlEntry, PnlResponse, VolumeRank};
+use crate::state::{State, StateData};
 use crate::types::asset::Asset;
 use crate::types::game::Game;
 use crate::types::public::Public;
 use crate::types::sync::Sync;
+use crate::util::unix_timestamp;
 use axum::http::StatusCode;
 use axum::{
     extract::{Path, Query, State as AxumState},
@@ -13,7 +20,6 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-// Query parameter structs
 // Query parameter structs
 #[derive(Deserialize)]
 pub struct AssetsQueryParams {
@@ -24,14 +30,22 @@ pub struct AssetsQueryParams {
     #[serde(default = "default_sort")]
     sort: String,
     q: Option<String>,
-}
-
-#[derive(Deserialize)]
-pub struct PaginationParams {
-    #[serde(default = "default_count")]
-    count: usize,
-    #[serde(default)]
-    skip: usize,
+    kind: Option<AssetKind>,
+    min_mkt_cap: Option<f32>,
+    max_mkt_cap: Option<f32>,
+    /// Minimum 24h volume, in USD
+    min_volume: Option<f32>,
+    /// Maximum 24h volume, in USD
+    max_volume: Option<f32>,
+    /// Minimum 24h change, in percent
+    min_change: Option<f32>,
+    /// Maximum 24h change, in percent
+    max_change: Option<f32>,
+    /// Minimum age, in seconds
+    min_age: Option<u64>,
+    /// Maximum age, in seconds
+    max_age: Option<u64>,
+    migrated: Option<bool>,
 }
 
 #[derive(Deserialize)]
@@ -40,12 +54,23 @@ pub struct PnlBoardParams {
     count: usize,
     #[serde(default)]
     skip: usize,
+    /// Deprecated, use `sort=realized_roi`: without a `sort`,
+    /// sort by realized ROI rather than unrealized ROI
     #[serde(default)]
     realized: bool,
+    sort: Option<PnlSort>,
+}
+
+#[derive(Deserialize)]
+pub struct PnlRankParams {
+    #[serde(default = "default_pnl_sort")]
+    sort: PnlSort,
+    #[serde(default = "default_neighbors")]
+    neighbors: usize,
 }
 
-fn default_count() -> usize {
-    20
+fn default_pnl_sort() -> PnlSort {
+    PnlSort::UnrealizedRoi
 }
 
 fn default_sort() -> String {
@@ -57,55 +82,55 @@ pub async fn list_assets(
     AxumState(state): AxumState<Arc<State>>,
     Query(params): Query<AssetsQueryParams>,
 ) -> ApiResult<Vec<Asset>> {
+    // Expire 24h volume of assets that haven't traded since,
+    // taking the write lock only when the hour has changed
+    let now = unix_timestamp();
+    if state.read().unwrap().assets.needs_clock_advance(now) {
+        state.write().unwrap().assets.advance_clock(now);
+    }
     let data = state.read().unwrap();
 
-    let assets = match params.sort.as_str() {
-        "new" | "recent" => {
-            if let Some(q) = params.q {
-                data.assets.search_recent_assets_by_name(
-                    &data.games,
-                    &data.syncs,
-                    &q,
-                    params.count,
-                    params.skip,
-                )
-            } else {
-                data.assets
-                    .get_recent_assets(&data.games, &data.syncs, params.count, params.skip)
-            }
-        }
-        "top" => {
-            if let Some(q) = params.q {
-                data.assets.search_top_assets_by_name(
-                    &data.games,
-                    &data.syncs,
-                    &q,
-                    params.count,
-                    params.skip,
-                )
-            } else {
-                data.assets
-                    .get_top_assets(&data.games, &data.syncs, params.count, params.skip)
-            }
-        }
-        "hot" | "trending" => {
-            if params.q.is_some() {
-                return Err(error(
-                    "Search ('q') is not supported for 'hot' sort",
-                    StatusCode::BAD_REQUEST,
-                ));
-            }
-            data.assets
-                .get_hot_assets(&data.games, &data.syncs, params.count, params.skip)
-        }
+    let order = match params.sort.as_str() {
+        "new" | "recent" => AssetOrder::Recent,
+        "top" => AssetOrder::Top,
+        "hot" | "trending" => AssetOrder::Hot,
+        "volume" => AssetOrder::Volume,
+        "gainers" => AssetOrder::Gainers,
+        "losers" => AssetOrder::Losers,
+        "last_trade" => AssetOrder::LastTrade,
+        "holders" => AssetOrder::Holders,
+        "progress" => AssetOrder::Progress,
         _ => {
             return Err(error(
-                "Invalid sort parameter. Use 'new', 'top', or 'hot'",
+                "Invalid sort parameter. Use 'new', 'top', 'hot', 'volume', 'gainers', 'losers', 'last_trade', 'holders', or 'progress'",
                 StatusCode::BAD_REQUEST,
             ))
         }
     };
 
+    let filter = AssetFilter {
+        query: params.q,
+        kind: params.kind,
+        min_mkt_cap_usd: params.min_mkt_cap,
+        max_mkt_cap_usd: params.max_mkt_cap,
+        min_volume_24h_usd: params.min_volume,
+        max_volume_24h_usd: params.max_volume,
+        min_change_pct_24h: params.min_change,
+        max_change_pct_24h: params.max_change,
+        min_age: params.min_age,
+        max_age: params.max_age,
+        migrated: params.migrated,
+    };
+
+    let assets = data.assets.list_assets(
+        &data.games,
+        &data.syncs,
+        order,
+        &filter,
+        params.count,
+        params.skip,
+    );
+
     Ok(success(assets))
 }
 
@@ -118,36 +143,122 @@ pub async fn assets_count(AxumState(state): AxumState<Arc<State>>) -> Json<ApiRe
 pub async fn volume_board(
     AxumState(state): AxumState<Arc<State>>,
     Path(address): Path<Public>,
-    Query(params): Query<PaginationParams>,
+    Query(params): Query<VolumeBoardParams>,
 ) -> Json<ApiResponse<Vec<crate::state::types::VlbEntry>>> {
+    expire_volume(&state);
     let data = state.read().unwrap();
     success(
         data.volume
-            .query_volume_lb(address, params.count, params.skip),
+            .query_volume_lb(address, params.window, params.count, params.skip),
     )
 }
 
+/// Get a user's rank on the volume leaderboard of a specific asset (game or sync)
+pub async fn volume_rank(
+    AxumState(state): AxumState<Arc<State>>,
+    Path((address, user)): Path<(Public, Public)>,
+    Query(params): Query<VolumeRankParams>,
+) -> Json<ApiResponse<VolumeRank>> {
+    expire_volume(&state);
+    let data = state.read().unwrap();
+    success(data.volume.get_rank(
+        address,
+        user,
+        params.window,
+        params.neighbors.min(MAX_NEIGHBORS),
+    ))
+}
+
+/// Get the holders of a specific asset (game or sync), largest first
+pub async fn holders(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(address): Path<Public>,
+    Query(params): Query<PaginationParams>,
+) -> Json<ApiResponse<crate::state::types::HoldersResponse>> {
+    let data = state.read().unwrap();
+    success(data.pnl.query_holders(address, params.count, params.skip))
+}
+
+/// Get the breakdown of a specific asset's hot score
+pub async fn hot_breakdown(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(address): Path<Public>,
+) -> ApiResult<HotBreakdown> {
+    let data = state.read().unwrap();
+    match data.assets.hot_breakdown(&address) {
+        Some(breakdown) => Ok(success(breakdown)),
+        None => Err(error(
+            &format!("Asset with address {} not found", address),
+            StatusCode::NOT_FOUND,
+        )),
+    }
+}
+
+/// The last USD price of a game or sync
+fn last_price_for(data: &StateData, address: &Public) -> f32 {
+    data.syncs
+        .last_price_for(address)
+        .unwrap_or_else(|| data.games.last_price_for(address))
+}
+
+/// Re-mark the asset's unrealized PnL leaderboards at its current price,
+/// if it drifted, taking the write lock only then
+fn mark_pnl(state: &State, address: &Public) {
+    let needs_mark = {
+        let data = state.read().unwrap();
+        data.pnl.needs_mark(address, last_price_for(&data, address))
+    };
+    if needs_mark {
+        let mut data = state.write().unwrap();
+        let price = last_price_for(&data, address);
+        data.pnl.mark(address, price);
+    }
+}
+
 /// Get PnL leaderboard for a specific asset (game or sync)
 pub async fn pnl_board(
     AxumState(state): AxumState<Arc<State>>,
     Path(address): Path<Public>,
     Query(params): Query<PnlBoardParams>,
-) -> Json<ApiResponse<Vec<crate::state::types::PnlEntry>>> {
+) -> Json<ApiResponse<Vec<PnlEntry>>> {
+    let sort = params.sort.unwrap_or(match params.realized {
+        true => PnlSort::RealizedRoi,
+        false => PnlSort::UnrealizedRoi,
+    });
+    mark_pnl(&state, &address);
     let data = state.read().unwrap();
-    let price = data.games.last_price_for(&address);
+    let price = last_price_for(&data, &address);
     success(
         data.pnl
-            .query_pnl_lb(address, price, params.count, params.skip, params.realized),
+            .query_pnl_lb(address, price, params.count, params.skip, sort),
     )
 }
 
+/// Get a user's rank on the PnL leaderboard of a specific asset (game or sync)
+pub async fn pnl_rank(
+    AxumState(state): AxumState<Arc<State>>,
+    Path((address, user)): Path<(Public, Public)>,
+    Query(params): Query<PnlRankParams>,
+) -> Json<ApiResponse<BoardRank<PnlEntry>>> {
+    mark_pnl(&state, &address);
+    let data = state.read().unwrap();
+    let price = last_price_for(&data, &address);
+    success(data.pnl.get_pnl_rank(
+        address,
+        user,
+        price,
+        params.sort,
+        params.neighbors.min(MAX_NEIGHBORS),
+    ))
+}
+
 /// Get PnL for a specific user in a specific asset (game or sync)
 pub async fn get_pnl(
     AxumState(state): AxumState<Arc<State>>,
     Path((address, user)): Path<(Public, Public)>,
 ) -> Json<ApiResponse<PnlResponse>> {
     let data = state.read().unwrap();
-    let price = data.games.last_price_for(&address);
+    let price = last_price_for(&data, &address);
     success(data.pnl.get_pnl(address, user, price))
 }
 
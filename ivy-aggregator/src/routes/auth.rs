@@ -0,0 +1,119 @@
+use crate::routes::types::{error, AppError};
+use crate::types::public::Public;
+use crate::types::signature::Signature;
+use crate::util::unix_timestamp;
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use ed25519_dalek::{Signature as Ed25519Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+
+// How far an owner signature's timestamp may be from now, in seconds
+const MAX_SIGNATURE_AGE: u64 = 5 * 60;
+
+/// The admin key, from the `ADMIN_KEY` environment variable.
+/// Admin requests are disabled if it's missing or empty.
+fn admin_key() -> Option<&'static str> {
+    static ADMIN_KEY: OnceLock<Option<String>> = OnceLock::new();
+    ADMIN_KEY
+        .get_or_init(|| std::env::var("ADMIN_KEY").ok().filter(|k| !k.is_empty()))
+        .as_deref()
+}
+
+/// Compare two byte strings in constant time
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Whether the request has `Authorization: Bearer <ADMIN_KEY>`
+pub fn is_admin(headers: &HeaderMap) -> bool {
+    let Some(key) = admin_key() else {
+        return false;
+    };
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.as_bytes(), key.as_bytes()))
+}
+
+/// A request that an owner may sign
+pub struct OwnerRequest<'a> {
+    pub method: &'a Method,
+    /// The request path, without a trailing slash or query
+    pub path: &'a str,
+    pub body: &'a [u8],
+}
+
+/// The message an owner signs to authorize `request` at `timestamp`:
+/// its method, path, timestamp and the SHA-256 of its body, as hex
+fn owner_message(request: &OwnerRequest, timestamp: u64) -> String {
+    format!(
+        "ivy-aggregator:{}:{}:{}:{}",
+        request.method,
+        request.path,
+        timestamp,
+        hex::encode(Sha256::digest(request.body))
+    )
+}
+
+/// Record that `signature` was used at `timestamp`, returning whether it was already.
+/// Only signatures that are still recent enough to be accepted are remembered.
+fn is_replayed(signature: &Signature, timestamp: u64) -> bool {
+    static USED: Mutex<BTreeSet<(u64, [u8; 64])>> = Mutex::new(BTreeSet::new());
+    let mut used = USED.lock().unwrap();
+    let oldest = unix_timestamp().saturating_sub(MAX_SIGNATURE_AGE);
+    *used = used.split_off(&(oldest, [0; 64]));
+    !used.insert((timestamp, signature.0))
+}
+
+/// Whether the request carries a valid signature by `owner` authorizing `request`:
+/// `X-Ivy-Timestamp` is a Unix timestamp within 5 minutes of now, and `X-Ivy-Signature`
+/// is an ed25519 signature (base58) of `owner_message(request, timestamp)`.
+/// Each signature is only accepted once.
+fn is_signed_by(headers: &HeaderMap, owner: &Public, request: &OwnerRequest) -> bool {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let Some(timestamp) = header("x-ivy-timestamp").and_then(|v| v.parse::<u64>().ok()) else {
+        return false;
+    };
+    if timestamp.abs_diff(unix_timestamp()) > MAX_SIGNATURE_AGE {
+        return false;
+    }
+    let Some(signature) = header("x-ivy-signature").and_then(|v| Signature::from_str(v).ok())
+    else {
+        return false;
+    };
+    let Ok(key) = VerifyingKey::from_bytes(&owner.0) else {
+        return false;
+    };
+    let message = owner_message(request, timestamp);
+    key.verify_strict(
+        message.as_bytes(),
+        &Ed25519Signature::from_bytes(&signature.0),
+    )
+    .is_ok()
+        && !is_replayed(&signature, timestamp)
+}
+
+/// Require the request to come from an admin
+pub fn require_admin(headers: &HeaderMap) -> Result<(), AppError> {
+    if is_admin(headers) {
+        Ok(())
+    } else {
+        Err(error("Unauthorized", StatusCode::UNAUTHORIZED))
+    }
+}
+
+/// Require the request to come from an admin, or to be signed by `owner`
+pub fn require_admin_or_owner(
+    headers: &HeaderMap,
+    owner: &Public,
+    request: &OwnerRequest,
+) -> Result<(), AppError> {
+    if is_admin(headers) || is_signed_by(headers, owner, request) {
+        Ok(())
+    } else {
+        Err(error("Unauthorized", StatusCode::UNAUTHORIZED))
+    }
+}
@@ -0,0 +1,105 @@
+use crate::routes::types::{error, success, ApiResult};
+use crate::state::constants::MAX_CANDLES;
+use crate::state::types::ChartResponse;
+use crate::state::State;
+use crate::types::charts::{ChartKind, ChartQuote};
+use crate::types::public::Public;
+use crate::util::from_ivy_amount;
+use axum::extract::{Path, Query, State as AxumState};
+use axum::http::StatusCode;
+use serde::Deserialize;
+use std::sync::Arc;
+
+// Query parameter structs
+#[derive(Deserialize)]
+pub struct ChartParams {
+    #[serde(default = "default_count")]
+    count: usize,
+    #[serde(default)]
+    after: u64,
+    #[serde(default)]
+    quote: ChartQuote,
+}
+
+fn default_count() -> usize {
+    100
+}
+
+// Handler functions
+
+/// Get a chart for a specific asset (game or sync)
+pub async fn asset_chart(
+    AxumState(state): AxumState<Arc<State>>,
+    Path((address, kind)): Path<(Public, ChartKind)>,
+    Query(params): Query<ChartParams>,
+) -> ApiResult<ChartResponse> {
+    let count = params.count.min(MAX_CANDLES);
+    let data = state.read().unwrap();
+
+    if data.games.get_game_by_address(&address).is_some() {
+        if params.quote == ChartQuote::Sol {
+            return Err(error(
+                "Game charts are only available in USD or IVY",
+                StatusCode::BAD_REQUEST,
+            ));
+        }
+        let (candles, mkt_cap_usd, change_24h) =
+            data.games
+                .query_game_chart(address, params.quote, kind, count, params.after);
+        return Ok(success(ChartResponse {
+            candles,
+            mkt_cap_usd,
+            change_24h,
+        }));
+    }
+
+    if let Some(sync) = data.syncs.get_sync_by_address(&address) {
+        if params.quote == ChartQuote::Ivy {
+            return Err(error(
+                "Sync charts are only available in USD or SOL",
+                StatusCode::BAD_REQUEST,
+            ));
+        }
+        return Ok(success(ChartResponse {
+            candles: data.syncs.query_sync_chart(
+                address,
+                params.quote,
+                kind,
+                count,
+                params.after,
+                &data.prices,
+            ),
+            mkt_cap_usd: sync.mkt_cap_usd,
+            change_24h: sync.change_pct_24h,
+        }));
+    }
+
+    Err(error(
+        &format!("Asset with address {} not found", address),
+        StatusCode::NOT_FOUND,
+    ))
+}
+
+/// Get a chart for the IVY curve
+pub async fn ivy_chart(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(kind): Path<ChartKind>,
+    Query(params): Query<ChartParams>,
+) -> ApiResult<ChartResponse> {
+    if params.quote != ChartQuote::Usd {
+        return Err(error(
+            "IVY charts are only available in USD",
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+    let count = params.count.min(MAX_CANDLES);
+    let data = state.read().unwrap();
+    let world_data = data.world.data();
+
+    Ok(success(ChartResponse {
+        candles: data.world.query_ivy_chart(kind, count, params.after),
+        mkt_cap_usd: from_ivy_amount(world_data.ivy_sold + world_data.ivy_vested)
+            * data.world.price(),
+        change_24h: data.world.ivy_change_24h(),
+    }))
+}
@@ -0,0 +1,60 @@
+use crate::routes::types::{error, success, ApiResponse, ApiResult};
+use crate::state::types::{FeePoint, FeesResponse};
+use crate::state::State;
+use crate::types::public::Public;
+use axum::{
+    extract::{Path, Query, State as AxumState},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+// Query parameter structs
+#[derive(Deserialize)]
+pub struct DailyFeesParams {
+    /// Inclusive start, as a Unix timestamp
+    from: Option<u64>,
+    /// Exclusive end, as a Unix timestamp
+    to: Option<u64>,
+}
+
+// Handler functions
+
+/// Get the fees taken by all game swaps over 24h, 7d, 30d and all time
+pub async fn protocol_fees(
+    AxumState(state): AxumState<Arc<State>>,
+) -> Json<ApiResponse<FeesResponse>> {
+    let data = state.read().unwrap();
+    success(data.fees.get_protocol_fees())
+}
+
+/// Get the fees taken by a game's swaps over 24h, 7d, 30d and all time
+pub async fn game_fees(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(game): Path<Public>,
+) -> ApiResult<FeesResponse> {
+    let data = state.read().unwrap();
+    if data.games.get_game_by_address(&game).is_none() {
+        return Err(error("Game not found", StatusCode::NOT_FOUND));
+    }
+    Ok(success(data.fees.get_game_fees(&game)))
+}
+
+/// Get the fees taken by a game's swaps per day, oldest first,
+/// skipping days without swaps
+pub async fn game_fees_daily(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(game): Path<Public>,
+    Query(params): Query<DailyFeesParams>,
+) -> ApiResult<Vec<FeePoint>> {
+    let data = state.read().unwrap();
+    if data.games.get_game_by_address(&game).is_none() {
+        return Err(error("Game not found", StatusCode::NOT_FOUND));
+    }
+    Ok(success(data.fees.query_game_daily(
+        &game,
+        params.from,
+        params.to,
+    )))
+}
@@ -7,7 +7,7 @@ use axum::http::StatusCode;
 use std::sync::Arc;
 
 // Helper function to decode a hex string as a [u8; 32]
-fn parse_hex_as_32_bytes(s: &str) -> Result<[u8; 32], &'static str> {
+pub fn parse_hex_as_32_bytes(s: &str) -> Result<[u8; 32], &'static str> {
     match hex::decode(s) {
         Ok(bytes) => {
             if bytes.len() != 32 {
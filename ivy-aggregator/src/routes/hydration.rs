@@ -0,0 +1,54 @@
+use crate::routes::auth::require_admin;
+use crate::routes::types::{error, success, ApiResult};
+use crate::state::components::hydrate::{HydrationJob, HydrationStatus};
+use crate::state::State;
+use crate::types::public::Public;
+use axum::{
+    extract::{Path, Query, State as AxumState},
+    http::{HeaderMap, StatusCode},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+// Query parameter structs
+#[derive(Deserialize)]
+pub struct HydrationJobsParams {
+    status: Option<HydrationStatus>,
+}
+
+// Handler functions
+
+/// List the queued hydration jobs, oldest first, optionally only those with a status
+pub async fn list_hydration_jobs(
+    AxumState(state): AxumState<Arc<State>>,
+    headers: HeaderMap,
+    Query(params): Query<HydrationJobsParams>,
+) -> ApiResult<Vec<HydrationJob>> {
+    require_admin(&headers)?;
+    let data = state.read().unwrap();
+    Ok(success(data.hydrator.get_jobs(params.status)))
+}
+
+/// Retry an asset's hydration job now, with its attempts reset
+pub async fn retry_hydration_job(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(asset): Path<Public>,
+    headers: HeaderMap,
+) -> ApiResult<HydrationJob> {
+    require_admin(&headers)?;
+    let mut data = state.write().unwrap();
+    match data.hydrator.retry(&asset) {
+        Some(job) => Ok(success(job)),
+        None => Err(error("Hydration job not found", StatusCode::NOT_FOUND)),
+    }
+}
+
+/// Retry every hydration job that was given up on, returning how many there were
+pub async fn retry_failed_hydration_jobs(
+    AxumState(state): AxumState<Arc<State>>,
+    headers: HeaderMap,
+) -> ApiResult<usize> {
+    require_admin(&headers)?;
+    let mut data = state.write().unwrap();
+    Ok(success(data.hydrator.retry_failed()))
+}
@@ -1,10 +1,24 @@
-use crate::routes::types::{success, ApiResponse};
-use crate::state::types::{GlobalInfo, IvyInfo};
+use crate::routes::types::{error, success, ApiResponse, ApiResult};
+use crate::state::types::{GlobalInfo, IvyInfo, IvySupply};
 use crate::state::State;
-use crate::util::from_ivy_amount;
-use axum::{extract::State as AxumState, response::Json};
+use crate::util::{from_ivy_amount, from_usdc_amount};
+use axum::{
+    extract::{Query, State as AxumState},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
 use std::sync::Arc;
 
+// Query parameter structs
+#[derive(Deserialize)]
+pub struct SupplyParams {
+    /// USDC to quote a buy of IVY for
+    buy_usdc: Option<f64>,
+    /// IVY to quote a sell for
+    sell_ivy: Option<f64>,
+}
+
 // Handler functions
 pub async fn ivy_info(AxumState(state): AxumState<Arc<State>>) -> Json<ApiResponse<IvyInfo>> {
     let data = state.read().unwrap();
@@ -31,3 +45,56 @@ pub async fn global_info(AxumState(state): AxumState<Arc<State>>) -> Json<ApiRes
         featured_assets: data.assets.get_featured_assets(&data.games, &data.syncs),
     })
 }
+
+/// Get IVY's supply, vesting, curve progress and reserve,
+/// with quotes for hypothetical swaps
+pub async fn ivy_supply(
+    AxumState(state): AxumState<Arc<State>>,
+    Query(params): Query<SupplyParams>,
+) -> ApiResult<IvySupply> {
+    let data = state.read().unwrap();
+    let world = &data.world;
+    let world_data = world.data();
+    let bad_request = |msg| error(msg, StatusCode::BAD_REQUEST);
+    let buy_quote = params
+        .buy_usdc
+        .map(|usdc| world.quote_buy(usdc))
+        .transpose()
+        .map_err(bad_request)?;
+    let sell_quote = params
+        .sell_ivy
+        .map(|ivy| world.quote_sell(ivy))
+        .transpose()
+        .map_err(bad_request)?;
+
+    let ivy_burned = data.fees.get_protocol_fees().all.ivy_fees;
+    let ivy_sold = from_ivy_amount(world_data.ivy_sold);
+    let ivy_vested = from_ivy_amount(world_data.ivy_vested);
+    let ivy_curve_max = from_ivy_amount(world_data.ivy_curve_max);
+    let usdc_reserve = from_usdc_amount(world_data.usdc_balance);
+    let (usdc_expected, solvent) = world.reserve_check();
+    Ok(success(IvySupply {
+        circulating_supply: (ivy_sold + ivy_vested - ivy_burned).max(0.0),
+        ivy_sold,
+        ivy_curve_max,
+        curve_fill: if ivy_curve_max > 0.0 {
+            ivy_sold / ivy_curve_max
+        } else {
+            0.0
+        },
+        ivy_vested,
+        ivy_burned,
+        vesting_history: world.vesting_history().to_vec(),
+        usdc_reserve,
+        usdc_expected: usdc_expected as f32,
+        reserve_ratio: if usdc_expected > 0.0 {
+            (usdc_reserve as f64 / usdc_expected) as f32
+        } else {
+            1.0
+        },
+        solvent,
+        ivy_price: world.price(),
+        buy_quote,
+        sell_quote,
+    }))
+}
@@ -0,0 +1,309 @@
+use crate::routes::types::error;
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request, State as AxumState},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures::StreamExt as _;
+use priority_queue::PriorityQueue;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+// Buckets tracked at once, beyond which the least recently used is dropped
+const MAX_TRACKED_BUCKETS: usize = 65536;
+
+/// API key and rate limit settings
+pub struct LimitsConfig {
+    /// Accepted API keys
+    pub api_keys: HashSet<String>,
+    /// Whether requests without an API key are rejected
+    pub require_api_key: bool,
+    /// Requests per second allowed per IP without an API key (0 disables the limit)
+    pub ip_rate: f64,
+    /// Requests an IP may burst above its rate
+    pub ip_burst: f64,
+    /// Requests per second allowed per API key (0 disables the limit)
+    pub key_rate: f64,
+    /// Requests an API key may burst above its rate
+    pub key_burst: f64,
+    /// Concurrent SSE streams per API key or IP
+    pub max_streams_per_client: usize,
+    /// Concurrent SSE streams overall
+    pub max_streams: usize,
+    /// Number of reverse proxies in front of us, each appending to `X-Forwarded-For`.
+    /// The client IP is taken from that many entries from the right (0 ignores the header).
+    pub trusted_proxies: usize,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            api_keys: HashSet::new(),
+            require_api_key: false,
+            ip_rate: 10.0,
+            ip_burst: 50.0,
+            key_rate: 50.0,
+            key_burst: 200.0,
+            max_streams_per_client: 8,
+            max_streams: 4096,
+            trusted_proxies: 0,
+        }
+    }
+}
+
+impl LimitsConfig {
+    /// Read the config from environment variables,
+    /// using the default for each one that's missing or invalid
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            match std::env::var(name) {
+                Ok(v) => v.parse().unwrap_or_else(|_| {
+                    eprintln!("warning: Can't parse {}, using default", name);
+                    default
+                }),
+                Err(_) => default,
+            }
+        }
+        let default = Self::default();
+        let api_keys: HashSet<String> = std::env::var("API_KEYS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|k| k.trim().to_string())
+            .filter(|k| !k.is_empty())
+            .collect();
+        let require_api_key = var("REQUIRE_API_KEY", default.require_api_key);
+        if require_api_key && api_keys.is_empty() {
+            eprintln!("warning: REQUIRE_API_KEY is set but API_KEYS is empty");
+        }
+        Self {
+            api_keys,
+            require_api_key,
+            ip_rate: var("RATE_LIMIT_IP_PER_SEC", default.ip_rate).max(0.0),
+            ip_burst: var("RATE_LIMIT_IP_BURST", default.ip_burst).max(1.0),
+            key_rate: var("RATE_LIMIT_KEY_PER_SEC", default.key_rate).max(0.0),
+            key_burst: var("RATE_LIMIT_KEY_BURST", default.key_burst).max(1.0),
+            max_streams_per_client: var("MAX_STREAMS_PER_CLIENT", default.max_streams_per_client),
+            max_streams: var("MAX_STREAMS", default.max_streams),
+            // TRUST_FORWARDED_FOR=true is a single proxy
+            trusted_proxies: var(
+                "TRUSTED_PROXIES",
+                var("TRUST_FORWARDED_FOR", false) as usize,
+            ),
+        }
+    }
+}
+
+/// The API key a request was authenticated with, added to its extensions
+#[derive(Clone)]
+pub struct ApiKey(pub String);
+
+/// Who a request is limited as
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Client {
+    Key(String),
+    Ip(IpAddr),
+}
+
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+impl Bucket {
+    /// Refill the bucket up to `now`
+    fn refill(&mut self, now: Instant, rate: f64, burst: f64) {
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(burst);
+        self.last = now;
+    }
+}
+
+#[derive(Default)]
+struct Streams {
+    total: usize,
+    per_client: HashMap<Client, usize>,
+}
+
+#[derive(Default)]
+struct Buckets {
+    buckets: HashMap<Client, Bucket>,
+    // Clients by when their bucket was last used, least recent first
+    by_last_use: PriorityQueue<Client, Reverse<Instant>>,
+}
+
+/// Token bucket rate limiter, and counter of open SSE streams
+pub struct Limiter {
+    config: LimitsConfig,
+    buckets: Mutex<Buckets>,
+    streams: Mutex<Streams>,
+}
+
+/// Keeps a stream counted until dropped
+struct StreamGuard {
+    limiter: Arc<Limiter>,
+    client: Client,
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        let mut streams = self.limiter.streams.lock().unwrap();
+        streams.total -= 1;
+        if let Some(count) = streams.per_client.get_mut(&self.client) {
+            *count -= 1;
+            if *count == 0 {
+                streams.per_client.remove(&self.client);
+            }
+        }
+    }
+}
+
+impl Limiter {
+    pub fn new(config: LimitsConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(Buckets::default()),
+            streams: Mutex::new(Streams::default()),
+        }
+    }
+
+    fn rate_and_burst(&self, client: &Client) -> (f64, f64) {
+        match client {
+            Client::Key(_) => (self.config.key_rate, self.config.key_burst),
+            Client::Ip(_) => (self.config.ip_rate, self.config.ip_burst),
+        }
+    }
+
+    /// Take a token from the client's bucket,
+    /// or return the number of seconds until one is available
+    fn take(&self, client: &Client) -> Result<(), u64> {
+        let (rate, burst) = self.rate_and_burst(client);
+        if rate == 0.0 {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let mut guard = self.buckets.lock().unwrap();
+        let Buckets {
+            buckets,
+            by_last_use,
+        } = &mut *guard;
+        let bucket = buckets.entry(client.clone()).or_insert(Bucket {
+            tokens: burst,
+            last: now,
+        });
+        bucket.refill(now, rate, burst);
+        let result = if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / rate).ceil() as u64)
+        };
+
+        by_last_use.push(client.clone(), Reverse(now));
+        if by_last_use.len() > MAX_TRACKED_BUCKETS {
+            // The least recently used bucket is the likeliest to have refilled
+            if let Some((evicted, _)) = by_last_use.pop() {
+                buckets.remove(&evicted);
+            }
+        }
+        result
+    }
+
+    /// Count a new stream for the client, if it's under the caps
+    fn open_stream(self: &Arc<Self>, client: &Client) -> Option<StreamGuard> {
+        let mut streams = self.streams.lock().unwrap();
+        let count = streams.per_client.get(client).copied().unwrap_or(0);
+        if streams.total >= self.config.max_streams || count >= self.config.max_streams_per_client {
+            return None;
+        }
+        streams.total += 1;
+        streams.per_client.insert(client.clone(), count + 1);
+        Some(StreamGuard {
+            limiter: self.clone(),
+            client: client.clone(),
+        })
+    }
+
+    /// The API key of a request, from the `X-Api-Key` header or the `api_key`
+    /// query parameter (as `EventSource` can't set headers)
+    fn api_key<'a>(headers: &'a HeaderMap, query: Option<&'a str>) -> Option<&'a str> {
+        if let Some(key) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) {
+            return Some(key);
+        }
+        query?
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("api_key="))
+    }
+
+    /// The IP of a request's client. Behind proxies, it's the address the
+    /// outermost trusted proxy saw, as entries left of it can be forged by the client.
+    fn client_ip(&self, headers: &HeaderMap, addr: SocketAddr) -> IpAddr {
+        let hops = self.config.trusted_proxies;
+        if hops > 0 {
+            let forwarded = headers
+                .get_all("x-forwarded-for")
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .flat_map(|v| v.split(','))
+                .collect::<Vec<_>>();
+            let ip = forwarded
+                .len()
+                .checked_sub(hops)
+                .and_then(|i| forwarded[i].trim().parse().ok());
+            if let Some(ip) = ip {
+                return ip;
+            }
+        }
+        addr.ip()
+    }
+}
+
+/// Middleware that authenticates API keys, rate limits requests
+/// (including opening SSE streams), and caps concurrent SSE streams
+pub async fn limit(
+    AxumState(limiter): AxumState<Arc<Limiter>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let headers = request.headers();
+    let client = match Limiter::api_key(headers, request.uri().query()) {
+        Some(key) if limiter.config.api_keys.contains(key) => Client::Key(key.to_string()),
+        Some(_) => return error("Invalid API key", StatusCode::UNAUTHORIZED).into_response(),
+        None if limiter.config.require_api_key => {
+            return error("API key required", StatusCode::UNAUTHORIZED).into_response()
+        }
+        None => Client::Ip(limiter.client_ip(headers, addr)),
+    };
+    if let Client::Key(key) = &client {
+        request.extensions_mut().insert(ApiKey(key.clone()));
+    }
+
+    if let Err(retry_after) = limiter.take(&client) {
+        let mut response =
+            error("Rate limit exceeded", StatusCode::TOO_MANY_REQUESTS).into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after.max(1)));
+        return response;
+    }
+
+    // SSE streams are also capped by count, as they stay open
+    if request.uri().path().ends_with("/stream") {
+        let Some(guard) = limiter.open_stream(&client) else {
+            return error("Too many open streams", StatusCode::TOO_MANY_REQUESTS).into_response();
+        };
+        let (parts, body) = next.run(request).await.into_parts();
+        // Release the stream once the response body is dropped
+        let stream = body.into_data_stream().map(move |chunk| {
+            let _ = &guard;
+            chunk
+        });
+        return Response::from_parts(parts, Body::from_stream(stream));
+    }
+    next.run(request).await
+}
@@ -1,9 +1,17 @@
+pub mod alerts;
 pub mod assets;
+pub mod auth;
+pub mod charts;
 pub mod comments;
+pub mod fees;
 pub mod games;
+pub mod hydration;
 pub mod info;
+pub mod limits;
 pub mod misc;
 pub mod price;
+pub mod receipts;
+pub mod stats;
 pub mod stream_assets;
 pub mod stream_game;
 pub mod stream_ivy;
@@ -11,4 +19,6 @@ pub mod stream_sync;
 pub mod stream_trades;
 pub mod sync;
 pub mod types;
+pub mod vaults;
 pub mod volume;
+pub mod webhooks;
@@ -1,10 +1,176 @@
-use crate::routes::types::{success, ApiResponse};
-use crate::state::State;
-use axum::{extract::State as AxumState, response::Json};
+use crate::routes::auth::require_admin;
+use crate::routes::types::{error, success, ApiResponse, ApiResult};
+use crate::state::components::prices::SolPriceCorrection;
+use crate::state::types::{RevalueInfo, SolPriceInfo, SolPricePoint};
+use crate::state::{State, StateData};
+use crate::util::unix_timestamp;
+use axum::{
+    extract::{Path, Query, State as AxumState},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
+
+const MIN_HISTORY_INTERVAL: u64 = 60;
+
+// Whether a revaluation is running, so that only one runs at a time
+static REVALUING: AtomicBool = AtomicBool::new(false);
+
+// Query parameter structs
+#[derive(Deserialize)]
+pub struct SolHistoryParams {
+    /// Inclusive start, as a Unix timestamp
+    from: Option<u64>,
+    /// Exclusive end, as a Unix timestamp
+    to: Option<u64>,
+    /// Seconds per point, at least a minute
+    #[serde(default = "default_interval")]
+    interval: u64,
+}
+
+fn default_interval() -> u64 {
+    3600
+}
+
+#[derive(Deserialize)]
+pub struct CorrectionRequest {
+    /// Inclusive start, as a Unix timestamp
+    from: u64,
+    /// Exclusive end, as a Unix timestamp
+    to: u64,
+    price: f32,
+}
 
 // Handler functions
 pub async fn ivy_price(AxumState(state): AxumState<Arc<State>>) -> Json<ApiResponse<f32>> {
     let data = state.read().unwrap();
     success(data.world.price())
 }
+
+/// Get the SOL price, with the latest price of each source and whether it's stale
+pub async fn sol_price(AxumState(state): AxumState<Arc<State>>) -> Json<ApiResponse<SolPriceInfo>> {
+    let data = state.read().unwrap();
+    success(data.prices.get_sol_info(unix_timestamp()))
+}
+
+/// Get the SOL price over time, oldest first
+pub async fn sol_price_history(
+    AxumState(state): AxumState<Arc<State>>,
+    Query(params): Query<SolHistoryParams>,
+) -> ApiResult<Vec<SolPricePoint>> {
+    if params.interval < MIN_HISTORY_INTERVAL {
+        return Err(error(
+            "Interval must be at least 60 seconds",
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+    let data = state.read().unwrap();
+    Ok(success(data.prices.query_sol_history(
+        params.from,
+        params.to,
+        params.interval,
+    )))
+}
+
+/// Get the SOL price at a Unix timestamp
+pub async fn sol_price_at(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(timestamp): Path<u64>,
+) -> Json<ApiResponse<SolPricePoint>> {
+    let data = state.read().unwrap();
+    success(SolPricePoint {
+        timestamp,
+        price: data.prices.sol_at(timestamp),
+    })
+}
+
+/// List the SOL price corrections, oldest first
+pub async fn list_sol_corrections(
+    AxumState(state): AxumState<Arc<State>>,
+) -> Json<ApiResponse<Vec<SolPriceCorrection>>> {
+    let data = state.read().unwrap();
+    success(data.prices.get_corrections().to_vec())
+}
+
+/// Correct the SOL price over a time range. USD aggregates valued at the wrong
+/// price keep it until they're revalued.
+pub async fn create_sol_correction(
+    AxumState(state): AxumState<Arc<State>>,
+    headers: HeaderMap,
+    Json(request): Json<CorrectionRequest>,
+) -> ApiResult<SolPriceCorrection> {
+    require_admin(&headers)?;
+    if request.from >= request.to {
+        return Err(error("From must be before to", StatusCode::BAD_REQUEST));
+    }
+    if !request.price.is_finite() || request.price <= 0.0 {
+        return Err(error(
+            "Price must be positive and finite",
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+    let mut data = state.write().unwrap();
+    Ok(success(data.prices.add_correction(
+        request.from,
+        request.to,
+        request.price,
+    )))
+}
+
+/// Remove every SOL price correction, returning how many there were
+pub async fn clear_sol_corrections(
+    AxumState(state): AxumState<Arc<State>>,
+    headers: HeaderMap,
+) -> ApiResult<usize> {
+    require_admin(&headers)?;
+    let mut data = state.write().unwrap();
+    Ok(success(data.prices.clear_corrections()))
+}
+
+/// Revalue the USD aggregates (PnL, volume and statistics) from the event log
+/// at the corrected SOL prices
+pub async fn revalue_state(
+    AxumState(state): AxumState<Arc<State>>,
+    headers: HeaderMap,
+) -> ApiResult<RevalueInfo> {
+    require_admin(&headers)?;
+    if REVALUING.swap(true, Ordering::AcqRel) {
+        return Err(error(
+            "A revaluation is already running",
+            StatusCode::CONFLICT,
+        ));
+    }
+    let start = Instant::now();
+    // Replaying the log blocks, so keep it off the async workers. The revaluation
+    // carries on if the client goes away, so it clears the flag itself.
+    let result = tokio::task::spawn_blocking(move || {
+        let result = StateData::revalue(&state).map_err(|e| e.to_string());
+        REVALUING.store(false, Ordering::Release);
+        result
+    })
+    .await;
+
+    match result {
+        Ok(Ok(events)) => Ok(success(RevalueInfo {
+            events,
+            duration_ms: start.elapsed().as_millis() as u64,
+        })),
+        Ok(Err(e)) => {
+            eprintln!("Failed to revalue state: {}", e);
+            Err(error(
+                "Failed to revalue state",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+        Err(e) => {
+            eprintln!("State revaluation panicked: {}", e);
+            Err(error(
+                "Failed to revalue state",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
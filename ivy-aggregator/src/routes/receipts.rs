@@ -0,0 +1,193 @@
+use crate::routes::types::{success, ApiResponse};
+use crate::state::components::receipts::ReceiptQuery;
+use crate::state::types::{
+    BurnInfo, DailyReceipts, DepositInfo, ReceiptList, VaultReceipt, WithdrawInfo,
+};
+use crate::state::State;
+use crate::types::public::Public;
+use axum::{
+    extract::{Path, Query, State as AxumState},
+    http::header,
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::sync::Arc;
+
+// Most receipts returned per request, in either format.
+// Larger exports are paged through with `skip`.
+const MAX_RECEIPTS: usize = 1000;
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReceiptFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+// Query parameter structs
+#[derive(Deserialize)]
+pub struct ReceiptsParams {
+    /// Inclusive start, as a Unix timestamp
+    from: Option<u64>,
+    /// Exclusive end, as a Unix timestamp
+    to: Option<u64>,
+    /// Defaults to 20 for JSON and to `MAX_RECEIPTS` for CSV
+    count: Option<usize>,
+    #[serde(default)]
+    skip: usize,
+    #[serde(default)]
+    reverse: bool,
+    #[serde(default)]
+    format: ReceiptFormat,
+    /// Only list withdrawals by this withdraw authority
+    withdraw_authority: Option<Public>,
+}
+
+impl ReceiptsParams {
+    pub fn format(&self) -> ReceiptFormat {
+        self.format
+    }
+
+    pub fn query(&self) -> ReceiptQuery {
+        let default_count = match self.format {
+            ReceiptFormat::Json => 20,
+            ReceiptFormat::Csv => MAX_RECEIPTS,
+        };
+        ReceiptQuery {
+            from: self.from,
+            to: self.to,
+            count: self.count.unwrap_or(default_count).min(MAX_RECEIPTS),
+            skip: self.skip,
+            reverse: self.reverse,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DailyReceiptsParams {
+    from: Option<u64>,
+    to: Option<u64>,
+    withdraw_authority: Option<Public>,
+}
+
+/// A receipt that can be exported as a CSV row
+pub trait CsvReceipt {
+    const HEADER: &'static str;
+    fn write_row(&self, id: &str, out: &mut String);
+}
+
+impl CsvReceipt for BurnInfo {
+    const HEADER: &'static str = "id,signature,timestamp";
+    fn write_row(&self, id: &str, out: &mut String) {
+        _ = writeln!(out, "{},{},{}", id, self.signature, self.timestamp);
+    }
+}
+
+impl CsvReceipt for DepositInfo {
+    const HEADER: &'static str = "id,signature,timestamp";
+    fn write_row(&self, id: &str, out: &mut String) {
+        _ = writeln!(out, "{},{},{}", id, self.signature, self.timestamp);
+    }
+}
+
+impl CsvReceipt for WithdrawInfo {
+    const HEADER: &'static str = "id,signature,timestamp,withdraw_authority";
+    fn write_row(&self, id: &str, out: &mut String) {
+        _ = writeln!(
+            out,
+            "{},{},{},{}",
+            id, self.signature, self.timestamp, self.withdraw_authority
+        );
+    }
+}
+
+impl CsvReceipt for VaultReceipt {
+    const HEADER: &'static str = "id,signature,timestamp";
+    fn write_row(&self, id: &str, out: &mut String) {
+        _ = writeln!(out, "{},{},{}", id, self.signature, self.timestamp);
+    }
+}
+
+/// Respond with `list` in the requested format. CSV exports carry the
+/// number of matching receipts in `X-Total-Count`, to page through them.
+pub fn respond<T: CsvReceipt + Serialize>(list: ReceiptList<T>, format: ReceiptFormat) -> Response {
+    match format {
+        ReceiptFormat::Json => success(list).into_response(),
+        ReceiptFormat::Csv => {
+            let mut csv = String::new();
+            csv.push_str(T::HEADER);
+            csv.push('\n');
+            for receipt in &list.receipts {
+                receipt.info.write_row(&receipt.id, &mut csv);
+            }
+            (
+                [
+                    (header::CONTENT_TYPE, "text/csv".to_string()),
+                    (
+                        header::HeaderName::from_static("x-total-count"),
+                        list.total.to_string(),
+                    ),
+                ],
+                csv,
+            )
+                .into_response()
+        }
+    }
+}
+
+// Handler functions
+
+/// List a game's burn receipts
+pub async fn list_burns(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(game): Path<Public>,
+    Query(params): Query<ReceiptsParams>,
+) -> Response {
+    let list = {
+        let data = state.read().unwrap();
+        data.receipts.list_burns(game, params.query())
+    };
+    respond(list, params.format())
+}
+
+/// List a game's deposit receipts
+pub async fn list_deposits(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(game): Path<Public>,
+    Query(params): Query<ReceiptsParams>,
+) -> Response {
+    let list = {
+        let data = state.read().unwrap();
+        data.receipts.list_deposits(game, params.query())
+    };
+    respond(list, params.format())
+}
+
+/// List a game's withdraw receipts, optionally only those by a given withdraw authority
+pub async fn list_withdrawals(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(game): Path<Public>,
+    Query(params): Query<ReceiptsParams>,
+) -> Response {
+    let list = {
+        let data = state.read().unwrap();
+        data.receipts
+            .list_withdraws(game, params.query(), params.withdraw_authority)
+    };
+    respond(list, params.format())
+}
+
+/// Get the number of burns, deposits and withdrawals for a game on each day
+pub async fn daily_receipts(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(game): Path<Public>,
+    Query(params): Query<DailyReceiptsParams>,
+) -> Json<ApiResponse<Vec<DailyReceipts>>> {
+    let data = state.read().unwrap();
+    success(
+        data.receipts
+            .daily_totals(game, params.from, params.to, params.withdraw_authority),
+    )
+}
@@ -0,0 +1,36 @@
+use crate::routes::types::{success, ApiResponse};
+use crate::state::components::stats::StatsInterval;
+use crate::state::types::StatsPoint;
+use crate::state::State;
+use axum::{
+    extract::{Query, State as AxumState},
+    response::Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+// Query parameter structs
+#[derive(Deserialize)]
+pub struct TimeseriesParams {
+    #[serde(default = "default_interval")]
+    interval: StatsInterval,
+    /// Inclusive start, as a Unix timestamp
+    from: Option<u64>,
+    /// Exclusive end, as a Unix timestamp
+    to: Option<u64>,
+}
+
+fn default_interval() -> StatsInterval {
+    StatsInterval::Day
+}
+
+// Handler functions
+
+/// Get hourly or daily protocol statistics, oldest first
+pub async fn stats_timeseries(
+    AxumState(state): AxumState<Arc<State>>,
+    Query(params): Query<TimeseriesParams>,
+) -> Json<ApiResponse<Vec<StatsPoint>>> {
+    let data = state.read().unwrap();
+    success(data.stats.query(params.interval, params.from, params.to))
+}
@@ -1,7 +1,7 @@
 use crate::routes::types::{error, AppError};
 use crate::state::State;
 use crate::types::chart::Candle;
-use crate::types::charts::ChartKind;
+use crate::types::charts::{ChartKind, ChartQuote};
 use crate::types::event::serialize_u64_as_string;
 use crate::types::public::Public;
 use axum::response::sse::{Event as SseEvent, Sse};
@@ -75,6 +75,8 @@ struct WorldUpdateEventSSE {
 #[derive(Deserialize)]
 pub struct StreamParams {
     chart: ChartKind,
+    #[serde(default)]
+    quote: ChartQuote,
     #[serde(default = "default_chart_count")]
     chart_count: usize,
     #[serde(default = "default_comment_count")]
@@ -95,8 +97,15 @@ pub async fn stream_game(
     Path(address): Path<Public>,
     Query(params): Query<StreamParams>,
 ) -> Result<Sse<impl Stream<Item = Result<SseEvent, axum::Error>>>, AppError> {
+    if params.quote == ChartQuote::Sol {
+        return Err(error(
+            "Game charts are only available in USD or IVY",
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+
     // Get initial data and subscribe to updates
-    let (initial_data, balance_rx, comment_rx, chart_rx, world_rx) = {
+    let (initial_data, balance_rx, comment_rx, chart_rx, mut chart_folder, world_rx) = {
         let mut data = state.write().unwrap();
 
         // Check if game exists
@@ -107,7 +116,7 @@ pub async fn stream_game(
         // Get initial chart data
         let (candles, mkt_cap_usd, change_pct_24h) =
             data.games
-                .query_game_chart(address, params.chart, params.chart_count, 0);
+                .query_game_chart(address, params.quote, params.chart, params.chart_count, 0);
 
         // Get initial comments
         let (_, comments) = data.comments.get_comment_info(
@@ -136,9 +145,9 @@ pub async fn stream_game(
 
         let comment_rx = data.comments.subscribe(&address);
 
-        let chart_rx = data
+        let (chart_rx, chart_folder) = data
             .games
-            .subscribe_to_game_chart(&address, params.chart)
+            .subscribe_to_game_chart(&address, params.quote, params.chart)
             .ok_or_else(|| {
                 error(
                     "Failed to subscribe to game chart",
@@ -164,7 +173,14 @@ pub async fn stream_game(
             game_fee_bps: world_data.game_fee_bps,
         };
 
-        (initial, balance_rx, comment_rx, chart_rx, world_rx)
+        (
+            initial,
+            balance_rx,
+            comment_rx,
+            chart_rx,
+            chart_folder,
+            world_rx,
+        )
     };
 
     // Create the SSE stream
@@ -228,7 +244,7 @@ pub async fn stream_game(
                         Ok(candle) => {
                             let event_data = CandleUpdateEvent {
                                 event_type: "candle",
-                                candle,
+                                candle: chart_folder.fold(candle),
                             };
                             yield Ok(SseEvent::default()
                                 .event("update")
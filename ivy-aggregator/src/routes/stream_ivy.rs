@@ -1,10 +1,11 @@
-use crate::routes::types::AppError;
+use crate::routes::types::{error, AppError};
 use crate::state::State;
 use crate::types::chart::Candle;
-use crate::types::charts::ChartKind;
+use crate::types::charts::{ChartKind, ChartQuote};
 use crate::types::event::serialize_u64_as_string;
 use crate::util::from_ivy_amount;
 use axum::extract::{Query, State as AxumState};
+use axum::http::StatusCode;
 use axum::response::sse::{Event as SseEvent, Sse};
 use futures::stream::Stream;
 use serde::{Deserialize, Serialize};
@@ -38,6 +39,8 @@ struct IvyUpdateEvent {
 #[derive(Deserialize)]
 pub struct IvyStreamParams {
     chart: ChartKind,
+    #[serde(default)]
+    quote: ChartQuote,
     #[serde(default = "default_chart_count")]
     chart_count: usize,
 }
@@ -57,8 +60,15 @@ pub async fn stream_ivy(
     AxumState(state): AxumState<Arc<State>>,
     Query(params): Query<IvyStreamParams>,
 ) -> Result<Sse<impl Stream<Item = Result<SseEvent, axum::Error>>>, AppError> {
+    if params.quote != ChartQuote::Usd {
+        return Err(error(
+            "IVY charts are only available in USD",
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+
     // Get initial data and subscribe to updates
-    let (initial_data, world_rx, chart_rx) = {
+    let (initial_data, world_rx, chart_rx, mut chart_folder) = {
         let mut data = state.write().unwrap();
 
         // Get initial ivy chart data
@@ -74,7 +84,7 @@ pub async fn stream_ivy(
 
         // Subscribe to updates
         let world_rx = data.world.subscribe();
-        let chart_rx = data.world.ivy_charts.subscribe_to_kind(params.chart);
+        let (chart_rx, chart_folder) = data.world.ivy_charts.subscribe_to_kind(params.chart);
 
         let initial = IvyInitialContextEvent {
             event_type: "initial",
@@ -87,7 +97,7 @@ pub async fn stream_ivy(
             change_pct_24h,
         };
 
-        (initial, world_rx, chart_rx)
+        (initial, world_rx, chart_rx, chart_folder)
     };
 
     // Create the SSE stream
@@ -128,7 +138,7 @@ pub async fn stream_ivy(
                         Ok(candle) => {
                             let event_data = CandleUpdateEvent {
                                 event_type: "candle",
-                                candle,
+                                candle: chart_folder.fold(candle),
                             };
                             yield Ok(SseEvent::default()
                                 .event("update")
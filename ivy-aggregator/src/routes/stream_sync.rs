@@ -1,7 +1,7 @@
 use crate::routes::types::{error, AppError};
 use crate::state::State;
 use crate::types::chart::Candle;
-use crate::types::charts::ChartKind;
+use crate::types::charts::{ChartKind, ChartQuote};
 use crate::types::public::Public;
 use axum::response::sse::{Event as SseEvent, Sse};
 use axum::{
@@ -60,6 +60,8 @@ struct CandleUpdateEvent {
 #[derive(Deserialize)]
 pub struct SyncStreamParams {
     chart: ChartKind,
+    #[serde(default)]
+    quote: ChartQuote,
     #[serde(default = "default_chart_count")]
     chart_count: usize,
     #[serde(default = "default_comment_count")]
@@ -79,9 +81,17 @@ pub async fn stream_sync(
     Path(address): Path<Public>,
     Query(params): Query<SyncStreamParams>,
 ) -> Result<Sse<impl Stream<Item = Result<SseEvent, axum::Error>>>, AppError> {
+    if params.quote == ChartQuote::Ivy {
+        return Err(error(
+            "Sync charts are only available in USD or SOL",
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+
     // Get initial data and subscribe to updates
-    let (initial_data, sync_rx, chart_rx, comment_rx, sol_price_rx) = {
-        let mut data = state.write().unwrap();
+    let (initial_data, sync_rx, chart_rx, mut chart_folder, comment_rx, sol_price_rx) = {
+        let mut guard = state.write().unwrap();
+        let data = &mut *guard;
 
         // Check if sync exists and get initial data
         let sync = data
@@ -90,9 +100,14 @@ pub async fn stream_sync(
             .ok_or_else(|| error("Sync not found", StatusCode::NOT_FOUND))?;
 
         // Get initial chart data
-        let candles = data
-            .syncs
-            .query_sync_chart(address, params.chart, params.chart_count, 0);
+        let candles = data.syncs.query_sync_chart(
+            address,
+            params.quote,
+            params.chart,
+            params.chart_count,
+            0,
+            &data.prices,
+        );
 
         // Get initial comments
         let (_, comments) = data.comments.get_comment_info(
@@ -111,9 +126,9 @@ pub async fn stream_sync(
         })?;
 
         // Subscribe to chart updates
-        let chart_rx = data
+        let (chart_rx, chart_folder) = data
             .syncs
-            .subscribe_to_sync_chart(&address, params.chart)
+            .subscribe_to_sync_chart(&address, params.quote, params.chart, &data.prices)
             .ok_or_else(|| {
                 error(
                     "Failed to subscribe to sync chart",
@@ -149,7 +164,14 @@ pub async fn stream_sync(
             sol_price,
         };
 
-        (initial, sync_rx, chart_rx, comment_rx, sol_price_rx)
+        (
+            initial,
+            sync_rx,
+            chart_rx,
+            chart_folder,
+            comment_rx,
+            sol_price_rx,
+        )
     };
 
     // Create the SSE stream
@@ -249,9 +271,14 @@ pub async fn stream_sync(
                 Some(result) = chart_stream.next() => {
                     match result {
                         Ok(candle) => {
+                            // Updates are in SOL, and the newest candle closes at the current price
+                            let candle = match params.quote {
+                                ChartQuote::Usd => candle.valued(cached_sync.sol_price),
+                                _ => candle,
+                            };
                             let event_data = CandleUpdateEvent {
                                 event_type: "candle",
-                                candle,
+                                candle: chart_folder.fold(candle),
                             };
                             yield Ok(SseEvent::default()
                                 .event("update")
@@ -16,6 +16,7 @@ struct TradeEvent {
     symbol: String,
     icon_url: String,
     volume_usd: f32,
+    price_usd: f32,
     mkt_cap_usd: f32,
     is_buy: bool,
 }
@@ -47,6 +48,7 @@ pub async fn stream_trades(
                     symbol: trade.symbol,
                     icon_url: trade.icon_url,
                     volume_usd: trade.volume_usd,
+                    price_usd: trade.price_usd,
                     mkt_cap_usd: trade.mkt_cap_usd,
                     is_buy: trade.is_buy,
                 };
@@ -1,11 +1,46 @@
-use crate::routes::types::{error, success, ApiResult};
+use crate::routes::types::{default_count, error, success, ApiResult, PaginationParams};
+use crate::state::types::{LiquidityEvent, LiquidityInfo};
 use crate::state::State;
 use crate::types::public::Public;
 use crate::types::sync::Sync;
-use axum::extract::{Path, State as AxumState};
+use axum::extract::{Path, Query, State as AxumState};
 use axum::http::StatusCode;
+use serde::Deserialize;
 use std::sync::Arc;
 
+// Query parameter structs
+#[derive(Deserialize)]
+pub struct SyncsQueryParams {
+    #[serde(default = "default_count")]
+    count: usize,
+    #[serde(default)]
+    skip: usize,
+    /// Minimum bonding curve completion, from 0 to 100, of syncs yet to migrate
+    near_migration: Option<f32>,
+}
+
+/// List syncs, newest first, or those near migration, furthest along first
+pub async fn list_syncs(
+    AxumState(state): AxumState<Arc<State>>,
+    Query(params): Query<SyncsQueryParams>,
+) -> ApiResult<Vec<Sync>> {
+    if params
+        .near_migration
+        .is_some_and(|pct| !(0.0..=100.0).contains(&pct))
+    {
+        return Err(error(
+            "near_migration must be between 0 and 100",
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+    let data = state.read().unwrap();
+    Ok(success(data.syncs.list_syncs(
+        params.near_migration,
+        params.count,
+        params.skip,
+    )))
+}
+
 /// Get a specific sync by address
 pub async fn get_sync(
     AxumState(state): AxumState<Arc<State>>,
@@ -17,3 +52,35 @@ pub async fn get_sync(
         None => Err(error("Sync not found", StatusCode::NOT_FOUND)),
     }
 }
+
+/// Get the liquidity and depth of a migrated sync's PumpSwap pool
+pub async fn sync_liquidity(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(address): Path<Public>,
+) -> ApiResult<LiquidityInfo> {
+    let data = state.read().unwrap();
+    if data.syncs.get_sync_by_address(&address).is_none() {
+        return Err(error("Sync not found", StatusCode::NOT_FOUND));
+    }
+    match data.syncs.get_liquidity(&address, data.prices.sol()) {
+        Some(info) => Ok(success(info)),
+        None => Err(error("Sync hasn't migrated", StatusCode::NOT_FOUND)),
+    }
+}
+
+/// Get the liquidity deposits and withdrawals of a sync's PumpSwap pool, newest first
+pub async fn sync_liquidity_history(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(address): Path<Public>,
+    Query(params): Query<PaginationParams>,
+) -> ApiResult<Vec<LiquidityEvent>> {
+    let data = state.read().unwrap();
+    if data.syncs.get_sync_by_address(&address).is_none() {
+        return Err(error("Sync not found", StatusCode::NOT_FOUND));
+    }
+    Ok(success(data.syncs.query_liquidity_history(
+        &address,
+        params.count,
+        params.skip,
+    )))
+}
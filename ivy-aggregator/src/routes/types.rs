@@ -1,6 +1,6 @@
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Json};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 // Response wrappers for consistent API formatting
 #[derive(Serialize)]
@@ -34,6 +34,27 @@ impl IntoResponse for AppError {
     }
 }
 
+// Entries returned by list endpoints when no count is given
+pub fn default_count() -> usize {
+    20
+}
+
+// Entries returned on each side of a ranked user when no count is given
+pub fn default_neighbors() -> usize {
+    2
+}
+
+// Most entries returned on each side of a ranked user
+pub const MAX_NEIGHBORS: usize = 50;
+
+#[derive(Deserialize)]
+pub struct PaginationParams {
+    #[serde(default = "default_count")]
+    pub count: usize,
+    #[serde(default)]
+    pub skip: usize,
+}
+
 pub fn success<T: Serialize>(data: T) -> Json<ApiResponse<T>> {
     Json(ApiResponse {
         status: "ok".to_string(),
@@ -0,0 +1,72 @@
+use crate::routes::games::parse_hex_as_32_bytes;
+use crate::routes::receipts::{respond, ReceiptsParams};
+use crate::routes::types::{error, success, ApiResult};
+use crate::state::types::{ReceiptEntry, VaultInfo, VaultReceipt};
+use crate::state::State;
+use crate::types::public::Public;
+use axum::{
+    extract::{Path, Query, State as AxumState},
+    http::StatusCode,
+    response::Response,
+};
+use std::sync::Arc;
+
+// Handler functions
+
+/// Get a summary of a vault's deposits and withdrawals
+pub async fn get_vault(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(vault): Path<Public>,
+) -> ApiResult<VaultInfo> {
+    let data = state.read().unwrap();
+    match data.vaults.get_vault_info(vault) {
+        Some(info) => Ok(success(info)),
+        None => Err(error("Vault not found", StatusCode::NOT_FOUND)),
+    }
+}
+
+/// Get vault deposit receipt info
+pub async fn get_vault_deposit(
+    AxumState(state): AxumState<Arc<State>>,
+    Path((vault, id)): Path<(Public, String)>,
+) -> ApiResult<Option<ReceiptEntry<VaultReceipt>>> {
+    let deposit_id = parse_hex_as_32_bytes(&id).map_err(|e| error(e, StatusCode::BAD_REQUEST))?;
+    let data = state.read().unwrap();
+    Ok(success(data.vaults.get_deposit_info(vault, deposit_id)))
+}
+
+/// Get vault withdraw receipt info
+pub async fn get_vault_withdrawal(
+    AxumState(state): AxumState<Arc<State>>,
+    Path((vault, id)): Path<(Public, String)>,
+) -> ApiResult<Option<ReceiptEntry<VaultReceipt>>> {
+    let withdraw_id = parse_hex_as_32_bytes(&id).map_err(|e| error(e, StatusCode::BAD_REQUEST))?;
+    let data = state.read().unwrap();
+    Ok(success(data.vaults.get_withdraw_info(vault, withdraw_id)))
+}
+
+/// List a vault's deposit receipts
+pub async fn vault_deposits(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(vault): Path<Public>,
+    Query(params): Query<ReceiptsParams>,
+) -> Response {
+    let list = {
+        let data = state.read().unwrap();
+        data.vaults.get_deposits(vault, params.query())
+    };
+    respond(list, params.format())
+}
+
+/// List a vault's withdraw receipts
+pub async fn vault_withdrawals(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(vault): Path<Public>,
+    Query(params): Query<ReceiptsParams>,
+) -> Response {
+    let list = {
+        let data = state.read().unwrap();
+        data.vaults.get_withdrawals(vault, params.query())
+    };
+    respond(list, params.format())
+}
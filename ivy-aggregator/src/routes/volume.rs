@@ -1,14 +1,17 @@
+use crate::state::components::volume::VolumeWindow;
+use crate::state::types::{VlbEntry, VolumeRank};
 use crate::state::State;
 use crate::types::public::Public;
+use crate::util::unix_timestamp;
 use axum::{
-    extract::{Path, State as AxumState},
+    extract::{Path, Query, State as AxumState},
     http::StatusCode,
     response::{IntoResponse, Json},
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-use crate::routes::types::{success, ApiResponse};
+use crate::routes::types::{default_count, default_neighbors, success, ApiResponse, MAX_NEIGHBORS};
 
 #[derive(Serialize)]
 struct ErrorResponse {
@@ -45,15 +48,79 @@ pub fn error(message: &str, status_code: StatusCode) -> AppError {
 #[derive(Deserialize)]
 pub struct VolumeMultipleRequest {
     users: Vec<Public>,
+    #[serde(default)]
+    window: VolumeWindow,
+}
+
+#[derive(Deserialize)]
+pub struct WindowParams {
+    #[serde(default)]
+    window: VolumeWindow,
+}
+
+#[derive(Deserialize)]
+pub struct VolumeBoardParams {
+    #[serde(default = "default_count")]
+    pub count: usize,
+    #[serde(default)]
+    pub skip: usize,
+    #[serde(default)]
+    pub window: VolumeWindow,
+}
+
+#[derive(Deserialize)]
+pub struct VolumeRankParams {
+    #[serde(default)]
+    pub window: VolumeWindow,
+    #[serde(default = "default_neighbors")]
+    pub neighbors: usize,
+}
+
+/// Move the volume windows to the current hour before they're queried,
+/// taking the write lock only if one moves
+pub fn expire_volume(state: &State) {
+    let now = unix_timestamp();
+    if state.read().unwrap().volume.needs_expiry(now) {
+        state.write().unwrap().volume.expire_at(now);
+    }
 }
 
 // Handler functions
 pub async fn get_volume(
     AxumState(state): AxumState<Arc<State>>,
     Path(user): Path<Public>,
+    Query(params): Query<WindowParams>,
 ) -> Json<ApiResponse<f32>> {
+    expire_volume(&state);
+    let data = state.read().unwrap();
+    success(data.volume.get_volume(user, params.window))
+}
+
+/// Get the protocol-wide volume leaderboard
+pub async fn global_volume_board(
+    AxumState(state): AxumState<Arc<State>>,
+    Query(params): Query<VolumeBoardParams>,
+) -> Json<ApiResponse<Vec<VlbEntry>>> {
+    expire_volume(&state);
+    let data = state.read().unwrap();
+    success(
+        data.volume
+            .query_global_volume_lb(params.window, params.count, params.skip),
+    )
+}
+
+/// Get a user's rank on the protocol-wide volume leaderboard
+pub async fn global_volume_rank(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(user): Path<Public>,
+    Query(params): Query<VolumeRankParams>,
+) -> Json<ApiResponse<VolumeRank>> {
+    expire_volume(&state);
     let data = state.read().unwrap();
-    success(data.volume.get_volume(user))
+    success(
+        data.volume
+            .get_global_rank(user, params.window, params.neighbors.min(MAX_NEIGHBORS)),
+    )
 }
 
 pub async fn volume_multiple(
@@ -74,6 +141,10 @@ pub async fn volume_multiple(
         ));
     }
 
+    expire_volume(&state);
     let data = state.read().unwrap();
-    Ok(success(data.volume.get_volume_multiple(&request.users)))
+    Ok(success(
+        data.volume
+            .get_volume_multiple(&request.users, request.window),
+    ))
 }
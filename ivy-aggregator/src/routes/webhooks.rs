@@ -0,0 +1,173 @@
+use crate::net::check_public_url;
+use crate::routes::auth::{require_admin_or_owner, OwnerRequest};
+use crate::routes::types::{default_count, error, success, ApiResult, AppError};
+use crate::state::types::{WebhookDelivery, WebhookInfo};
+use crate::state::{State, StateData};
+use crate::types::public::Public;
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State as AxumState},
+    http::{HeaderMap, Method, StatusCode, Uri},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+// Shortest secret we accept for signing payloads
+const MIN_SECRET_LEN: usize = 16;
+
+// Request and query parameter structs
+#[derive(Deserialize)]
+pub struct WebhookRequest {
+    url: String,
+    secret: String,
+}
+
+#[derive(Deserialize)]
+pub struct DeliveriesParams {
+    #[serde(default = "default_count")]
+    count: usize,
+    #[serde(default)]
+    skip: usize,
+}
+
+#[derive(Serialize)]
+pub struct DeliveriesResponse {
+    total: usize,
+    deliveries: Vec<WebhookDelivery>,
+}
+
+/// Check that a webhook's URL and secret are usable.
+/// The URL's host must resolve to public addresses only; deliveries
+/// check the addresses they connect to again, as they may change.
+pub async fn validate_webhook(url: &str, secret: &str) -> Result<(), AppError> {
+    let owned_url = url.to_string();
+    match tokio::task::spawn_blocking(move || check_public_url(&owned_url)).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            return Err(error(
+                &format!("Invalid webhook URL: {}", e),
+                StatusCode::BAD_REQUEST,
+            ))
+        }
+        Err(_) => {
+            return Err(error(
+                "Can't check webhook URL",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+    if secret.len() < MIN_SECRET_LEN {
+        return Err(error(
+            &format!(
+                "Webhook secret must be at least {} characters",
+                MIN_SECRET_LEN
+            ),
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+    Ok(())
+}
+
+/// Check that the request may manage `game`'s webhook
+fn authorize(
+    data: &StateData,
+    headers: &HeaderMap,
+    game: &Public,
+    request: &OwnerRequest,
+) -> Result<(), AppError> {
+    let Some(game_data) = data.games.get_game_by_address(game) else {
+        return Err(error("Game not found", StatusCode::NOT_FOUND));
+    };
+    require_admin_or_owner(headers, &game_data.owner, request)
+}
+
+// Handler functions
+
+/// Get a game's webhook
+pub async fn get_webhook(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(game): Path<Public>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> ApiResult<Option<WebhookInfo>> {
+    let request = OwnerRequest {
+        method: &method,
+        path: uri.path(),
+        body: &[],
+    };
+    let data = state.read().unwrap();
+    authorize(&data, &headers, &game, &request)?;
+    Ok(success(data.webhooks.get_webhook(&game)))
+}
+
+/// Register or replace a game's webhook
+pub async fn put_webhook(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(game): Path<Public>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<WebhookInfo> {
+    // Owners sign the body as sent, so parse it ourselves
+    let webhook: WebhookRequest = serde_json::from_slice(&body).map_err(|e| {
+        error(
+            &format!("Invalid request body: {}", e),
+            StatusCode::BAD_REQUEST,
+        )
+    })?;
+    validate_webhook(&webhook.url, &webhook.secret).await?;
+    let request = OwnerRequest {
+        method: &method,
+        path: uri.path(),
+        body: &body,
+    };
+    let mut data = state.write().unwrap();
+    authorize(&data, &headers, &game, &request)?;
+    Ok(success(data.webhooks.register(
+        game,
+        webhook.url,
+        webhook.secret,
+    )))
+}
+
+/// Remove a game's webhook
+pub async fn delete_webhook(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(game): Path<Public>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> ApiResult<bool> {
+    let request = OwnerRequest {
+        method: &method,
+        path: uri.path(),
+        body: &[],
+    };
+    let mut data = state.write().unwrap();
+    authorize(&data, &headers, &game, &request)?;
+    Ok(success(data.webhooks.unregister(&game)))
+}
+
+/// Get the log of a game's webhook deliveries, newest first
+pub async fn webhook_deliveries(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(game): Path<Public>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    Query(params): Query<DeliveriesParams>,
+) -> ApiResult<DeliveriesResponse> {
+    let request = OwnerRequest {
+        method: &method,
+        path: uri.path(),
+        body: &[],
+    };
+    let data = state.read().unwrap();
+    authorize(&data, &headers, &game, &request)?;
+    let (total, deliveries) = data
+        .webhooks
+        .get_deliveries(&game, params.count, params.skip);
+    Ok(success(DeliveriesResponse { total, deliveries }))
+}
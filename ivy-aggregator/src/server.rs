@@ -1,14 +1,16 @@
+use crate::routes::limits::{limit, Limiter, LimitsConfig};
 use crate::routes::stream_assets::stream_assets;
 use crate::routes::stream_game::stream_game;
 use crate::routes::stream_ivy::stream_ivy;
 use crate::routes::stream_sync::stream_sync;
 use crate::routes::stream_trades::stream_trades;
-use crate::routes::sync::get_sync;
+use crate::routes::sync::{get_sync, list_syncs, sync_liquidity, sync_liquidity_history};
 use crate::state::State;
 use axum::extract::Request;
+use axum::middleware;
 use axum::ServiceExt;
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 use std::{net::SocketAddr, sync::Arc};
@@ -16,13 +18,31 @@ use tower_http::normalize_path::NormalizePathLayer;
 use tower_layer::Layer;
 
 // Import route handlers
-use crate::routes::assets::{assets_count, get_asset, list_assets, pnl_board, volume_board};
+use crate::routes::alerts::{create_alert, delete_alert, list_alerts, stream_alerts};
+use crate::routes::assets::{
+    assets_count, get_asset, holders, hot_breakdown, list_assets, pnl_board, pnl_rank,
+    volume_board, volume_rank,
+};
+use crate::routes::charts::{asset_chart, ivy_chart};
 use crate::routes::comments::get_comments;
+use crate::routes::fees::{game_fees, game_fees_daily, protocol_fees};
 use crate::routes::games::{get_burn_info, get_deposit_info, get_game, get_withdraw_info};
-use crate::routes::info::{global_info, ivy_info};
+use crate::routes::hydration::{
+    list_hydration_jobs, retry_failed_hydration_jobs, retry_hydration_job,
+};
+use crate::routes::info::{global_info, ivy_info, ivy_supply};
 use crate::routes::misc::{root, validate_address};
-use crate::routes::price::ivy_price;
-use crate::routes::volume::{get_volume, volume_multiple};
+use crate::routes::price::{
+    clear_sol_corrections, create_sol_correction, ivy_price, list_sol_corrections, revalue_state,
+    sol_price, sol_price_at, sol_price_history,
+};
+use crate::routes::receipts::{daily_receipts, list_burns, list_deposits, list_withdrawals};
+use crate::routes::stats::stats_timeseries;
+use crate::routes::vaults::{
+    get_vault, get_vault_deposit, get_vault_withdrawal, vault_deposits, vault_withdrawals,
+};
+use crate::routes::volume::{get_volume, global_volume_board, global_volume_rank, volume_multiple};
+use crate::routes::webhooks::{delete_webhook, get_webhook, put_webhook, webhook_deliveries};
 
 pub fn create_router(state: Arc<State>) -> Router {
     Router::new()
@@ -30,14 +50,27 @@ pub fn create_router(state: Arc<State>) -> Router {
         .route("/", get(root))
         // === GAME ROUTES ===
         .route("/games/{address}", get(get_game))
+        .route("/games/{address}/fees", get(game_fees))
+        .route("/games/{address}/fees/daily", get(game_fees_daily))
         // === SYNC ROUTES ===
+        .route("/syncs", get(list_syncs))
         .route("/syncs/{address}", get(get_sync))
+        .route("/syncs/{address}/liquidity", get(sync_liquidity))
+        .route(
+            "/syncs/{address}/liquidity/history",
+            get(sync_liquidity_history),
+        )
         // === ASSETS ROUTES ===
         .route("/assets", get(list_assets))
         .route("/assets/count", get(assets_count))
         .route("/assets/{address}", get(get_asset))
+        .route("/assets/{address}/charts/{kind}", get(asset_chart))
         .route("/assets/{address}/volume_board", get(volume_board))
+        .route("/assets/{address}/volume_board/{user}", get(volume_rank))
+        .route("/assets/{address}/holders", get(holders))
+        .route("/assets/{address}/hot", get(hot_breakdown))
         .route("/assets/{address}/pnl_board", get(pnl_board))
+        .route("/assets/{address}/pnl_board/{user}", get(pnl_rank))
         .route(
             "/assets/{address}/pnl/{user}",
             get(crate::routes::assets::get_pnl),
@@ -45,6 +78,8 @@ pub fn create_router(state: Arc<State>) -> Router {
         // === VOLUME ROUTES ===
         .route("/volume/{user}", get(get_volume))
         .route("/volume/multiple", post(volume_multiple))
+        .route("/volume_board", get(global_volume_board))
+        .route("/volume_board/{user}", get(global_volume_rank))
         // === COMMENT ROUTES ===
         .route("/comments/{game}", get(get_comments))
         // === STREAMING ROUTES ===
@@ -55,13 +90,53 @@ pub fn create_router(state: Arc<State>) -> Router {
         .route("/syncs/{address}/stream", get(stream_sync))
         // === PRICE ROUTES ===
         .route("/ivy/price", get(ivy_price))
+        .route("/ivy/charts/{kind}", get(ivy_chart))
+        .route("/sol/price", get(sol_price))
+        .route("/sol/price/history", get(sol_price_history))
+        .route("/sol/price/at/{timestamp}", get(sol_price_at))
+        .route(
+            "/sol/price/corrections",
+            get(list_sol_corrections)
+                .post(create_sol_correction)
+                .delete(clear_sol_corrections),
+        )
+        .route("/revalue", post(revalue_state))
+        .route("/hydration", get(list_hydration_jobs))
+        .route("/hydration/retry", post(retry_failed_hydration_jobs))
+        .route("/hydration/{asset}/retry", post(retry_hydration_job))
         // === DEPOSIT ROUTE ===
+        .route("/games/{game}/burns", get(list_burns))
+        .route("/games/{game}/deposits", get(list_deposits))
+        .route("/games/{game}/withdrawals", get(list_withdrawals))
+        .route("/games/{game}/receipts/daily", get(daily_receipts))
         .route("/games/{game}/burns/{id}", get(get_burn_info))
         .route("/games/{game}/deposits/{id}", get(get_deposit_info))
         .route("/games/{game}/withdrawals/{id}", get(get_withdraw_info))
+        // === WEBHOOK ROUTES ===
+        .route(
+            "/games/{game}/webhook",
+            get(get_webhook).put(put_webhook).delete(delete_webhook),
+        )
+        .route("/games/{game}/webhook/deliveries", get(webhook_deliveries))
+        // === VAULT ROUTES ===
+        .route("/vaults/{vault}", get(get_vault))
+        .route("/vaults/{vault}/deposits", get(vault_deposits))
+        .route("/vaults/{vault}/deposits/{id}", get(get_vault_deposit))
+        .route("/vaults/{vault}/withdrawals", get(vault_withdrawals))
+        .route(
+            "/vaults/{vault}/withdrawals/{id}",
+            get(get_vault_withdrawal),
+        )
+        // === ALERT ROUTES ===
+        .route("/alerts", get(list_alerts).post(create_alert))
+        .route("/alerts/stream", get(stream_alerts))
+        .route("/alerts/{id}", delete(delete_alert))
         // === INFO ROUTES ===
         .route("/ivy/info", get(ivy_info))
+        .route("/ivy/supply", get(ivy_supply))
         .route("/global-info", get(global_info))
+        .route("/stats/timeseries", get(stats_timeseries))
+        .route("/fees", get(protocol_fees))
         // === MISC ROUTES ===
         .route("/validate/address/{address}", get(validate_address))
         // Add state and CORS
@@ -71,19 +146,29 @@ pub fn create_router(state: Arc<State>) -> Router {
 pub struct Server {
     addr: SocketAddr,
     state: Arc<State>,
+    limits: LimitsConfig,
 }
 
 impl Server {
-    pub const fn new(addr: SocketAddr, state: Arc<State>) -> Self {
-        Self { addr, state }
+    pub const fn new(addr: SocketAddr, state: Arc<State>, limits: LimitsConfig) -> Self {
+        Self {
+            addr,
+            state,
+            limits,
+        }
     }
     pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
-        let app = create_router(self.state);
+        let limiter = Arc::new(Limiter::new(self.limits));
+        let app = create_router(self.state).layer(middleware::from_fn_with_state(limiter, limit));
         let app = NormalizePathLayer::trim_trailing_slash().layer(app);
         println!("Now listening on {}", self.addr);
 
         let listener = tokio::net::TcpListener::bind(self.addr).await?;
-        axum::serve(listener, ServiceExt::<Request>::into_make_service(app)).await?;
+        axum::serve(
+            listener,
+            ServiceExt::<Request>::into_make_service_with_connect_info::<SocketAddr>(app),
+        )
+        .await?;
 
         Ok(())
     }
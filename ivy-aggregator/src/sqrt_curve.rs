@@ -11,4 +11,20 @@ impl SqrtCurve {
     pub fn current_price(supply: f64, input_scale: f64) -> f64 {
         (supply * input_scale).sqrt()
     }
+
+    /// Calculate the reserve paid for the supply between `a` and `b`,
+    /// the integral of the price from `a` to `b`:
+    /// (2/3)(sqrt(c)b^(3/2) - sqrt(c)a^(3/2))
+    pub fn integrate(a: f64, b: f64, input_scale: f64) -> f64 {
+        if b <= a {
+            return 0.0;
+        }
+        (2.0 / 3.0) * input_scale.sqrt() * (b.powf(1.5) - a.powf(1.5))
+    }
+
+    /// Calculate the supply reached by paying `reserve` into the curve at `supply`:
+    /// ((3y)/sqrt(4c) + a^(3/2))^(2/3)
+    pub fn right_bound(supply: f64, reserve: f64, input_scale: f64) -> f64 {
+        (3.0 * reserve / (4.0 * input_scale).sqrt() + supply.powf(1.5)).powf(2.0 / 3.0)
+    }
 }
@@ -0,0 +1,431 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::mpsc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, watch};
+
+use crate::applier::Cursor;
+use crate::state::types::{Alert, WebhookDelivery};
+use crate::types::event::{Event, EventData};
+use crate::types::public::Public;
+use crate::types::trade::Trade;
+use crate::util::unix_timestamp;
+
+use super::sync::SyncComponent;
+use super::webhooks::{WebhookJob, WebhookSource};
+
+// 64 alerts before receiver is deemed lagged
+const CHANNEL_BUFFER_SIZE: usize = 64;
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// The asset's price rises to `price_usd` or above
+    PriceAbove { asset: Public, price_usd: f32 },
+    /// The asset's price falls to `price_usd` or below
+    PriceBelow { asset: Public, price_usd: f32 },
+    /// The asset's market cap rises to `mkt_cap_usd` or above
+    MktCapAbove { asset: Public, mkt_cap_usd: f32 },
+    /// The asset's market cap falls to `mkt_cap_usd` or below
+    MktCapBelow { asset: Public, mkt_cap_usd: f32 },
+    /// A trade of at least `min_volume_usd`, on `asset` or on any asset
+    WhaleTrade {
+        asset: Option<Public>,
+        min_volume_usd: f32,
+    },
+    /// `sync` migrates, or any sync migrates
+    SyncMigrated { sync: Option<Public> },
+}
+
+impl AlertCondition {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AlertCondition::PriceAbove { .. } => "price_above",
+            AlertCondition::PriceBelow { .. } => "price_below",
+            AlertCondition::MktCapAbove { .. } => "mkt_cap_above",
+            AlertCondition::MktCapBelow { .. } => "mkt_cap_below",
+            AlertCondition::WhaleTrade { .. } => "whale_trade",
+            AlertCondition::SyncMigrated { .. } => "sync_migrated",
+        }
+    }
+
+    /// The asset the condition watches, or `None` if it watches every asset
+    pub fn asset(&self) -> Option<Public> {
+        match *self {
+            AlertCondition::PriceAbove { asset, .. }
+            | AlertCondition::PriceBelow { asset, .. }
+            | AlertCondition::MktCapAbove { asset, .. }
+            | AlertCondition::MktCapBelow { asset, .. } => Some(asset),
+            AlertCondition::WhaleTrade { asset, .. } => asset,
+            AlertCondition::SyncMigrated { sync } => sync,
+        }
+    }
+
+    /// The threshold of the condition, if it has one
+    pub fn threshold(&self) -> Option<f32> {
+        match *self {
+            AlertCondition::PriceAbove { price_usd, .. }
+            | AlertCondition::PriceBelow { price_usd, .. } => Some(price_usd),
+            AlertCondition::MktCapAbove { mkt_cap_usd, .. }
+            | AlertCondition::MktCapBelow { mkt_cap_usd, .. } => Some(mkt_cap_usd),
+            AlertCondition::WhaleTrade { min_volume_usd, .. } => Some(min_volume_usd),
+            AlertCondition::SyncMigrated { .. } => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum AlertDelivery {
+    /// POST the alert to `url`, signed like game webhooks
+    Webhook { url: String, secret: String },
+    /// Send the alert to the client's stream
+    Sse,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AlertRule {
+    pub id: u64,
+    /// The client that registered the rule
+    pub client: String,
+    pub condition: AlertCondition,
+    pub delivery: AlertDelivery,
+    pub created_at: u64,
+}
+
+/// An alert rule as shown to clients, without its webhook secret
+#[derive(Serialize, Clone)]
+pub struct AlertRuleInfo {
+    pub id: u64,
+    pub client: String,
+    pub condition: AlertCondition,
+    /// "webhook" or "sse"
+    pub delivery: &'static str,
+    pub url: Option<String>,
+    pub created_at: u64,
+    /// The latest webhook delivery attempt for this rule
+    pub last_delivery: Option<WebhookDelivery>,
+}
+
+/// Evaluates alert rules against each event applied after initialization,
+/// and delivers the alerts they fire by webhook or to the client's stream.
+pub struct AlertsComponent {
+    rules: BTreeMap<u64, AlertRule>,
+    // Rules by the asset they watch, so that only those of the traded
+    // or migrated asset are evaluated
+    asset_rules: HashMap<Public, Vec<u64>>,
+    // Rules that watch every asset
+    any_asset_rules: Vec<u64>,
+    // Number of rules of each client
+    client_rules: HashMap<String, usize>,
+    store: Cursor<Vec<AlertRule>>,
+    next_id: u64,
+    // The latest value seen by each threshold rule, to detect crossings
+    last_values: HashMap<u64, f32>,
+    last_deliveries: HashMap<u64, WebhookDelivery>,
+    trades_rx: watch::Receiver<Option<Trade>>,
+    webhook_tx: mpsc::Sender<WebhookJob>,
+    client_txs: HashMap<String, broadcast::Sender<Alert>>,
+    // Alerts shouldn't fire for replayed events
+    is_initialized: bool,
+}
+
+impl AlertsComponent {
+    pub fn new(
+        mut store: Cursor<Vec<AlertRule>>,
+        trades_rx: watch::Receiver<Option<Trade>>,
+        webhook_tx: mpsc::Sender<WebhookJob>,
+    ) -> Self {
+        let rules = match store.read() {
+            Ok(rules) => rules.unwrap_or_default(),
+            Err(e) => {
+                eprintln!("warning: Can't read alert rules: {}", e);
+                Vec::new()
+            }
+        };
+        let next_id = rules.iter().map(|r| r.id + 1).max().unwrap_or(1);
+        let mut alerts = Self {
+            rules: BTreeMap::new(),
+            asset_rules: HashMap::new(),
+            any_asset_rules: Vec::new(),
+            client_rules: HashMap::new(),
+            store,
+            next_id,
+            last_values: HashMap::new(),
+            last_deliveries: HashMap::new(),
+            trades_rx,
+            webhook_tx,
+            client_txs: HashMap::new(),
+            is_initialized: false,
+        };
+        for rule in rules {
+            alerts.index(rule);
+        }
+        alerts
+    }
+
+    /// Insert a rule and index it
+    fn index(&mut self, rule: AlertRule) {
+        match rule.condition.asset() {
+            Some(asset) => self.asset_rules.entry(asset).or_default().push(rule.id),
+            None => self.any_asset_rules.push(rule.id),
+        }
+        *self.client_rules.entry(rule.client.clone()).or_default() += 1;
+        self.rules.insert(rule.id, rule);
+    }
+
+    /// Remove a rule from the indexes
+    fn unindex(&mut self, rule: &AlertRule) {
+        match rule.condition.asset() {
+            Some(asset) => {
+                if let Some(ids) = self.asset_rules.get_mut(&asset) {
+                    ids.retain(|&id| id != rule.id);
+                    if ids.is_empty() {
+                        self.asset_rules.remove(&asset);
+                    }
+                }
+            }
+            None => self.any_asset_rules.retain(|&id| id != rule.id),
+        }
+        if let Some(count) = self.client_rules.get_mut(&rule.client) {
+            *count -= 1;
+            if *count == 0 {
+                self.client_rules.remove(&rule.client);
+            }
+        }
+    }
+
+    /// Evaluate the rules against `event`, after all other components have applied it
+    pub fn on_event(&mut self, event: &Event, syncs: &SyncComponent) -> bool {
+        if let EventData::Initialize(_) = event.data {
+            self.is_initialized = true;
+            // Don't alert on the last replayed trade
+            self.trades_rx.borrow_and_update();
+            return false;
+        }
+        if !self.is_initialized {
+            return false;
+        }
+
+        // The event's trade, if it had one
+        let trade = match self.trades_rx.has_changed() {
+            Ok(true) => self.trades_rx.borrow_and_update().clone(),
+            _ => None,
+        };
+        let migrated = match &event.data {
+            EventData::PfMigration(d) => syncs.sync_address_for_mint(&d.mint),
+            _ => None,
+        };
+        if trade.is_none() && migrated.is_none() {
+            return false;
+        }
+
+        // Only the rules of the assets involved, and those watching every asset
+        let traded = trade.as_ref().map(|t| t.asset);
+        let rule_ids = [traded, migrated.filter(|&m| Some(m) != traded)]
+            .into_iter()
+            .flatten()
+            .filter_map(|asset| self.asset_rules.get(&asset))
+            .flatten()
+            .chain(&self.any_asset_rules);
+
+        let mut fired = Vec::new();
+        for id in rule_ids {
+            let rule = &self.rules[id];
+            let last_values = &mut self.last_values;
+            let fired_value = match (rule.condition, &trade, migrated) {
+                (AlertCondition::PriceAbove { asset, price_usd }, Some(t), _)
+                    if t.asset == asset =>
+                {
+                    crossed(last_values, rule.id, t.price_usd, |v| v >= price_usd)
+                        .map(|v| (asset, Some(v)))
+                }
+                (AlertCondition::PriceBelow { asset, price_usd }, Some(t), _)
+                    if t.asset == asset =>
+                {
+                    crossed(last_values, rule.id, t.price_usd, |v| v <= price_usd)
+                        .map(|v| (asset, Some(v)))
+                }
+                (AlertCondition::MktCapAbove { asset, mkt_cap_usd }, Some(t), _)
+                    if t.asset == asset =>
+                {
+                    crossed(last_values, rule.id, t.mkt_cap_usd, |v| v >= mkt_cap_usd)
+                        .map(|v| (asset, Some(v)))
+                }
+                (AlertCondition::MktCapBelow { asset, mkt_cap_usd }, Some(t), _)
+                    if t.asset == asset =>
+                {
+                    crossed(last_values, rule.id, t.mkt_cap_usd, |v| v <= mkt_cap_usd)
+                        .map(|v| (asset, Some(v)))
+                }
+                (
+                    AlertCondition::WhaleTrade {
+                        asset,
+                        min_volume_usd,
+                    },
+                    Some(t),
+                    _,
+                ) if asset.is_none_or(|a| a == t.asset) && t.volume_usd >= min_volume_usd => {
+                    Some((t.asset, Some(t.volume_usd)))
+                }
+                (AlertCondition::SyncMigrated { sync }, _, Some(migrated))
+                    if sync.is_none_or(|s| s == migrated) =>
+                {
+                    Some((migrated, None))
+                }
+                _ => None,
+            };
+            if let Some((asset, value)) = fired_value {
+                fired.push(Alert {
+                    rule_id: rule.id,
+                    client: rule.client.clone(),
+                    kind: rule.condition.kind(),
+                    asset,
+                    value,
+                    threshold: rule.condition.threshold(),
+                    signature: event.signature,
+                    timestamp: event.timestamp,
+                });
+            }
+        }
+
+        for alert in fired {
+            self.deliver(alert);
+        }
+
+        // Alerts don't need their own events persisted
+        false
+    }
+
+    fn deliver(&mut self, alert: Alert) {
+        let Some(rule) = self.rules.get(&alert.rule_id) else {
+            return;
+        };
+        match &rule.delivery {
+            AlertDelivery::Webhook { url, secret } => {
+                _ = self.webhook_tx.send(WebhookJob {
+                    source: WebhookSource::Alert(rule.id),
+                    delivery_id: format!("alert:{}:{}", rule.id, alert.signature),
+                    url: url.clone(),
+                    secret: secret.clone(),
+                    body: serde_json::to_string(&alert).unwrap(),
+                });
+            }
+            AlertDelivery::Sse => {
+                if let Some(tx) = self.client_txs.get(&rule.client) {
+                    if tx.send(alert).is_err() {
+                        // No receivers, drop the channel to save memory
+                        self.client_txs.remove(&rule.client);
+                    }
+                }
+            }
+        }
+    }
+
+    fn persist(&mut self) {
+        let rules: Vec<_> = self.rules.values().cloned().collect();
+        if let Err(e) = self.store.write(&rules) {
+            eprintln!("warning: Can't write alert rules: {}", e);
+        }
+    }
+
+    fn to_info(&self, rule: &AlertRule) -> AlertRuleInfo {
+        let (delivery, url) = match &rule.delivery {
+            AlertDelivery::Webhook { url, .. } => ("webhook", Some(url.clone())),
+            AlertDelivery::Sse => ("sse", None),
+        };
+        AlertRuleInfo {
+            id: rule.id,
+            client: rule.client.clone(),
+            condition: rule.condition,
+            delivery,
+            url,
+            created_at: rule.created_at,
+            last_delivery: self.last_deliveries.get(&rule.id).cloned(),
+        }
+    }
+
+    /// Add a rule, returning it
+    pub fn add_rule(
+        &mut self,
+        client: String,
+        condition: AlertCondition,
+        delivery: AlertDelivery,
+    ) -> AlertRuleInfo {
+        let rule = AlertRule {
+            id: self.next_id,
+            client,
+            condition,
+            delivery,
+            created_at: unix_timestamp(),
+        };
+        self.next_id += 1;
+        let info = self.to_info(&rule);
+        self.index(rule);
+        self.persist();
+        info
+    }
+
+    /// The number of rules registered by `client`
+    pub fn rule_count(&self, client: &str) -> usize {
+        self.client_rules.get(client).copied().unwrap_or(0)
+    }
+
+    /// Remove a rule, if it was registered by `client` (or by anyone if `None`),
+    /// returning whether it was removed
+    pub fn remove_rule(&mut self, id: u64, client: Option<&str>) -> bool {
+        let Some(rule) = self.rules.get(&id) else {
+            return false;
+        };
+        if client.is_some_and(|c| rule.client != c) {
+            return false;
+        }
+        if let Some(rule) = self.rules.remove(&id) {
+            self.unindex(&rule);
+        }
+        self.last_values.remove(&id);
+        self.last_deliveries.remove(&id);
+        self.persist();
+        true
+    }
+
+    /// List rules, optionally only those registered by `client`
+    pub fn get_rules(&self, client: Option<&str>) -> Vec<AlertRuleInfo> {
+        self.rules
+            .values()
+            .filter(|rule| client.is_none_or(|c| rule.client == c))
+            .map(|rule| self.to_info(rule))
+            .collect()
+    }
+
+    /// Subscribe to the alerts of `client`'s rules with SSE delivery
+    pub fn subscribe(&mut self, client: &str) -> broadcast::Receiver<Alert> {
+        match self.client_txs.get(client) {
+            Some(tx) => tx.subscribe(),
+            None => {
+                let (tx, rx) = broadcast::channel(CHANNEL_BUFFER_SIZE);
+                self.client_txs.insert(client.to_string(), tx);
+                rx
+            }
+        }
+    }
+
+    /// Record a webhook delivery attempt for a rule
+    pub fn on_delivery(&mut self, rule_id: u64, delivery: WebhookDelivery) {
+        if self.rules.contains_key(&rule_id) {
+            self.last_deliveries.insert(rule_id, delivery);
+        }
+    }
+}
+
+/// Record `value` as the rule's last value, returning it if it satisfies
+/// `is_past` while the previous one didn't
+fn crossed(
+    last_values: &mut HashMap<u64, f32>,
+    rule_id: u64,
+    value: f32,
+    is_past: impl Fn(f32) -> bool,
+) -> Option<f32> {
+    let last = last_values.insert(rule_id, value)?;
+    (is_past(value) && !is_past(last)).then_some(value)
+}
@@ -1,15 +1,25 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 
-use crate::state::constants::{
-    MAX_FEATURED_ASSETS, MAX_HOT_ASSETS, MAX_HOT_ASSET_AGE, MIN_HOT_ASSET_COUNT,
-};
-use crate::state::helpers::{calculate_hot_score, normalize_string};
+use crate::state::constants::MAX_FEATURED_ASSETS;
+use crate::state::hot::{HotConfig, HotRanking};
+use crate::state::types::HotBreakdown;
 use crate::types::asset::Asset;
-use std::time::SystemTime;
+use crate::types::game::Game;
+use crate::types::public::Public;
+use crate::types::search::SearchIndex;
+use crate::types::sync::Sync;
+use crate::types::trade::Trade;
+use crate::util::{unix_timestamp, usd_to_mil};
+use serde::Deserialize;
 
 use super::games::GamesComponent;
 use super::sync::SyncComponent;
 
+const SECONDS_PER_HOUR: u64 = 60 * 60;
+const VOLUME_WINDOW_HOURS: u64 = 24;
+
+type AssetKey = (usize, bool); // (index, is_sync)
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TopAssetEntry {
     mkt_cap_usd_cents: u64, // Store as cents to avoid float comparison
@@ -18,340 +28,537 @@ pub struct TopAssetEntry {
     is_sync: bool,
 }
 
-pub struct AssetsComponent {
-    pub top_assets: BTreeSet<TopAssetEntry>,
-    pub hot_asset_indices: Vec<(usize, bool)>, // (index, is_sync)
+/// The order in which to list assets
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AssetOrder {
+    Recent,
+    Top,
+    Hot,
+    /// Highest 24h volume first
+    Volume,
+    /// Highest 24h change first
+    Gainers,
+    /// Lowest 24h change first
+    Losers,
+    /// Most recently traded first
+    LastTrade,
+    /// Most holders first
+    Holders,
+    /// Closest to migration first (unmigrated syncs only)
+    Progress,
 }
 
-impl AssetsComponent {
-    pub fn new() -> Self {
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetKind {
+    Game,
+    Sync,
+}
+
+/// Restricts which assets are listed. Each bound is inclusive.
+#[derive(Default)]
+pub struct AssetFilter {
+    /// Only list assets whose name or symbol match this,
+    /// most relevant first (ties are broken by the requested order)
+    pub query: Option<String>,
+    pub kind: Option<AssetKind>,
+    pub min_mkt_cap_usd: Option<f32>,
+    pub max_mkt_cap_usd: Option<f32>,
+    pub min_volume_24h_usd: Option<f32>,
+    pub max_volume_24h_usd: Option<f32>,
+    pub min_change_pct_24h: Option<f32>,
+    pub max_change_pct_24h: Option<f32>,
+    /// Minimum age, in seconds
+    pub min_age: Option<u64>,
+    /// Maximum age, in seconds
+    pub max_age: Option<u64>,
+    /// Only games can't migrate, so filtering on this excludes them
+    pub migrated: Option<bool>,
+}
+
+/// The per-asset values our sort indexes are keyed by
+#[derive(Clone, Copy, PartialEq)]
+struct AssetStats {
+    create_timestamp: u64,
+    mkt_cap_usd: f32,
+    change_bps_24h: i64,
+    volume_24h_mil: u64,
+    last_trade: u64,
+    holders: u64,
+    // Migration progress in parts per million, for unmigrated syncs
+    progress_ppm: Option<u32>,
+    is_migrated: bool,
+}
+
+impl AssetStats {
+    fn from_game(game: &Game) -> Self {
         Self {
-            top_assets: BTreeSet::new(),
-            hot_asset_indices: Vec::new(),
+            create_timestamp: game.create_timestamp,
+            mkt_cap_usd: game.mkt_cap_usd,
+            change_bps_24h: (game.change_pct_24h * 100.0) as i64,
+            volume_24h_mil: 0,
+            last_trade: 0,
+            holders: game.holders,
+            progress_ppm: None,
+            is_migrated: false,
         }
     }
 
-    /// Called when a new game is created
-    pub fn on_game_created(&mut self, index: usize, mkt_cap_usd: f32, create_timestamp: u64) {
-        self.top_assets.insert(TopAssetEntry {
-            mkt_cap_usd_cents: (mkt_cap_usd * 100.0) as u64,
-            create_timestamp,
-            index,
-            is_sync: false,
-        });
+    fn from_sync(sync: &Sync) -> Self {
+        Self {
+            create_timestamp: sync.create_timestamp,
+            mkt_cap_usd: sync.mkt_cap_usd,
+            change_bps_24h: (sync.change_pct_24h * 100.0) as i64,
+            volume_24h_mil: 0,
+            last_trade: 0,
+            holders: sync.holders,
+            progress_ppm: match sync.is_migrated {
+                true => None,
+                false => Some((sync.migration_progress() * 1_000_000.0) as u32),
+            },
+            is_migrated: sync.is_migrated,
+        }
     }
 
-    /// Called when a game's market cap changes (e.g., after a swap)
-    pub fn on_game_updated(
-        &mut self,
-        index: usize,
-        old_mkt_cap_usd: f32,
-        new_mkt_cap_usd: f32,
-        create_timestamp: u64,
-    ) {
-        // Remove old entry
-        self.top_assets.remove(&TopAssetEntry {
-            mkt_cap_usd_cents: (old_mkt_cap_usd * 100.0) as u64,
-            create_timestamp,
-            index,
-            is_sync: false,
-        });
-
-        // Add new entry
-        self.top_assets.insert(TopAssetEntry {
-            mkt_cap_usd_cents: (new_mkt_cap_usd * 100.0) as u64,
-            create_timestamp,
-            index,
-            is_sync: false,
-        });
+    fn mkt_cap_usd_cents(&self) -> u64 {
+        (self.mkt_cap_usd * 100.0) as u64
     }
 
-    /// Called when a new sync is created
-    pub fn on_sync_created(&mut self, index: usize, mkt_cap_usd: f32, create_timestamp: u64) {
-        self.top_assets.insert(TopAssetEntry {
-            mkt_cap_usd_cents: (mkt_cap_usd * 100.0) as u64,
-            create_timestamp,
-            index,
-            is_sync: true,
-        });
+    /// `change_bps_24h`, mapped to a `u64` with the same ordering
+    fn change_key(&self) -> u64 {
+        change_key(self.change_bps_24h)
     }
+}
 
-    /// Called when a sync's market cap changes
-    pub fn on_sync_updated(
-        &mut self,
-        index: usize,
-        old_mkt_cap_usd: f32,
-        new_mkt_cap_usd: f32,
-        create_timestamp: u64,
-    ) {
-        // Remove old entry
-        self.top_assets.remove(&TopAssetEntry {
-            mkt_cap_usd_cents: (old_mkt_cap_usd * 100.0) as u64,
-            create_timestamp,
-            index,
-            is_sync: true,
-        });
-
-        // Add new entry
-        self.top_assets.insert(TopAssetEntry {
-            mkt_cap_usd_cents: (new_mkt_cap_usd * 100.0) as u64,
-            create_timestamp,
-            index,
-            is_sync: true,
-        });
+/// A change in basis points, mapped to a `u64` with the same ordering
+fn change_key(change_bps: i64) -> u64 {
+    (change_bps as u64) ^ (1 << 63)
+}
+
+impl AssetFilter {
+    /// The inclusive range of market caps, in cents, that pass the filter
+    fn mkt_cap_range(&self) -> (u64, u64) {
+        (
+            self.min_mkt_cap_usd
+                .map_or(0, |v| (v * 100.0).ceil() as u64),
+            self.max_mkt_cap_usd
+                .map_or(u64::MAX, |v| (v * 100.0) as u64),
+        )
     }
 
-    /// Calculate the hot list based on current data (uses readonly references)
-    pub fn calculate_hot_list(
-        &self,
-        games: &GamesComponent,
-        syncs: &SyncComponent,
-    ) -> Vec<(usize, bool)> {
-        let now = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        let mut scored_assets: Vec<(f32, usize, bool)> = Vec::new();
-
-        // Score games
-        for (idx, game) in games.game_list.iter().enumerate().rev() {
-            let age_seconds = now.saturating_sub(game.create_timestamp);
-            if age_seconds > MAX_HOT_ASSET_AGE && scored_assets.len() > MIN_HOT_ASSET_COUNT {
-                break;
-            }
-            let hot_score = calculate_hot_score(game.mkt_cap_usd, age_seconds);
-            scored_assets.push((hot_score, idx, false));
-        }
+    /// The inclusive range of 24h volumes, in mil, that pass the filter
+    fn volume_range(&self) -> (u64, u64) {
+        (
+            self.min_volume_24h_usd
+                .map_or(0, |v| (v * 1000.0).ceil() as u64),
+            self.max_volume_24h_usd.map_or(u64::MAX, usd_to_mil),
+        )
+    }
 
-        // Score syncs
-        for (idx, sync) in syncs.syncs.iter().enumerate().rev() {
-            let age_seconds = now.saturating_sub(sync.create_timestamp);
-            if age_seconds > MAX_HOT_ASSET_AGE && scored_assets.len() > MIN_HOT_ASSET_COUNT {
-                break;
-            }
-            let hot_score = calculate_hot_score(sync.mkt_cap_usd, age_seconds);
-            scored_assets.push((hot_score, idx, true));
-        }
+    /// The inclusive range of 24h change keys that pass the filter
+    fn change_range(&self) -> (u64, u64) {
+        (
+            self.min_change_pct_24h
+                .map_or(0, |v| change_key((v * 100.0).ceil() as i64)),
+            self.max_change_pct_24h
+                .map_or(u64::MAX, |v| change_key((v * 100.0).floor() as i64)),
+        )
+    }
 
-        // Sort by score descending
-        scored_assets.sort_by(|a, b| b.0.total_cmp(&a.0));
+    /// The inclusive range of creation timestamps that pass the filter at `now`
+    fn created_range(&self, now: u64) -> (u64, u64) {
+        (
+            self.max_age.map_or(0, |v| now.saturating_sub(v)),
+            self.min_age.map_or(u64::MAX, |v| now.saturating_sub(v)),
+        )
+    }
 
-        // Return the top hot assets
-        scored_assets
-            .into_iter()
-            .take(MAX_HOT_ASSETS)
-            .map(|(_, idx, is_sync)| (idx, is_sync))
-            .collect()
+    fn matches(&self, is_sync: bool, stats: &AssetStats, now: u64) -> bool {
+        let within = |(min, max): (u64, u64), v: u64| min <= v && v <= max;
+        match self.kind {
+            Some(AssetKind::Game) if is_sync => return false,
+            Some(AssetKind::Sync) if !is_sync => return false,
+            _ => {}
+        }
+        if self
+            .migrated
+            .is_some_and(|m| !is_sync || m != stats.is_migrated)
+        {
+            return false;
+        }
+        within(self.mkt_cap_range(), stats.mkt_cap_usd_cents())
+            && within(self.volume_range(), stats.volume_24h_mil)
+            && within(self.change_range(), stats.change_key())
+            && within(self.created_range(now), stats.create_timestamp)
     }
+}
 
-    /// Update the hot list with the provided data (requires writable reference)
-    pub fn update_hot_list(&mut self, hot_list: Vec<(usize, bool)>) {
-        self.hot_asset_indices = hot_list;
+/// The keys in `index` whose sort value is within `min..=max`, ascending
+fn keys_in<K: Ord + Copy>(
+    index: &BTreeSet<(K, AssetKey)>,
+    (min, max): (K, K),
+) -> impl DoubleEndedIterator<Item = AssetKey> + '_ {
+    (min <= max)
+        .then(|| index.range((min, (0, false))..=(max, (usize::MAX, true))))
+        .into_iter()
+        .flatten()
+        .map(|&(_, key)| key)
+}
+
+/// Insert `key` into `index` under `new`, removing it from under `old`
+fn reindex<K: Ord>(index: &mut BTreeSet<(K, AssetKey)>, old: Option<K>, new: K, key: AssetKey) {
+    if let Some(old) = old {
+        index.remove(&(old, key));
     }
+    index.insert((new, key));
+}
 
-    pub fn get_top_assets(
-        &self,
-        games: &GamesComponent,
-        syncs: &SyncComponent,
-        count: usize,
-        skip: usize,
-    ) -> Vec<Asset> {
-        self.top_assets
-            .iter()
-            .rev()
-            .skip(skip)
-            .take(count)
-            .filter_map(|entry| {
-                if entry.is_sync {
-                    syncs.syncs.get(entry.index).map(|s| s.to_asset())
-                } else {
-                    games.game_list.get(entry.index).map(|g| g.to_asset())
-                }
-            })
-            .collect()
+pub struct AssetsComponent {
+    pub top_assets: BTreeSet<TopAssetEntry>,
+    hot: HotRanking<AssetKey>,
+    address_to_key: HashMap<Public, AssetKey>,
+    search_index: SearchIndex<AssetKey>,
+    stats: HashMap<AssetKey, AssetStats>,
+    // Sort indexes, each ascending
+    by_created: BTreeSet<(u64, AssetKey)>,
+    by_volume: BTreeSet<(u64, AssetKey)>,
+    by_change: BTreeSet<(u64, AssetKey)>,
+    by_last_trade: BTreeSet<(u64, AssetKey)>,
+    by_holders: BTreeSet<(u64, AssetKey)>,
+    by_progress: BTreeSet<(u32, AssetKey)>,
+    // Volume per asset for each of the last 24 hours, as (hour, volume)
+    hourly_volume: HashMap<AssetKey, VecDeque<(u64, u64)>>,
+    // Hour -> assets with an hourly volume bucket expiring then
+    volume_expiries: BTreeMap<u64, Vec<AssetKey>>,
+    // The latest hour we've seen a trade in
+    current_hour: u64,
+}
+
+impl AssetsComponent {
+    pub fn new(hot_config: HotConfig) -> Self {
+        Self {
+            top_assets: BTreeSet::new(),
+            hot: HotRanking::new(hot_config),
+            address_to_key: HashMap::new(),
+            search_index: SearchIndex::new(),
+            stats: HashMap::new(),
+            by_created: BTreeSet::new(),
+            by_volume: BTreeSet::new(),
+            by_change: BTreeSet::new(),
+            by_last_trade: BTreeSet::new(),
+            by_holders: BTreeSet::new(),
+            by_progress: BTreeSet::new(),
+            hourly_volume: HashMap::new(),
+            volume_expiries: BTreeMap::new(),
+            current_hour: 0,
+        }
     }
 
-    pub fn get_hot_assets(
-        &self,
-        games: &GamesComponent,
-        syncs: &SyncComponent,
-        count: usize,
-        skip: usize,
-    ) -> Vec<Asset> {
-        self.hot_asset_indices
-            .iter()
-            .skip(skip)
-            .take(count)
-            .filter_map(|(idx, is_sync)| {
-                if *is_sync {
-                    syncs.syncs.get(*idx).map(|s| s.to_asset())
-                } else {
-                    games.game_list.get(*idx).map(|g| g.to_asset())
-                }
-            })
-            .collect()
+    /// Called when a new game is created
+    pub fn on_game_created(&mut self, index: usize, game: &Game) {
+        self.search_index
+            .insert((index, false), &game.name, &game.symbol);
+        self.set_stats((index, false), AssetStats::from_game(game));
+        self.address_to_key.insert(game.address, (index, false));
+        self.hot
+            .on_created((index, false), game.create_timestamp, game.mkt_cap_usd);
     }
 
-    pub fn get_recent_assets(
-        &self,
-        games: &GamesComponent,
-        syncs: &SyncComponent,
-        count: usize,
-        skip: usize,
-    ) -> Vec<Asset> {
-        // Both lists are chronologically ordered (newest at the end)
-        // We'll iterate backwards and merge them
-        let mut result = Vec::with_capacity(count);
-        let mut skipped = 0;
-
-        // Start from the end of both lists
-        let mut game_idx = games.game_list.len();
-        let mut sync_idx = syncs.syncs.len();
-
-        while result.len() < count && (game_idx > 0 || sync_idx > 0) {
-            // Peek at the next game and sync
-            let game_timestamp = if game_idx > 0 {
-                games.game_list[game_idx - 1].create_timestamp
-            } else {
-                0
-            };
+    /// Called when a game's stats change (e.g., after a swap)
+    pub fn on_game_updated(&mut self, index: usize, game: &Game) {
+        self.update_stats((index, false), AssetStats::from_game(game));
+    }
 
-            let sync_timestamp = if sync_idx > 0 {
-                syncs.syncs[sync_idx - 1].create_timestamp
-            } else {
-                0
-            };
+    /// Called when a new sync is created
+    pub fn on_sync_created(&mut self, index: usize, sync: &Sync) {
+        self.search_index
+            .insert((index, true), &sync.name, &sync.symbol);
+        self.set_stats((index, true), AssetStats::from_sync(sync));
+        self.address_to_key.insert(sync.address, (index, true));
+        self.hot
+            .on_created((index, true), sync.create_timestamp, sync.mkt_cap_usd);
+    }
 
-            // Take the more recent one
-            let asset = if game_idx > 0 && (sync_idx == 0 || game_timestamp >= sync_timestamp) {
-                game_idx -= 1;
-                games.game_list[game_idx].to_asset()
-            } else if sync_idx > 0 {
-                sync_idx -= 1;
-                syncs.syncs[sync_idx].to_asset()
-            } else {
-                break;
-            };
+    /// Called when a sync's stats change (e.g., after a trade or migration)
+    pub fn on_sync_updated(&mut self, index: usize, sync: &Sync) {
+        self.update_stats((index, true), AssetStats::from_sync(sync));
+    }
 
-            // Apply skip
-            if skipped < skip {
-                skipped += 1;
-                continue;
+    /// Called when an asset is traded, after its stats are updated
+    pub fn on_trade(&mut self, index: usize, is_sync: bool, timestamp: u64, trade: &Trade) {
+        let key = (index, is_sync);
+        self.advance_clock(timestamp);
+        self.hot.on_trade(
+            key,
+            timestamp,
+            trade.user,
+            trade.volume_usd,
+            trade.mkt_cap_usd,
+        );
+
+        let Some(mut stats) = self.stats.get(&key).copied() else {
+            return;
+        };
+        let hour = timestamp / SECONDS_PER_HOUR;
+        let volume_mil = usd_to_mil(trade.volume_usd);
+        let buckets = self.hourly_volume.entry(key).or_default();
+        match buckets.back_mut() {
+            Some((last_hour, volume)) if *last_hour >= hour => *volume += volume_mil,
+            _ => {
+                buckets.push_back((hour, volume_mil));
+                self.volume_expiries
+                    .entry(hour + VOLUME_WINDOW_HOURS)
+                    .or_default()
+                    .push(key);
             }
-
-            result.push(asset);
         }
 
-        result
+        stats.volume_24h_mil += volume_mil;
+        stats.last_trade = stats.last_trade.max(timestamp);
+        self.set_stats(key, stats);
     }
 
-    pub fn search_recent_assets_by_name(
-        &self,
-        games: &GamesComponent,
-        syncs: &SyncComponent,
-        name: &str,
-        count: usize,
-        skip: usize,
-    ) -> Vec<Asset> {
-        let query = normalize_string(name);
-        if query.is_empty() {
-            return Vec::new();
-        }
-
-        let mut result = Vec::with_capacity(count);
-        let mut skipped = 0;
+    /// Whether hourly volume buckets may have left the 24h window since the clock
+    /// was last advanced, which only happens once an hour
+    pub fn needs_clock_advance(&self, timestamp: u64) -> bool {
+        timestamp / SECONDS_PER_HOUR > self.current_hour
+    }
 
-        // Start from the end of both lists (most recent)
-        let mut game_idx = games.game_list.len();
-        let mut sync_idx = syncs.syncs.len();
+    /// Drop hourly volume buckets that have left the 24h window at `timestamp`.
+    /// Called on each trade, and with the current time before assets are listed,
+    /// so that assets that stopped trading don't keep their volume.
+    pub fn advance_clock(&mut self, timestamp: u64) {
+        let hour = timestamp / SECONDS_PER_HOUR;
+        if hour <= self.current_hour {
+            return;
+        }
+        self.current_hour = hour;
 
-        while result.len() < count && (game_idx > 0 || sync_idx > 0) {
-            // Peek at the next game and sync timestamps
-            let game_timestamp = if game_idx > 0 {
-                games.game_list[game_idx - 1].create_timestamp
-            } else {
-                0
+        let pending = self.volume_expiries.split_off(&(hour + 1));
+        let expired = std::mem::replace(&mut self.volume_expiries, pending);
+        for key in expired.into_values().flatten() {
+            let Some(buckets) = self.hourly_volume.get_mut(&key) else {
+                continue;
             };
+            while buckets
+                .front()
+                .is_some_and(|&(h, _)| h + VOLUME_WINDOW_HOURS <= hour)
+            {
+                buckets.pop_front();
+            }
+            let volume_24h_mil = buckets.iter().map(|&(_, v)| v).sum();
+            if buckets.is_empty() {
+                self.hourly_volume.remove(&key);
+            }
+            if let Some(mut stats) = self.stats.get(&key).copied() {
+                stats.volume_24h_mil = volume_24h_mil;
+                self.set_stats(key, stats);
+            }
+        }
+    }
 
-            let sync_timestamp = if sync_idx > 0 {
-                syncs.syncs[sync_idx - 1].create_timestamp
-            } else {
-                0
-            };
+    /// Replace the stats derived from a game or sync, keeping those we track ourselves
+    fn update_stats(&mut self, key: AssetKey, mut stats: AssetStats) {
+        if let Some(old) = self.stats.get(&key) {
+            stats.volume_24h_mil = old.volume_24h_mil;
+            stats.last_trade = old.last_trade;
+        }
+        self.set_stats(key, stats);
+    }
 
-            // Process the more recent one
-            if game_idx > 0 && (sync_idx == 0 || game_timestamp >= sync_timestamp) {
-                game_idx -= 1;
-                let game = &games.game_list[game_idx];
-
-                if game.normalized_name.contains(&query) {
-                    if skipped < skip {
-                        skipped += 1;
-                    } else {
-                        result.push(game.to_asset());
-                    }
-                }
-            } else if sync_idx > 0 {
-                sync_idx -= 1;
-                let sync = &syncs.syncs[sync_idx];
-
-                // For syncs, we normalize the name on the fly
-                let normalized_sync_name = normalize_string(&sync.name);
-                if normalized_sync_name.contains(&query) {
-                    if skipped < skip {
-                        skipped += 1;
-                    } else {
-                        result.push(sync.to_asset());
-                    }
-                }
+    fn set_stats(&mut self, key: AssetKey, new: AssetStats) {
+        let old = self.stats.insert(key, new);
+        if old == Some(new) {
+            return;
+        }
+        let (index, is_sync) = key;
+
+        if old.map(|o| o.mkt_cap_usd_cents()) != Some(new.mkt_cap_usd_cents()) {
+            if let Some(old) = old {
+                self.top_assets.remove(&TopAssetEntry {
+                    mkt_cap_usd_cents: old.mkt_cap_usd_cents(),
+                    create_timestamp: old.create_timestamp,
+                    index,
+                    is_sync,
+                });
+            }
+            self.top_assets.insert(TopAssetEntry {
+                mkt_cap_usd_cents: new.mkt_cap_usd_cents(),
+                create_timestamp: new.create_timestamp,
+                index,
+                is_sync,
+            });
+        }
+        if old.is_none() {
+            self.by_created.insert((new.create_timestamp, key));
+        }
+        if old.map(|o| o.volume_24h_mil) != Some(new.volume_24h_mil) {
+            reindex(
+                &mut self.by_volume,
+                old.map(|o| o.volume_24h_mil),
+                new.volume_24h_mil,
+                key,
+            );
+        }
+        if old.map(|o| o.change_key()) != Some(new.change_key()) {
+            reindex(
+                &mut self.by_change,
+                old.map(|o| o.change_key()),
+                new.change_key(),
+                key,
+            );
+        }
+        if old.map(|o| o.last_trade) != Some(new.last_trade) {
+            reindex(
+                &mut self.by_last_trade,
+                old.map(|o| o.last_trade),
+                new.last_trade,
+                key,
+            );
+        }
+        if old.map(|o| o.holders) != Some(new.holders) {
+            reindex(
+                &mut self.by_holders,
+                old.map(|o| o.holders),
+                new.holders,
+                key,
+            );
+        }
+        let old_progress = old.and_then(|o| o.progress_ppm);
+        if old_progress != new.progress_ppm {
+            if let Some(old_progress) = old_progress {
+                self.by_progress.remove(&(old_progress, key));
+            }
+            if let Some(new_progress) = new.progress_ppm {
+                self.by_progress.insert((new_progress, key));
             }
         }
+    }
+
+    /// Called when a comment is posted on an asset
+    pub fn on_comment(&mut self, address: &Public, timestamp: u64) {
+        if let Some(&key) = self.address_to_key.get(address) {
+            self.hot.on_comment(key, timestamp);
+        }
+    }
 
-        result
+    /// Explain the hot score of the asset at `address`
+    pub fn hot_breakdown(&self, address: &Public) -> Option<HotBreakdown> {
+        let key = self.address_to_key.get(address)?;
+        self.hot.breakdown(key, unix_timestamp())
     }
 
-    pub fn search_top_assets_by_name(
+    fn to_asset(games: &GamesComponent, syncs: &SyncComponent, key: AssetKey) -> Asset {
+        match key {
+            (index, true) => syncs.syncs[index].to_asset(),
+            (index, false) => games.game_list[index].to_asset(),
+        }
+    }
+
+    /// List assets in `order`, skipping those not matching `filter`
+    pub fn list_assets(
         &self,
         games: &GamesComponent,
         syncs: &SyncComponent,
-        name: &str,
+        order: AssetOrder,
+        filter: &AssetFilter,
         count: usize,
         skip: usize,
     ) -> Vec<Asset> {
-        let query = normalize_string(name);
-        if query.is_empty() {
-            return Vec::new();
+        fn keys<K>(
+            index: &BTreeSet<(K, AssetKey)>,
+        ) -> impl DoubleEndedIterator<Item = AssetKey> + '_ {
+            index.iter().map(|&(_, key)| key)
         }
+        let now = unix_timestamp();
+        // Sorting by a value the filter bounds only walks the matching part of its index
+        let keys: Box<dyn Iterator<Item = AssetKey> + '_> = match (&filter.query, order) {
+            (Some(query), _) => Box::new(self.search(query, order).into_iter()),
+            (None, AssetOrder::Recent) => {
+                Box::new(keys_in(&self.by_created, filter.created_range(now)).rev())
+            }
+            (None, AssetOrder::Top) => {
+                let (min, max) = filter.mkt_cap_range();
+                let entry = |mkt_cap_usd_cents, is_max: bool| TopAssetEntry {
+                    mkt_cap_usd_cents,
+                    create_timestamp: if is_max { u64::MAX } else { 0 },
+                    index: if is_max { usize::MAX } else { 0 },
+                    is_sync: is_max,
+                };
+                Box::new(
+                    (min <= max)
+                        .then(|| self.top_assets.range(entry(min, false)..=entry(max, true)))
+                        .into_iter()
+                        .flatten()
+                        .rev()
+                        .map(|entry| (entry.index, entry.is_sync)),
+                )
+            }
+            (None, AssetOrder::Hot) => Box::new(self.hot.iter()),
+            (None, AssetOrder::Volume) => {
+                Box::new(keys_in(&self.by_volume, filter.volume_range()).rev())
+            }
+            (None, AssetOrder::Gainers) => {
+                Box::new(keys_in(&self.by_change, filter.change_range()).rev())
+            }
+            (None, AssetOrder::Losers) => Box::new(keys_in(&self.by_change, filter.change_range())),
+            (None, AssetOrder::LastTrade) => Box::new(keys(&self.by_last_trade).rev()),
+            (None, AssetOrder::Holders) => Box::new(keys(&self.by_holders).rev()),
+            (None, AssetOrder::Progress) => Box::new(keys(&self.by_progress).rev()),
+        };
 
-        self.top_assets
-            .iter()
-            .rev()
-            .filter_map(|entry| {
-                if entry.is_sync {
-                    syncs.syncs.get(entry.index).and_then(|sync| {
-                        let normalized_sync_name = normalize_string(&sync.name);
-                        if normalized_sync_name.contains(&query) {
-                            Some(sync.to_asset())
-                        } else {
-                            None
-                        }
-                    })
-                } else {
-                    games.game_list.get(entry.index).and_then(|game| {
-                        if game.normalized_name.contains(&query) {
-                            Some(game.to_asset())
-                        } else {
-                            None
-                        }
-                    })
-                }
-            })
+        keys.filter(|key| self.matches(filter, *key, now))
             .skip(skip)
             .take(count)
+            .map(|key| Self::to_asset(games, syncs, key))
             .collect()
     }
 
+    fn matches(&self, filter: &AssetFilter, key: AssetKey, now: u64) -> bool {
+        self.stats
+            .get(&key)
+            .is_some_and(|stats| filter.matches(key.1, stats, now))
+    }
+
+    /// Search assets by name or symbol, most relevant first.
+    /// Equally relevant assets are ordered by `order`.
+    fn search(&self, query: &str, order: AssetOrder) -> Vec<AssetKey> {
+        // Lower is better
+        let tiebreak = |key: &AssetKey, stats: &AssetStats| -> (u64, u64) {
+            let mkt_cap = u64::MAX - stats.mkt_cap_usd_cents();
+            match order {
+                AssetOrder::Recent => (u64::MAX - stats.create_timestamp, 0),
+                AssetOrder::Top => (mkt_cap, 0),
+                AssetOrder::Hot => (self.hot.sort_key(key), mkt_cap),
+                AssetOrder::Volume => (u64::MAX - stats.volume_24h_mil, mkt_cap),
+                AssetOrder::Gainers => (u64::MAX - stats.change_key(), mkt_cap),
+                AssetOrder::Losers => (stats.change_key(), mkt_cap),
+                AssetOrder::LastTrade => (u64::MAX - stats.last_trade, mkt_cap),
+                AssetOrder::Holders => (u64::MAX - stats.holders, mkt_cap),
+                AssetOrder::Progress => (
+                    stats.progress_ppm.map_or(u64::MAX, |p| u64::MAX - p as u64),
+                    mkt_cap,
+                ),
+            }
+        };
+
+        let mut matches: Vec<_> = self
+            .search_index
+            .search(query)
+            .into_iter()
+            .filter_map(|m| {
+                let stats = self.stats.get(&m.item)?;
+                if order == AssetOrder::Progress && stats.progress_ppm.is_none() {
+                    return None;
+                }
+                Some((m.tier, m.distance, tiebreak(&m.item, stats), m.item))
+            })
+            .collect();
+        matches.sort_unstable();
+
+        matches.into_iter().map(|(_, _, _, key)| key).collect()
+    }
+
     pub fn get_asset_count(&self, games: &GamesComponent, syncs: &SyncComponent) -> usize {
         games.game_list.len() + syncs.syncs.len()
     }
@@ -8,6 +8,8 @@ use crate::types::public::Public;
 use crate::state::constants::{HIDDEN_GAMES, HIDDEN_SYNCS};
 use crate::state::types::Comment;
 
+use super::assets::AssetsComponent;
+
 // 16 updates before receiver is deemed lagged
 const CHANNEL_BUFFER_SIZE: usize = 16;
 
@@ -68,17 +70,17 @@ impl CommentsComponent {
             .subscribe()
     }
 
-    pub fn on_event(&mut self, event: &Event) -> bool {
+    pub fn on_event(&mut self, event: &Event, assets: &mut AssetsComponent) -> bool {
         match &event.data {
             EventData::Comment(data) => {
-                self.process_comment_event(data);
+                self.process_comment_event(data, assets);
                 true
             }
             _ => false,
         }
     }
 
-    fn process_comment_event(&mut self, comment_data: &CommentEvent) {
+    fn process_comment_event(&mut self, comment_data: &CommentEvent, assets: &mut AssetsComponent) {
         // Check if this asset is hidden (could be either a game or sync)
         if HIDDEN_GAMES.contains(&comment_data.game) || HIDDEN_SYNCS.contains(&comment_data.game) {
             return;
@@ -100,6 +102,8 @@ impl CommentsComponent {
         asset_comments.broadcast_comment(comment.clone());
 
         asset_comments.comments.push(comment);
+
+        assets.on_comment(&comment_data.game, comment_data.timestamp);
     }
 
     /// Get comment information for an asset
@@ -0,0 +1,285 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::state::types::{FeePoint, FeeTotals, FeesResponse};
+use crate::types::event::{Event, EventData, GameSwapEvent};
+use crate::types::public::Public;
+use crate::util::{from_game_amount, from_ivy_amount, mil_to_usd, usd_to_mil};
+
+use super::games::GamesComponent;
+use super::volume::VolumeWindow;
+use super::world::WorldComponent;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+// Hours of fees kept for the longest expiring window
+const MAX_WINDOW_HOURS: u64 = 30 * 24;
+
+/// The fee taken from `amount` at `bps` basis points, rounded down like the program does
+fn fee_of(amount: u64, bps: u64) -> u64 {
+    (amount as u128 * bps as u128 / 10_000) as u64
+}
+
+/// The fee taken from an amount out of the curve, given what the user received of it.
+/// Amounts a raw unit apart can leave the same remainder, in which case the smallest is used.
+fn fee_from_received(received: u64, bps: u64) -> u64 {
+    let remainder = |amount: u64| amount - fee_of(amount, bps);
+    let estimate = (received as u128 * 10_000).div_ceil(10_000 - bps as u128);
+    let mut amount = estimate.min(u64::MAX as u128) as u64;
+    while amount > received && remainder(amount - 1) >= received {
+        amount -= 1;
+    }
+    amount - received
+}
+
+/// The fees taken by a game swap
+#[derive(Clone, Copy, Default)]
+pub struct SwapFees {
+    /// Raw IVY burned
+    pub ivy_fee: u64,
+    /// Raw game tokens sent to the game's treasury
+    pub game_fee: u64,
+    pub ivy_fee_usd: f32,
+    pub game_fee_usd: f32,
+}
+
+impl SwapFees {
+    /// Work out the fees of `swap`. The program takes one fee from what the user pays
+    /// and another from what comes out of the curve, burning the IVY fee and sending
+    /// the game token fee to the game's treasury.
+    fn of(swap: &GameSwapEvent, world: &WorldComponent, games: &GamesComponent) -> Self {
+        let data = world.data();
+        let ivy_bps = data.ivy_fee_bps as u64;
+        let game_bps = data.game_fee_bps as u64;
+        let (ivy_fee, game_fee) = if swap.is_buy {
+            // Pays IVY, receives game tokens
+            (
+                fee_of(swap.ivy_amount, ivy_bps),
+                fee_from_received(swap.game_amount, game_bps),
+            )
+        } else {
+            // Pays game tokens, receives IVY
+            (
+                fee_from_received(swap.ivy_amount, ivy_bps),
+                fee_of(swap.game_amount, game_bps),
+            )
+        };
+        Self {
+            ivy_fee,
+            game_fee,
+            ivy_fee_usd: from_ivy_amount(ivy_fee) * world.price(),
+            game_fee_usd: from_game_amount(game_fee) * games.last_price_for(&swap.game),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct FeeAmounts {
+    ivy_raw: u64,
+    game_raw: u64,
+    ivy_mil: u64,
+    game_mil: u64,
+}
+
+impl FeeAmounts {
+    fn add(&mut self, other: &FeeAmounts) {
+        self.ivy_raw = self.ivy_raw.saturating_add(other.ivy_raw);
+        self.game_raw = self.game_raw.saturating_add(other.game_raw);
+        self.ivy_mil = self.ivy_mil.saturating_add(other.ivy_mil);
+        self.game_mil = self.game_mil.saturating_add(other.game_mil);
+    }
+
+    /// Convert to totals, with game tokens only if they're all of the same game
+    fn totals(&self, single_game: bool) -> FeeTotals {
+        FeeTotals {
+            ivy_fees: from_ivy_amount(self.ivy_raw),
+            ivy_fees_usd: mil_to_usd(self.ivy_mil),
+            game_fees: single_game.then(|| from_game_amount(self.game_raw)),
+            game_fees_usd: mil_to_usd(self.game_mil),
+        }
+    }
+}
+
+/// Fees all-time, per hour for the windows, and per day
+#[derive(Default)]
+struct Ledger {
+    all: FeeAmounts,
+    hourly: BTreeMap<u64, FeeAmounts>,
+    // Start of the day -> fees in that day
+    daily: BTreeMap<u64, FeeAmounts>,
+}
+
+impl Ledger {
+    fn add(&mut self, timestamp: u64, fees: &FeeAmounts) {
+        self.all.add(fees);
+        self.hourly.entry(timestamp / HOUR).or_default().add(fees);
+        self.daily
+            .entry(timestamp - timestamp % DAY)
+            .or_default()
+            .add(fees);
+    }
+
+    /// Drop the hours before `oldest`
+    fn expire(&mut self, oldest: u64) {
+        while let Some(entry) = self.hourly.first_entry() {
+            if *entry.key() >= oldest {
+                break;
+            }
+            entry.remove();
+        }
+    }
+
+    /// The fees of `window`, ending at `hour`
+    fn window(&self, hour: u64, window: VolumeWindow) -> FeeAmounts {
+        let Some(length) = window.hours() else {
+            return self.all;
+        };
+        let mut sum = FeeAmounts::default();
+        for fees in self.hourly.range((hour + 1).saturating_sub(length)..) {
+            sum.add(fees.1);
+        }
+        sum
+    }
+
+    fn response(&self, hour: u64, single_game: bool) -> FeesResponse {
+        let totals = |window| self.window(hour, window).totals(single_game);
+        FeesResponse {
+            day: totals(VolumeWindow::Day),
+            week: totals(VolumeWindow::Week),
+            month: totals(VolumeWindow::Month),
+            all: totals(VolumeWindow::All),
+        }
+    }
+}
+
+/// Attributes the IVY and game token fees of each game swap to its game
+/// and to the protocol, valued in USD at the time of the swap
+pub struct FeesComponent {
+    games: HashMap<Public, Ledger>,
+    protocol: Ledger,
+    // Curve balances of each new game before its initial purchase,
+    // which is fee-free, until the game's first swap
+    initial_balances: HashMap<Public, (u64, u64)>,
+    // The latest hour seen, which windows end at
+    hour: u64,
+    // Fees of the last event applied
+    last: SwapFees,
+}
+
+impl FeesComponent {
+    pub fn new() -> Self {
+        Self {
+            games: HashMap::new(),
+            protocol: Ledger::default(),
+            initial_balances: HashMap::new(),
+            hour: 0,
+            last: SwapFees::default(),
+        }
+    }
+
+    /// Record the fees of `event`, before `world` applies it
+    pub fn on_event(
+        &mut self,
+        event: &Event,
+        world: &WorldComponent,
+        games: &GamesComponent,
+    ) -> bool {
+        self.last = SwapFees::default();
+        let swap = match &event.data {
+            EventData::GameCreate(create) => {
+                self.initial_balances
+                    .insert(create.game, (create.ivy_balance, create.game_balance));
+                return false;
+            }
+            EventData::GameSwap(swap) => swap,
+            _ => return false,
+        };
+
+        // The initial purchase moves the whole amounts in and out of the curve
+        if let Some((ivy_balance, game_balance)) = self.initial_balances.remove(&swap.game) {
+            if swap.is_buy
+                && swap.ivy_balance == ivy_balance.saturating_add(swap.ivy_amount)
+                && swap.game_balance == game_balance.saturating_sub(swap.game_amount)
+            {
+                return false;
+            }
+        }
+
+        let hour = event.timestamp / HOUR;
+        if hour > self.hour {
+            self.hour = hour;
+            let oldest = (hour + 1).saturating_sub(MAX_WINDOW_HOURS);
+            self.protocol.expire(oldest);
+            for ledger in self.games.values_mut() {
+                ledger.expire(oldest);
+            }
+        }
+
+        let fees = SwapFees::of(swap, world, games);
+        let amounts = FeeAmounts {
+            ivy_raw: fees.ivy_fee,
+            game_raw: fees.game_fee,
+            ivy_mil: usd_to_mil(fees.ivy_fee_usd),
+            game_mil: usd_to_mil(fees.game_fee_usd),
+        };
+        // Raw amounts of different game tokens can't be added together
+        self.protocol.add(
+            event.timestamp,
+            &FeeAmounts {
+                game_raw: 0,
+                ..amounts
+            },
+        );
+        self.games
+            .entry(swap.game)
+            .or_default()
+            .add(event.timestamp, &amounts);
+        self.last = fees;
+
+        // Swaps are persisted by the games component
+        false
+    }
+
+    /// The fees of the last event applied, zero if it wasn't a game swap
+    pub fn last_fees(&self) -> SwapFees {
+        self.last
+    }
+
+    /// Get the fees taken by a game's swaps over each window
+    pub fn get_game_fees(&self, game: &Public) -> FeesResponse {
+        match self.games.get(game) {
+            Some(ledger) => ledger.response(self.hour, true),
+            None => Ledger::default().response(self.hour, true),
+        }
+    }
+
+    /// Get the fees taken by all game swaps over each window
+    pub fn get_protocol_fees(&self) -> FeesResponse {
+        self.protocol.response(self.hour, false)
+    }
+
+    /// Get the fees taken by a game's swaps per day, for the days
+    /// that start within `[from, to)` and had any swaps
+    pub fn query_game_daily(
+        &self,
+        game: &Public,
+        from: Option<u64>,
+        to: Option<u64>,
+    ) -> Vec<FeePoint> {
+        let Some(ledger) = self.games.get(game) else {
+            return Vec::new();
+        };
+        let from = from.unwrap_or(0);
+        let to = to.unwrap_or(u64::MAX);
+        if from >= to {
+            return Vec::new();
+        }
+        ledger
+            .daily
+            .range(from..to)
+            .map(|(&timestamp, fees)| FeePoint {
+                timestamp,
+                fees: fees.totals(true),
+            })
+            .collect()
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use crate::types::asset::Asset;
 use crate::types::chart::Candle;
-use crate::types::charts::{ChartKind, Charts};
+use crate::types::charts::{CandleFolder, ChartKind, ChartQuote, Charts};
 use crate::types::event::{
     Event, EventData, GameCreateEvent, GameEditEvent, GameSwapEvent, HydrateEvent,
 };
@@ -17,7 +17,6 @@ use tokio::sync::{broadcast, watch};
 use super::assets::AssetsComponent;
 use super::world::WorldComponent;
 use crate::state::constants::HIDDEN_GAMES;
-use crate::state::helpers::normalize_string;
 
 // 512 updates before receiver is deemed lagged :)
 const CHANNEL_BUFFER_SIZE: usize = 512;
@@ -32,20 +31,41 @@ pub struct GameBalanceUpdate {
 
 struct GameMeta {
     index: usize,
+    // Charts denominated in USD
     charts: Charts,
+    // Charts denominated in IVY
+    ivy_charts: Charts,
     // Broadcast channel for real-time balance updates
     balance_update_tx: Option<broadcast::Sender<GameBalanceUpdate>>,
 }
 
 impl GameMeta {
-    fn new(index: usize, charts: Charts) -> Self {
+    fn new(index: usize, charts: Charts, ivy_charts: Charts) -> Self {
         Self {
             index,
             charts,
+            ivy_charts,
             balance_update_tx: None,
         }
     }
 
+    /// Get the charts denominated in `quote`, if this game has them
+    fn charts_for(&self, quote: ChartQuote) -> Option<&Charts> {
+        match quote {
+            ChartQuote::Usd => Some(&self.charts),
+            ChartQuote::Ivy => Some(&self.ivy_charts),
+            ChartQuote::Sol => None,
+        }
+    }
+
+    fn charts_for_mut(&mut self, quote: ChartQuote) -> Option<&mut Charts> {
+        match quote {
+            ChartQuote::Usd => Some(&mut self.charts),
+            ChartQuote::Ivy => Some(&mut self.ivy_charts),
+            ChartQuote::Sol => None,
+        }
+    }
+
     /// Subscribe to balance updates for this game
     fn subscribe_to_balances(&mut self) -> broadcast::Receiver<GameBalanceUpdate> {
         match &self.balance_update_tx {
@@ -106,15 +126,19 @@ impl GamesComponent {
     }
 
     /// Subscribe to chart updates for a specific game.
-    /// Returns a receiver that will receive candle updates for the specified chart kind.
+    /// Returns a receiver that will receive candle updates for the specified chart kind,
+    /// which must be passed through the returned `CandleFolder`.
+    /// Returns `None` if the game doesn't exist or has no charts in `quote`.
     pub fn subscribe_to_game_chart(
         &mut self,
         game: &Public,
+        quote: ChartQuote,
         kind: ChartKind,
-    ) -> Option<broadcast::Receiver<Candle>> {
+    ) -> Option<(broadcast::Receiver<Candle>, CandleFolder)> {
         self.address_to_game_meta
             .get_mut(game)
-            .map(|meta| meta.charts.subscribe_to_kind(kind))
+            .and_then(|meta| meta.charts_for_mut(quote))
+            .map(|charts| charts.subscribe_to_kind(kind))
     }
 
     pub fn on_event(
@@ -156,10 +180,8 @@ impl GamesComponent {
         }
 
         let game_balance = from_game_amount(create_data.game_balance);
-        let game_price_usd =
-            (from_ivy_amount(create_data.ivy_balance) / game_balance) * world.price();
-
-        let normalized_name = normalize_string(&create_data.name);
+        let game_price_ivy = from_ivy_amount(create_data.ivy_balance) / game_balance;
+        let game_price_usd = game_price_ivy * world.price();
 
         let game = Game {
             name: create_data.name.clone(),
@@ -172,27 +194,35 @@ impl GamesComponent {
             game_url: String::new(),
             icon_url: String::new(),
             description: String::new(),
+            website: String::new(),
+            twitter: String::new(),
+            telegram: String::new(),
             metadata_url: String::new(),
             create_timestamp: timestamp,
             ivy_balance: create_data.ivy_balance,
             game_balance: create_data.game_balance,
             starting_ivy_balance: create_data.ivy_balance,
             starting_game_balance: create_data.game_balance,
-            normalized_name,
             last_price_usd: game_price_usd,
             mkt_cap_usd: game_balance * game_price_usd,
             change_pct_24h: 0.0,
+            holders: 0,
         };
 
         let index = self.game_list.len();
         let mkt_cap_usd = game.mkt_cap_usd;
-        let create_timestamp = game.create_timestamp;
         self.game_list.push(game);
 
         let mut charts = Charts::new(self.max_candles);
-        charts.append(timestamp, game_price_usd, 0.0).unwrap();
+        charts
+            .append(timestamp, game_price_usd, 0.0, true, None)
+            .unwrap();
+        let mut ivy_charts = Charts::new(self.max_candles);
+        ivy_charts
+            .append(timestamp, game_price_ivy, 0.0, true, None)
+            .unwrap();
 
-        let mut meta = GameMeta::new(index, charts);
+        let mut meta = GameMeta::new(index, charts, ivy_charts);
 
         // Broadcast initial balance update
         meta.broadcast_balance_update(GameBalanceUpdate {
@@ -206,7 +236,7 @@ impl GamesComponent {
         self.update_game_tvl(0, create_data.ivy_balance, create_data.ivy_balance);
 
         // Update assets component
-        assets.on_game_created(index, mkt_cap_usd, create_timestamp);
+        assets.on_game_created(index, &self.game_list[index]);
     }
 
     fn process_game_edit(&mut self, edit_data: &GameEditEvent) {
@@ -249,19 +279,13 @@ impl GamesComponent {
         let is_new_game = game.icon_url.is_empty() && game.description.is_empty();
         game.icon_url = hydrate.icon_url.clone();
         game.description = hydrate.description.clone();
+        game.website = hydrate.website.clone();
+        game.twitter = hydrate.twitter.clone();
+        game.telegram = hydrate.telegram.clone();
 
         // Notify assets stream if this is a new game
         if is_new_game {
-            _ = self.assets_tx.send(Asset {
-                name: game.name.clone(),
-                symbol: game.symbol.clone(),
-                address: game.address,
-                icon_url: game.icon_url.clone(),
-                description: game.description.clone(),
-                create_timestamp: game.create_timestamp,
-                mkt_cap_usd: game.mkt_cap_usd,
-                price: game.last_price_usd,
-            });
+            _ = self.assets_tx.send(game.to_asset());
         }
     }
 
@@ -310,18 +334,32 @@ impl GamesComponent {
             return;
         }
 
-        let old_mkt_cap_usd = game.mkt_cap_usd;
         game.last_price_usd = game_price_usd;
 
-        if let Err(e) = game_meta
-            .charts
-            .append(timestamp, game_price_usd, usdc_value)
-        {
+        if let Err(e) = game_meta.charts.append(
+            timestamp,
+            game_price_usd,
+            usdc_value,
+            swap_data.is_buy,
+            Some(swap_data.user),
+        ) {
             eprintln!(
                 "warning: Could not append to game chart (game: {}, sig: {}, time: {}): {}",
                 swap_data.game, signature, timestamp, e
             );
         }
+        if let Err(e) = game_meta.ivy_charts.append(
+            timestamp,
+            game_price_ivy,
+            ivy_amount,
+            swap_data.is_buy,
+            Some(swap_data.user),
+        ) {
+            eprintln!(
+                "warning: Could not append to game IVY chart (game: {}, sig: {}, time: {}): {}",
+                swap_data.game, signature, timestamp, e
+            );
+        }
 
         game.mkt_cap_usd = from_game_amount(game.starting_game_balance) * game_price_usd;
         game.change_pct_24h = game_meta.charts.get_change_pct_24h().unwrap_or(0.0);
@@ -334,23 +372,25 @@ impl GamesComponent {
             change_pct_24h: game.change_pct_24h,
         });
 
-        // Send to trades listener
-        _ = self.trades_tx.send(Some(Trade {
+        let trade = Trade {
             user: swap_data.user,
             asset: swap_data.game,
             symbol: game.symbol.clone(),
             icon_url: game.icon_url.clone(),
             volume_usd: usdc_value,
+            price_usd: game.last_price_usd,
             mkt_cap_usd: game.mkt_cap_usd,
             is_buy: swap_data.is_buy,
-        }));
+        };
 
-        let mkt_cap_usd = game.mkt_cap_usd;
-        let create_timestamp = game.create_timestamp;
         self.update_game_tvl(old_ivy_balance, new_ivy_balance, starting_ivy_balance);
 
         // Update assets component
-        assets.on_game_updated(game_index, old_mkt_cap_usd, mkt_cap_usd, create_timestamp);
+        assets.on_game_updated(game_index, &self.game_list[game_index]);
+        assets.on_trade(game_index, false, timestamp, &trade);
+
+        // Send to trades listener
+        _ = self.trades_tx.send(Some(trade));
     }
 
     fn update_game_tvl(
@@ -375,6 +415,15 @@ impl GamesComponent {
             .map(|meta| self.game_list[meta.index].clone())
     }
 
+    /// Set the number of holders of a game
+    pub fn set_holders(&mut self, game: &Public, holders: u64, assets: &mut AssetsComponent) {
+        if let Some(meta) = self.address_to_game_meta.get(game) {
+            let game = &mut self.game_list[meta.index];
+            game.holders = holders;
+            assets.on_game_updated(meta.index, game);
+        }
+    }
+
     pub fn get_game_count(&self) -> usize {
         self.game_list.len()
     }
@@ -382,13 +431,17 @@ impl GamesComponent {
     pub fn query_game_chart(
         &self,
         game: Public,
+        quote: ChartQuote,
         kind: ChartKind,
         count: usize,
         after_inclusive: u64,
     ) -> (Vec<Candle>, f32, f32) {
         match self.address_to_game_meta.get(&game) {
             Some(m) => {
-                let candles = m.charts.query(kind, count, after_inclusive);
+                let candles = m
+                    .charts_for(quote)
+                    .map(|c| c.query(kind, count, after_inclusive))
+                    .unwrap_or_default();
                 let g = &self.game_list[m.index];
                 (candles, g.mkt_cap_usd, g.change_pct_24h)
             }
@@ -1,83 +1,340 @@
+use crate::applier::Cursor;
 use crate::types::event::{
     Event, EventData, GameEditEvent, HydrateEvent, InitializeEvent, SyncCreateEvent,
 };
 use crate::types::public::Public;
+use crate::util::unix_timestamp;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::mpsc::Sender;
 
-/// State component: collects metadata_url by asset pre-initialization,
-/// drains and sends to worker after InitializeEvent, and immediately
-/// forwards new metadata_url updates after initialization.
+// Jobs fetched at once
+pub const MAX_RUNNING: usize = 8;
+// Jobs fetched at once from the same host
+const MAX_RUNNING_PER_HOST: usize = 2;
+// Delay before the first retry, doubled after each failed attempt
+const BASE_RETRY_DELAY: u64 = 5;
+// Longest delay between attempts, in seconds
+const MAX_RETRY_DELAY: u64 = 60 * 60;
+// Attempts before a job is given up on
+const MAX_ATTEMPTS: u32 = 10;
+// Jobs given up on that are kept, the oldest are dropped past it
+const MAX_FAILED: usize = 1000;
+// Least time between writes of the queue, in seconds
+const PERSIST_INTERVAL: u64 = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HydrationStatus {
+    Pending,
+    Running,
+    Failed,
+}
+
+/// Fetching an asset's web metadata
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HydrationJob {
+    pub asset: Public,
+    pub metadata_url: String,
+    pub host: String,
+    pub status: HydrationStatus,
+    /// Number of attempts made so far
+    pub attempts: u32,
+    /// Unix timestamp of the next attempt
+    pub next_attempt: u64,
+    pub last_error: Option<String>,
+    pub created_at: u64,
+}
+
+/// The host of a URL, which jobs are limited per. IPFS and Arweave URIs
+/// are all fetched through the same gateway, so they count as one host.
+fn host_of(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    if scheme.eq_ignore_ascii_case("ipfs") || scheme.eq_ignore_ascii_case("ar") {
+        return scheme.to_ascii_lowercase();
+    }
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    host.to_ascii_lowercase()
+}
+
+/// State component: keeps the queue of assets whose metadata_url hasn't been
+/// hydrated yet, persisted with each job's attempts, and hands due jobs to the
+/// hydrator's workers once initialized. Replay reconciles the persisted queue
+/// with the event log, so jobs lost before being persisted are picked up again.
+/// That lets the hydrator write the queue at most every `PERSIST_INTERVAL`,
+/// rather than on every change.
 pub struct HydrateComponent {
     initialized: bool,
-    pending: HashMap<Public, String>, // asset -> metadata_url
-    tx: Sender<(Public, String)>,     // hydration requests to worker
+    jobs: HashMap<Public, HydrationJob>,
+    // Whether the jobs changed since the queue was last written
+    dirty: bool,
+    // When the queue was last written
+    persisted_at: u64,
+    // Jobs read from the store, kept during replay to carry their attempts over
+    saved: HashMap<Public, HydrationJob>,
+    store: Cursor<Vec<HydrationJob>>,
+    tx: Sender<()>, // wakes the hydrator when jobs are queued
 }
 
 impl HydrateComponent {
-    pub fn new(tx: Sender<(Public, String)>) -> Self {
+    pub fn new(mut store: Cursor<Vec<HydrationJob>>, tx: Sender<()>) -> Self {
+        let saved = match store.read() {
+            Ok(jobs) => jobs.unwrap_or_default(),
+            Err(e) => {
+                eprintln!("warning: Can't read hydration queue: {}", e);
+                Vec::new()
+            }
+        };
         Self {
             initialized: false,
-            pending: HashMap::new(),
+            jobs: HashMap::new(),
+            dirty: false,
+            persisted_at: 0,
+            saved: saved.into_iter().map(|job| (job.asset, job)).collect(),
+            store,
             tx,
         }
     }
 
     pub fn on_event(&mut self, event: &Event) -> bool {
         match &event.data {
-            // During replay/pre-initialization: track latest metadata_url
             EventData::GameEdit(GameEditEvent {
                 game, metadata_url, ..
-            }) => {
-                if metadata_url.is_empty() {
-                    return false;
-                }
-                if !self.initialized {
-                    self.pending.insert(*game, metadata_url.clone());
-                } else {
-                    let _ = self.tx.send((*game, metadata_url.clone()));
-                }
-                false
-            }
+            }) => self.enqueue(*game, metadata_url, event.timestamp),
             EventData::SyncCreate(SyncCreateEvent {
                 sync, metadata_url, ..
-            }) => {
-                if metadata_url.is_empty() {
-                    return false;
-                }
-                if !self.initialized {
-                    self.pending.insert(*sync, metadata_url.clone());
-                } else {
-                    let _ = self.tx.send((*sync, metadata_url.clone()));
-                }
-                false
-            }
+            }) => self.enqueue(*sync, metadata_url, event.timestamp),
 
-            // While pre-initialization, we use HydrateEvent as dedupe to clear finished jobs.
+            // The job is done once its metadata_url is hydrated
             EventData::Hydrate(HydrateEvent {
                 asset,
                 metadata_url,
                 ..
-            }) => {
-                if !self.initialized {
-                    if let Some(cur) = self.pending.get(asset) {
-                        if cur == metadata_url {
-                            self.pending.remove(asset);
-                        }
-                    }
-                }
-                false
-            }
+            }) => self.complete(*asset, metadata_url),
 
-            // Switching mode: drain the map to the worker and ignore HydrateEvents thereafter.
+            // Switching mode: persist the reconciled queue and wake the hydrator
             EventData::Initialize(InitializeEvent {}) => {
                 self.initialized = true;
-                for (asset, url) in self.pending.drain() {
-                    let _ = self.tx.send((asset, url));
+                self.saved.clear();
+                for job in self.jobs.values_mut() {
+                    // Jobs that were running when the queue was persisted
+                    if job.status == HydrationStatus::Running {
+                        job.status = HydrationStatus::Pending;
+                    }
                 }
-                false
+                self.persist();
+                let _ = self.tx.send(());
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Queue the hydration of `asset`'s `metadata_url`, replacing any job for an older one
+    fn enqueue(&mut self, asset: Public, metadata_url: &str, timestamp: u64) {
+        if metadata_url.is_empty() {
+            return;
+        }
+        if let Some(job) = self.jobs.get(&asset) {
+            if job.metadata_url == metadata_url {
+                return;
+            }
+        }
+        let job = match self.saved.get(&asset) {
+            Some(saved) if saved.metadata_url == metadata_url => saved.clone(),
+            _ => HydrationJob {
+                asset,
+                metadata_url: metadata_url.to_string(),
+                host: host_of(metadata_url),
+                status: HydrationStatus::Pending,
+                attempts: 0,
+                next_attempt: 0,
+                last_error: None,
+                created_at: timestamp,
+            },
+        };
+        self.jobs.insert(asset, job);
+        if self.initialized {
+            self.dirty = true;
+            let _ = self.tx.send(());
+        }
+    }
+
+    /// Mark the jobs that are due at `now` as running and return them, without going
+    /// over the limits on running jobs, along with when the next waiting job is due
+    pub fn take_due(&mut self, now: u64) -> (Vec<(Public, String)>, Option<u64>) {
+        if !self.initialized {
+            return (Vec::new(), None);
+        }
+        let mut running = 0;
+        let mut running_per_host: HashMap<String, usize> = HashMap::new();
+        for job in self.jobs.values() {
+            if job.status == HydrationStatus::Running {
+                running += 1;
+                *running_per_host.entry(job.host.clone()).or_default() += 1;
+            }
+        }
+
+        // Oldest first, so that a busy host doesn't starve the others
+        let mut due: Vec<&mut HydrationJob> = self
+            .jobs
+            .values_mut()
+            .filter(|job| job.status == HydrationStatus::Pending)
+            .collect();
+        due.sort_by_key(|job| (job.next_attempt, job.created_at));
+
+        let mut taken = Vec::new();
+        let mut next_due = None;
+        for job in due {
+            if job.next_attempt > now {
+                // The rest are due later still
+                next_due = Some(job.next_attempt);
+                break;
+            }
+            let host_running = running_per_host.entry(job.host.clone()).or_default();
+            if running >= MAX_RUNNING || *host_running >= MAX_RUNNING_PER_HOST {
+                continue;
             }
-            _ => false,
+            running += 1;
+            *host_running += 1;
+            job.status = HydrationStatus::Running;
+            taken.push((job.asset, job.metadata_url.clone()));
+        }
+        (taken, next_due)
+    }
+
+    /// Record that the metadata of `asset` at `metadata_url` was fetched.
+    /// The job is removed now, rather than when its HydrateEvent is applied,
+    /// so that it doesn't hold a slot meanwhile. Replay queues it again if
+    /// the event is lost.
+    pub fn on_success(&mut self, asset: Public, metadata_url: &str) {
+        self.complete(asset, metadata_url);
+    }
+
+    /// Remove the job for `asset` if it's still for `metadata_url`
+    fn complete(&mut self, asset: Public, metadata_url: &str) {
+        if self
+            .jobs
+            .get(&asset)
+            .is_some_and(|job| job.metadata_url == metadata_url)
+        {
+            self.jobs.remove(&asset);
+            self.dirty = true;
+        }
+    }
+
+    /// Record a failed attempt, scheduling a retry with exponential backoff
+    /// or giving up after too many attempts
+    pub fn on_failure(&mut self, asset: Public, metadata_url: &str, error: String) {
+        let Some(job) = self.jobs.get_mut(&asset) else {
+            return;
+        };
+        // The asset's metadata_url changed while this one was being fetched
+        if job.metadata_url != metadata_url {
+            return;
+        }
+        job.attempts += 1;
+        job.last_error = Some(error);
+        if job.attempts >= MAX_ATTEMPTS {
+            job.status = HydrationStatus::Failed;
+            self.prune_failed();
+        } else {
+            let delay = BASE_RETRY_DELAY
+                .saturating_mul(1 << (job.attempts - 1).min(32))
+                .min(MAX_RETRY_DELAY);
+            job.status = HydrationStatus::Pending;
+            job.next_attempt = unix_timestamp() + delay;
+        }
+        self.dirty = true;
+    }
+
+    /// Drop the oldest jobs given up on past `MAX_FAILED`. They're queued
+    /// again by the replay on the next start.
+    fn prune_failed(&mut self) {
+        let mut failed: Vec<(u64, Public)> = self
+            .jobs
+            .values()
+            .filter(|job| job.status == HydrationStatus::Failed)
+            .map(|job| (job.created_at, job.asset))
+            .collect();
+        if failed.len() <= MAX_FAILED {
+            return;
+        }
+        failed.sort_unstable();
+        for (_, asset) in &failed[..failed.len() - MAX_FAILED] {
+            self.jobs.remove(asset);
+        }
+    }
+
+    /// List the queued jobs, optionally only those with `status`, oldest first
+    pub fn get_jobs(&self, status: Option<HydrationStatus>) -> Vec<HydrationJob> {
+        let mut jobs: Vec<HydrationJob> = self
+            .jobs
+            .values()
+            .filter(|job| status.is_none_or(|s| job.status == s))
+            .cloned()
+            .collect();
+        jobs.sort_by_key(|job| job.created_at);
+        jobs
+    }
+
+    /// Retry a job now, with its attempts reset, unless it's running.
+    /// Returns the job, or `None` if there's no job for `asset`.
+    pub fn retry(&mut self, asset: &Public) -> Option<HydrationJob> {
+        let job = self.jobs.get_mut(asset)?;
+        if job.status != HydrationStatus::Running {
+            job.status = HydrationStatus::Pending;
+            job.attempts = 0;
+            job.next_attempt = 0;
+        }
+        let job = job.clone();
+        self.dirty = true;
+        let _ = self.tx.send(());
+        Some(job)
+    }
+
+    /// Retry every job that was given up on, returning how many there were
+    pub fn retry_failed(&mut self) -> usize {
+        let mut count = 0;
+        for job in self.jobs.values_mut() {
+            if job.status == HydrationStatus::Failed {
+                job.status = HydrationStatus::Pending;
+                job.attempts = 0;
+                job.next_attempt = 0;
+                count += 1;
+            }
+        }
+        if count > 0 {
+            self.dirty = true;
+            let _ = self.tx.send(());
+        }
+        count
+    }
+
+    /// Write the queue if it changed, unless it was written less than
+    /// `PERSIST_INTERVAL` before `now`. Returns when to try again if
+    /// changes are left unwritten.
+    pub fn flush(&mut self, now: u64) -> Option<u64> {
+        if !self.dirty {
+            return None;
+        }
+        let due = self.persisted_at + PERSIST_INTERVAL;
+        if now < due {
+            return Some(due);
+        }
+        self.persist();
+        None
+    }
+
+    fn persist(&mut self) {
+        let jobs: Vec<_> = self.jobs.values().cloned().collect();
+        if let Err(e) = self.store.write(&jobs) {
+            eprintln!("warning: Can't write hydration queue: {}", e);
         }
+        self.dirty = false;
+        self.persisted_at = unix_timestamp();
     }
 }
@@ -1,10 +1,15 @@
+pub mod alerts;
 pub mod assets;
 pub mod comments;
+pub mod fees;
 pub mod games;
 pub mod hydrate;
 pub mod pnl;
 pub mod prices;
 pub mod receipts;
+pub mod stats;
 pub mod sync;
+pub mod vaults;
 pub mod volume;
+pub mod webhooks;
 pub mod world;
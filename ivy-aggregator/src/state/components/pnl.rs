@@ -1,26 +1,300 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
 
 use crate::state::components::prices::PricesComponent;
 use crate::state::components::world::WorldComponent;
-use crate::state::types::{PnlEntry, PnlResponse};
+use crate::state::types::{BoardRank, HolderEntry, HoldersResponse, PnlEntry, PnlResponse};
 use crate::types::event::{Event, EventData, GameSwapEvent, SyncSwapEvent};
+use crate::types::leaderboard::Leaderboard;
 use crate::types::public::Public;
-use crate::util::{from_game_amount, from_ivy_amount, from_sol_amount, mil_to_usd, usd_to_mil};
+use crate::util::{
+    from_game_amount, from_ivy_amount, from_sol_amount, mil_to_usd, order_key, usd_to_mil,
+};
+
+// Number of holders counted in the concentration metric
+const TOP_HOLDER_COUNT: usize = 10;
+
+// How far an asset's price may move, relative to the price its unrealized
+// indexes were built at, before they're rebuilt
+const MARK_TOLERANCE: f32 = 0.01;
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PnlSort {
+    /// Realized profit over the cost basis of what was sold
+    RealizedRoi,
+    /// Realized profit, in USD
+    RealizedProfit,
+    /// Realized plus unrealized profit at the current price, over USD in
+    UnrealizedRoi,
+    /// Realized plus unrealized profit at the current price, in USD
+    UnrealizedProfit,
+}
+
+/// How the cost of tokens sold is determined
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CostBasis {
+    /// Tokens sold cost the position's average cost
+    Average,
+    /// Tokens sold cost what the oldest tokens still held were bought for
+    Fifo,
+}
+
+impl CostBasis {
+    /// Read the cost basis from the `PNL_COST_BASIS` environment variable,
+    /// `average` (default) or `fifo`
+    pub fn from_env() -> Self {
+        match std::env::var("PNL_COST_BASIS").as_deref() {
+            Ok("fifo") => CostBasis::Fifo,
+            Ok("average") | Err(_) => CostBasis::Average,
+            Ok(_) => {
+                eprintln!("warning: Can't parse PNL_COST_BASIS, using average");
+                CostBasis::Average
+            }
+        }
+    }
+}
+
+/// `v * num / den`, without overflowing
+fn mul_div(v: u64, num: u64, den: u64) -> u64 {
+    ((v as u128 * num as u128) / den as u128) as u64
+}
 
 pub struct Pnl {
     pub in_mil: u64,
     pub out_mil: u64,
     pub position_raw: u64,
+    /// Cost basis of the current position
+    pub cost_mil: u64,
+    /// Cost basis of everything sold so far
+    pub sold_cost_mil: u64,
+    // Lots still held as (amount, cost), oldest first, under FIFO
+    lots: VecDeque<(u64, u64)>,
 }
 
+impl Pnl {
+    fn new() -> Self {
+        Self {
+            in_mil: 0,
+            out_mil: 0,
+            position_raw: 0,
+            cost_mil: 0,
+            sold_cost_mil: 0,
+            lots: VecDeque::new(),
+        }
+    }
+
+    fn buy(&mut self, value_mil: u64, amount_raw: u64, basis: CostBasis) {
+        self.in_mil = self.in_mil.saturating_add(value_mil);
+        self.position_raw = self.position_raw.saturating_add(amount_raw);
+        self.cost_mil = self.cost_mil.saturating_add(value_mil);
+        if basis == CostBasis::Fifo {
+            self.lots.push_back((amount_raw, value_mil));
+        }
+    }
+
+    fn sell(&mut self, value_mil: u64, amount_raw: u64, basis: CostBasis) {
+        self.out_mil = self.out_mil.saturating_add(value_mil);
+        // Tokens sold beyond the position (received elsewhere) have no cost basis
+        let sold = amount_raw.min(self.position_raw);
+        let sold_cost = match basis {
+            _ if sold == 0 => 0,
+            CostBasis::Average => mul_div(self.cost_mil, sold, self.position_raw),
+            CostBasis::Fifo => {
+                let mut left = sold;
+                let mut cost = 0;
+                while left > 0 {
+                    let Some(lot) = self.lots.front_mut() else {
+                        break;
+                    };
+                    if lot.0 <= left {
+                        left -= lot.0;
+                        cost += lot.1;
+                        self.lots.pop_front();
+                    } else {
+                        let part = mul_div(lot.1, left, lot.0);
+                        lot.0 -= left;
+                        lot.1 -= part;
+                        cost += part;
+                        left = 0;
+                    }
+                }
+                cost
+            }
+        };
+        self.position_raw -= sold;
+        self.cost_mil = self.cost_mil.saturating_sub(sold_cost);
+        self.sold_cost_mil = self.sold_cost_mil.saturating_add(sold_cost);
+        if self.position_raw == 0 {
+            // Drop rounding dust
+            self.cost_mil = 0;
+            self.lots.clear();
+        }
+    }
+
+    /// Profit from sells: proceeds minus the cost basis of what was sold
+    fn realized_profit(&self) -> f64 {
+        (self.out_mil as f64 - self.sold_cost_mil as f64) / 1000.0
+    }
+
+    fn realized_roi(&self) -> f64 {
+        match self.sold_cost_mil {
+            0 => 0.0,
+            sold_cost_mil => self.realized_profit() / (sold_cost_mil as f64 / 1000.0),
+        }
+    }
+
+    /// Profit of the position at `price`: its value minus its cost basis
+    fn unrealized_profit(&self, price: f32) -> f64 {
+        from_game_amount(self.position_raw) as f64 * price as f64 - self.cost_mil as f64 / 1000.0
+    }
+
+    fn total_roi(&self, price: f32) -> f64 {
+        match self.in_mil {
+            0 => 0.0,
+            in_mil => self.total_profit(price) / (in_mil as f64 / 1000.0),
+        }
+    }
+
+    fn total_profit(&self, price: f32) -> f64 {
+        self.realized_profit() + self.unrealized_profit(price)
+    }
+
+    fn to_entry(&self, user: Public, price: f32) -> PnlEntry {
+        PnlEntry {
+            user,
+            in_usd: mil_to_usd(self.in_mil),
+            out_usd: mil_to_usd(self.out_mil),
+            position: from_game_amount(self.position_raw),
+            cost_usd: mil_to_usd(self.cost_mil),
+            realized_usd: self.realized_profit() as f32,
+            unrealized_usd: self.unrealized_profit(price) as f32,
+        }
+    }
+}
+
+/// Sort keys of a user's PnL, in the order of `PnlSort`
+fn sort_keys(pnl: &Pnl, mark_price: f32) -> [u64; 4] {
+    [
+        pnl.realized_roi(),
+        pnl.realized_profit(),
+        pnl.total_roi(mark_price),
+        pnl.total_profit(mark_price),
+    ]
+    .map(order_key)
+}
+
+/// The PnL of every user of an asset, with an index for each `PnlSort`
+/// and one of its holders
+struct PnlAsset {
+    users: HashMap<Public, Pnl>,
+    /// The price the unrealized indexes are ordered at
+    mark_price: f32,
+    /// An index per `PnlSort`, in its order
+    indexes: [Leaderboard<Public, u64>; 4],
+    /// Users by position, in raw token units; users with an empty position are left out
+    holders: Leaderboard<Public, u64>,
+    /// Sum of all positions
+    held_raw: u64,
+}
+
+impl PnlAsset {
+    fn new() -> Self {
+        Self {
+            users: HashMap::new(),
+            mark_price: 0.0,
+            indexes: Default::default(),
+            holders: Leaderboard::new(),
+            held_raw: 0,
+        }
+    }
+
+    /// The fraction of all positions that `raw` is
+    fn share(&self, raw: u64) -> f32 {
+        match self.held_raw {
+            0 => 0.0,
+            total => (raw as f64 / total as f64) as f32,
+        }
+    }
+
+    /// The share of all positions held by the largest holders
+    fn top_share(&self) -> f32 {
+        let top_raw: u64 = self
+            .holders
+            .range(0, TOP_HOLDER_COUNT)
+            .map(|(_, &raw)| raw)
+            .sum();
+        self.share(top_raw)
+    }
+
+    /// Apply a swap to `user`'s PnL and reindex them
+    fn on_swap(
+        &mut self,
+        user: Public,
+        is_buy: bool,
+        value_mil: u64,
+        amount_raw: u64,
+        basis: CostBasis,
+    ) {
+        let pnl = self.users.entry(user).or_insert_with(Pnl::new);
+        let old_position = pnl.position_raw;
+        if is_buy {
+            pnl.buy(value_mil, amount_raw, basis);
+        } else {
+            pnl.sell(value_mil, amount_raw, basis);
+        }
+
+        let keys = sort_keys(pnl, self.mark_price);
+        for (index, key) in self.indexes.iter_mut().zip(keys) {
+            index.update(user, key);
+        }
+
+        let position = pnl.position_raw;
+        self.held_raw = self.held_raw - old_position + position;
+        if position == 0 {
+            self.holders.remove(&user);
+        } else {
+            self.holders.update(user, position);
+        }
+    }
+
+    /// Whether `price` is far enough from the mark price to reorder the unrealized indexes
+    fn is_stale(&self, price: f32) -> bool {
+        (price - self.mark_price).abs() > self.mark_price * MARK_TOLERANCE
+    }
+
+    /// Rebuild the unrealized indexes at `price`
+    fn remark(&mut self, price: f32) {
+        self.mark_price = price;
+        let [.., unrealized_roi, unrealized_profit] = &mut self.indexes;
+        unrealized_roi.clear();
+        unrealized_profit.clear();
+        for (&user, pnl) in &self.users {
+            unrealized_roi.update(user, order_key(pnl.total_roi(price)));
+            unrealized_profit.update(user, order_key(pnl.total_profit(price)));
+        }
+    }
+}
+
+/// Keeps track of each user's PnL per asset, with leaderboards that are
+/// updated as swaps come in rather than sorted on each request. The holders
+/// of each asset are ranked by the positions kept for PnL.
+///
+/// Unrealized leaderboards depend on the asset's price, so they're ordered at
+/// a mark price. It's moved lazily, when a leaderboard is queried at a price
+/// that drifted `MARK_TOLERANCE` away, rather than as trades move the price.
 pub struct PnlComponent {
-    pub(crate) address_to_pnl_map: HashMap<Public, HashMap<Public, Pnl>>,
+    assets: HashMap<Public, PnlAsset>,
+    basis: CostBasis,
 }
 
 impl PnlComponent {
-    pub fn new() -> Self {
+    pub fn new(basis: CostBasis) -> Self {
         Self {
-            address_to_pnl_map: HashMap::new(),
+            assets: HashMap::new(),
+            basis,
         }
     }
 
@@ -31,11 +305,32 @@ impl PnlComponent {
         prices: &PricesComponent,
     ) -> bool {
         match &event.data {
-            EventData::GameSwap(swap) => self.handle_game_swap(swap, world),
-            EventData::SyncSwap(swap) => self.handle_sync_swap(swap, prices),
-            _ => return false,
+            EventData::GameSwap(swap) => {
+                self.handle_game_swap(swap, world);
+                true
+            }
+            EventData::SyncSwap(swap) => {
+                self.handle_sync_swap(event.timestamp, swap, prices);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether `game`'s unrealized leaderboards must be re-marked to be ordered at `price`
+    pub fn needs_mark(&self, game: &Public, price: f32) -> bool {
+        self.assets
+            .get(game)
+            .is_some_and(|asset| asset.is_stale(price))
+    }
+
+    /// Re-mark `game`'s unrealized leaderboards at `price`, if it drifted from their mark
+    pub fn mark(&mut self, game: &Public, price: f32) {
+        if let Some(asset) = self.assets.get_mut(game) {
+            if asset.is_stale(price) {
+                asset.remark(price);
+            }
         }
-        true
     }
 
     fn handle_game_swap(&mut self, swap_data: &GameSwapEvent, world: &WorldComponent) {
@@ -48,51 +343,71 @@ impl PnlComponent {
         let usdc_value = ivy_amount * ivy_price;
         let usdc_value_mil = usd_to_mil(usdc_value);
 
-        let pnl = self
-            .address_to_pnl_map
+        self.assets
             .entry(swap_data.game)
-            .or_insert_with(HashMap::new)
-            .entry(user)
-            .or_insert(Pnl {
-                in_mil: 0,
-                out_mil: 0,
-                position_raw: 0,
-            });
-
-        if swap_data.is_buy {
-            pnl.in_mil = pnl.in_mil.saturating_add(usdc_value_mil);
-            pnl.position_raw = pnl.position_raw.saturating_add(swap_data.game_amount);
-        } else {
-            pnl.out_mil = pnl.out_mil.saturating_add(usdc_value_mil);
-            pnl.position_raw = pnl.position_raw.saturating_sub(swap_data.game_amount);
-        }
+            .or_insert_with(PnlAsset::new)
+            .on_swap(
+                user,
+                swap_data.is_buy,
+                usdc_value_mil,
+                swap_data.game_amount,
+                self.basis,
+            );
     }
 
-    fn handle_sync_swap(&mut self, swap_data: &SyncSwapEvent, prices: &PricesComponent) {
+    fn handle_sync_swap(
+        &mut self,
+        timestamp: u64,
+        swap_data: &SyncSwapEvent,
+        prices: &PricesComponent,
+    ) {
         let user = swap_data.user;
         if user == Public::zero() {
             return;
         }
-        let usdc_value = from_sol_amount(swap_data.sol_amount) * prices.sol();
+        let usdc_value = from_sol_amount(swap_data.sol_amount) * prices.sol_at(timestamp);
         let usdc_value_mil = usd_to_mil(usdc_value);
 
-        let pnl = self
-            .address_to_pnl_map
+        self.assets
             .entry(swap_data.sync)
-            .or_insert_with(HashMap::new)
-            .entry(user)
-            .or_insert(Pnl {
-                in_mil: 0,
-                out_mil: 0,
-                position_raw: 0,
-            });
-
-        if swap_data.is_buy {
-            pnl.in_mil = pnl.in_mil.saturating_add(usdc_value_mil);
-            pnl.position_raw = pnl.position_raw.saturating_add(swap_data.token_amount);
-        } else {
-            pnl.out_mil = pnl.out_mil.saturating_add(usdc_value_mil);
-            pnl.position_raw = pnl.position_raw.saturating_sub(swap_data.token_amount);
+            .or_insert_with(PnlAsset::new)
+            .on_swap(
+                user,
+                swap_data.is_buy,
+                usdc_value_mil,
+                swap_data.token_amount,
+                self.basis,
+            );
+    }
+
+    /// The number of users holding some of an asset
+    pub fn holder_count(&self, asset: &Public) -> u64 {
+        self.assets
+            .get(asset)
+            .map_or(0, |asset| asset.holders.len() as u64)
+    }
+
+    /// Query the holders of an asset, sorted by position (largest first)
+    pub fn query_holders(&self, asset: Public, count: usize, skip: usize) -> HoldersResponse {
+        let Some(asset) = self.assets.get(&asset) else {
+            return HoldersResponse {
+                total: 0,
+                top10_share: 0.0,
+                holders: Vec::new(),
+            };
+        };
+        HoldersResponse {
+            total: asset.holders.len(),
+            top10_share: asset.top_share(),
+            holders: asset
+                .holders
+                .range(skip, count)
+                .map(|(&user, &raw)| HolderEntry {
+                    user,
+                    position: from_game_amount(raw),
+                    share: asset.share(raw),
+                })
+                .collect(),
         }
     }
 
@@ -102,85 +417,59 @@ impl PnlComponent {
         price: f32,
         count: usize,
         skip: usize,
-        realized: bool,
+        sort: PnlSort,
     ) -> Vec<PnlEntry> {
-        let pnl_map = match self.address_to_pnl_map.get(&game) {
-            Some(v) => v,
-            None => return Vec::new(),
+        let Some(asset) = self.assets.get(&game) else {
+            return Vec::new();
         };
+        asset.indexes[sort as usize]
+            .range(skip, count)
+            .map(|(user, _)| asset.users[user].to_entry(*user, price))
+            .collect()
+    }
 
-        let mut entries: Vec<PnlEntry> = pnl_map
-            .iter()
-            .map(|(&user, p)| PnlEntry {
-                user,
-                in_usd: mil_to_usd(p.in_mil),
-                out_usd: mil_to_usd(p.out_mil),
-                position: from_game_amount(p.position_raw),
-            })
-            .collect();
-
-        if realized {
-            entries.sort_by(|a, b| {
-                let ratio_a = if a.in_usd > 0.0 {
-                    a.out_usd / a.in_usd
-                } else {
-                    0.0
-                };
-                let ratio_b = if b.in_usd > 0.0 {
-                    b.out_usd / b.in_usd
-                } else {
-                    0.0
-                };
-                ratio_b.total_cmp(&ratio_a)
-            });
-        } else {
-            entries.sort_by(|a, b| {
-                let out_a = a.out_usd + a.position * price;
-                let out_b = b.out_usd + b.position * price;
-                let ratio_a = if a.in_usd > 0.0 {
-                    out_a / a.in_usd
-                } else {
-                    0.0
-                };
-                let ratio_b = if b.in_usd > 0.0 {
-                    out_b / b.in_usd
-                } else {
-                    0.0
-                };
-                ratio_b.total_cmp(&ratio_a)
-            });
-        }
-
-        if skip == 0 {
-            entries.truncate(count);
-            return entries;
-        }
-        if count == 0 || skip >= entries.len() {
-            return Vec::new();
+    /// Get a user's rank on an asset's PnL leaderboard,
+    /// with `neighbors` entries above and below them
+    pub fn get_pnl_rank(
+        &self,
+        game: Public,
+        user: Public,
+        price: f32,
+        sort: PnlSort,
+        neighbors: usize,
+    ) -> BoardRank<PnlEntry> {
+        let Some(asset) = self.assets.get(&game) else {
+            return BoardRank::unranked(user, 0);
+        };
+        let index = &asset.indexes[sort as usize];
+        let Some((rank, start, entries)) = index.neighbors(&user, neighbors) else {
+            return BoardRank::unranked(user, index.len());
+        };
+        BoardRank {
+            user,
+            rank: Some(rank),
+            total: index.len(),
+            neighbors_start: start,
+            neighbors: entries
+                .map(|(user, _)| asset.users[user].to_entry(*user, price))
+                .collect(),
         }
-        let left = skip;
-        let right = (skip + count).min(entries.len());
-        entries[left..right].to_vec()
     }
 
     pub fn get_pnl(&self, game: Public, user: Public, price: f32) -> PnlResponse {
-        match self
-            .address_to_pnl_map
-            .get(&game)
-            .and_then(|m| m.get(&user))
-        {
-            Some(p) => PnlResponse {
-                in_usd: mil_to_usd(p.in_mil),
-                out_usd: mil_to_usd(p.out_mil),
-                position: from_game_amount(p.position_raw),
-                price,
-            },
-            None => PnlResponse {
-                in_usd: 0.0,
-                out_usd: 0.0,
-                position: 0.0,
-                price,
-            },
+        let entry = match self.assets.get(&game).and_then(|a| a.users.get(&user)) {
+            Some(p) => p.to_entry(user, price),
+            None => Pnl::new().to_entry(user, price),
+        };
+        PnlResponse {
+            in_usd: entry.in_usd,
+            out_usd: entry.out_usd,
+            position: entry.position,
+            cost_usd: entry.cost_usd,
+            realized_usd: entry.realized_usd,
+            unrealized_usd: entry.unrealized_usd,
+            price,
+            cost_basis: self.basis,
         }
     }
 }
@@ -1,38 +1,299 @@
+use crate::applier::Cursor;
+use crate::state::types::{SolPriceInfo, SolPricePoint, SourcePrice};
 use crate::types::event::{Event, EventData, SolPriceEvent};
+use crate::types::public::Public;
+use crate::types::source::PriceSource;
+use crate::util::unix_timestamp;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use tokio::sync::watch;
 
+// Least time between samples of the SOL/USDC pool, in seconds
+const POOL_SAMPLE_INTERVAL: u64 = 60;
+// Resolution of the price history, in seconds
+const HISTORY_RESOLUTION: u64 = 60;
+
+/// SOL price oracle settings
+#[derive(Clone)]
+pub struct PriceConfig {
+    /// Seconds after which a source's price is stale and left out of the median
+    pub stale_secs: u64,
+    /// A PumpSwap pool with WSOL as its base and USDC as its quote, to price SOL from
+    pub sol_usdc_pool: Option<Public>,
+}
+
+impl Default for PriceConfig {
+    fn default() -> Self {
+        Self {
+            stale_secs: 300,
+            sol_usdc_pool: None,
+        }
+    }
+}
+
+impl PriceConfig {
+    /// Read the config from environment variables,
+    /// using the default for each one that's missing or invalid
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let stale_secs = match std::env::var("SOL_PRICE_STALE_SECS") {
+            Ok(v) => v.parse().unwrap_or_else(|_| {
+                eprintln!("warning: Can't parse SOL_PRICE_STALE_SECS, using default");
+                default.stale_secs
+            }),
+            Err(_) => default.stale_secs,
+        };
+        let sol_usdc_pool =
+            std::env::var("SOL_USDC_POOL")
+                .ok()
+                .and_then(|v| match Public::from_str(&v) {
+                    Ok(pool) => Some(pool),
+                    Err(_) => {
+                        eprintln!("warning: Can't parse SOL_USDC_POOL, ignoring it");
+                        None
+                    }
+                });
+        Self {
+            stale_secs,
+            sol_usdc_pool,
+        }
+    }
+}
+
+/// A SOL price that replaces the recorded one over `[from, to)`,
+/// for when a source reported a wrong price
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SolPriceCorrection {
+    pub from: u64,
+    pub to: u64,
+    pub price: f32,
+    pub created_at: u64,
+}
+
 pub struct PricesComponent {
+    config: PriceConfig,
     sol: f32,
+    // Latest (price, timestamp) from each source, indexed by `PriceSource`
+    samples: [Option<(f64, u64)>; PriceSource::ALL.len()],
+    // When `sol` was last updated
+    updated: u64,
+    // The SOL price at the end of each minute it changed, oldest first
+    history: Vec<SolPricePoint>,
+    // Oldest first, so later corrections take precedence
+    corrections: Vec<SolPriceCorrection>,
+    store: Cursor<Vec<SolPriceCorrection>>,
     price_tx: watch::Sender<f32>,
     price_rx: watch::Receiver<f32>,
 }
 
 impl PricesComponent {
-    pub fn new() -> Self {
+    pub fn new(config: PriceConfig, mut store: Cursor<Vec<SolPriceCorrection>>) -> Self {
+        let corrections = match store.read() {
+            Ok(corrections) => corrections.unwrap_or_default(),
+            Err(e) => {
+                eprintln!("warning: Can't read SOL price corrections: {}", e);
+                Vec::new()
+            }
+        };
         let (price_tx, price_rx) = watch::channel(0.0);
         Self {
+            config,
             sol: 0.0,
+            samples: [None; PriceSource::ALL.len()],
+            updated: 0,
+            history: Vec::new(),
+            corrections,
+            store,
             price_tx,
             price_rx,
         }
     }
 
     pub fn on_event(&mut self, event: &Event) -> bool {
-        if let &EventData::SolPrice(SolPriceEvent { price }) = &event.data {
-            if price.is_finite() && price > 0.0 {
-                self.sol = price as f32;
-                // Broadcast the new price to all watchers
-                _ = self.price_tx.send(self.sol);
-                return true;
+        match &event.data {
+            &EventData::SolPrice(SolPriceEvent { price, source }) => {
+                if price.is_finite() && price > 0.0 {
+                    self.add_sample(source, price, event.timestamp);
+                    return true;
+                }
+                false
+            }
+            EventData::PaBuy(d) if Some(d.pool) == self.config.sol_usdc_pool => self.on_pool_trade(
+                event.timestamp,
+                d.pool_base_token_reserves,
+                d.pool_quote_token_reserves,
+            ),
+            EventData::PaSell(d) if Some(d.pool) == self.config.sol_usdc_pool => self
+                .on_pool_trade(
+                    event.timestamp,
+                    d.pool_base_token_reserves,
+                    d.pool_quote_token_reserves,
+                ),
+            _ => false,
+        }
+    }
+
+    /// Price SOL from the reserves of the SOL/USDC pool,
+    /// persisting the trade only when it's sampled
+    fn on_pool_trade(&mut self, timestamp: u64, sol_reserves: u64, usdc_reserves: u64) -> bool {
+        if let Some((_, last)) = self.samples[PriceSource::Pool as usize] {
+            if timestamp < last + POOL_SAMPLE_INTERVAL {
+                return false;
             }
         }
-        false
+        if sol_reserves == 0 || usdc_reserves == 0 {
+            return false;
+        }
+        // USDC has 6 decimals, SOL has 9
+        let price = (usdc_reserves as f64 / 1_000_000.0) / (sol_reserves as f64 / 1_000_000_000.0);
+        self.add_sample(PriceSource::Pool, price, timestamp);
+        true
+    }
+
+    /// Take the median of the sources that aren't stale at `timestamp`
+    fn add_sample(&mut self, source: PriceSource, price: f64, timestamp: u64) {
+        self.samples[source as usize] = Some((price, timestamp));
+        let mut fresh: Vec<f64> = self
+            .samples
+            .iter()
+            .flatten()
+            .filter(|&&(_, ts)| ts + self.config.stale_secs >= timestamp)
+            .map(|&(price, _)| price)
+            .collect();
+        fresh.sort_by(f64::total_cmp);
+        // The new sample is always fresh
+        let mid = fresh.len() / 2;
+        let median = if fresh.len().is_multiple_of(2) {
+            (fresh[mid - 1] + fresh[mid]) / 2.0
+        } else {
+            fresh[mid]
+        };
+
+        self.sol = median as f32;
+        self.updated = self.updated.max(timestamp);
+        // Broadcast the new price to all watchers
+        _ = self.price_tx.send(self.sol);
+
+        // Prices older than the last point are counted in it
+        let minute = timestamp - timestamp % HISTORY_RESOLUTION;
+        match self.history.last_mut() {
+            Some(last) if last.timestamp >= minute => last.price = self.sol,
+            _ => self.history.push(SolPricePoint {
+                timestamp: minute,
+                price: self.sol,
+            }),
+        }
     }
 
     pub fn sol(&self) -> f32 {
         self.sol
     }
 
+    /// Get the SOL price at `timestamp`: the latest correction covering it,
+    /// else the last price at or before it, or the first one known if there's none before it
+    pub fn sol_at(&self, timestamp: u64) -> f32 {
+        if let Some(price) = self.correction_at(timestamp) {
+            return price;
+        }
+        match self.history.partition_point(|p| p.timestamp <= timestamp) {
+            0 => self.history.first().map(|p| p.price).unwrap_or(self.sol),
+            i => self.history[i - 1].price,
+        }
+    }
+
+    fn correction_at(&self, timestamp: u64) -> Option<f32> {
+        self.corrections
+            .iter()
+            .rev()
+            .find(|c| (c.from..c.to).contains(&timestamp))
+            .map(|c| c.price)
+    }
+
+    /// Get the SOL price and each source's latest price, as of `now`
+    pub fn get_sol_info(&self, now: u64) -> SolPriceInfo {
+        let is_stale = |ts: u64| ts + self.config.stale_secs < now;
+        let sources: Vec<SourcePrice> = PriceSource::ALL
+            .iter()
+            .filter_map(|&source| {
+                let (price, timestamp) = self.samples[source as usize]?;
+                Some(SourcePrice {
+                    source,
+                    price: price as f32,
+                    timestamp,
+                    stale: is_stale(timestamp),
+                })
+            })
+            .collect();
+        SolPriceInfo {
+            price: self.sol,
+            timestamp: self.updated,
+            stale: sources.iter().all(|s| s.stale),
+            sources,
+        }
+    }
+
+    /// Get the last SOL price of each `interval` seconds within `[from, to)`, corrected
+    pub fn query_sol_history(
+        &self,
+        from: Option<u64>,
+        to: Option<u64>,
+        interval: u64,
+    ) -> Vec<SolPricePoint> {
+        let from = from.unwrap_or(0);
+        let to = to.unwrap_or(u64::MAX);
+        let start = self.history.partition_point(|p| p.timestamp < from);
+        let mut points: Vec<SolPricePoint> = Vec::new();
+        for p in self.history[start..]
+            .iter()
+            .take_while(|p| p.timestamp < to)
+        {
+            let bucket = p.timestamp - p.timestamp % interval;
+            let price = self.correction_at(p.timestamp).unwrap_or(p.price);
+            match points.last_mut() {
+                Some(last) if last.timestamp == bucket => last.price = price,
+                _ => points.push(SolPricePoint {
+                    timestamp: bucket,
+                    price,
+                }),
+            }
+        }
+        points
+    }
+
+    pub fn get_corrections(&self) -> &[SolPriceCorrection] {
+        &self.corrections
+    }
+
+    /// Correct the SOL price over `[from, to)`. Charts take it when they're queried,
+    /// while aggregates valued at the wrong price keep it until they're revalued.
+    pub fn add_correction(&mut self, from: u64, to: u64, price: f32) -> SolPriceCorrection {
+        let correction = SolPriceCorrection {
+            from,
+            to,
+            price,
+            created_at: unix_timestamp(),
+        };
+        self.corrections.push(correction.clone());
+        self.persist();
+        correction
+    }
+
+    /// Remove every correction, returning how many there were
+    pub fn clear_corrections(&mut self) -> usize {
+        let count = self.corrections.len();
+        if count > 0 {
+            self.corrections.clear();
+            self.persist();
+        }
+        count
+    }
+
+    fn persist(&mut self) {
+        if let Err(e) = self.store.write(&self.corrections) {
+            eprintln!("warning: Can't write SOL price corrections: {}", e);
+        }
+    }
+
     /// Subscribe to real-time SOL price updates.
     /// Returns a receiver that will receive the latest SOL price whenever it changes.
     pub fn subscribe_sol(&self) -> watch::Receiver<f32> {
@@ -1,41 +1,82 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::types::event::{Event, EventData, GameBurnEvent, GameDepositEvent, GameWithdrawEvent};
 use crate::types::public::Public;
+use crate::types::receipt_log::ReceiptLog;
 
-use crate::state::types::{BurnInfo, DepositInfo, WithdrawInfo};
+use crate::state::types::{
+    BurnInfo, DailyReceipts, DepositInfo, ReceiptEntry, ReceiptList, WithdrawInfo,
+};
 use crate::types::signature::Signature;
 
-#[derive(Hash, Clone, Copy, PartialEq, Eq)]
-struct BurnKey {
-    game: Public,
-    id: [u8; 32],
+/// A time window and page of receipts to query
+#[derive(Clone, Copy)]
+pub struct ReceiptQuery {
+    /// Inclusive start, as a Unix timestamp
+    pub from: Option<u64>,
+    /// Exclusive end, as a Unix timestamp
+    pub to: Option<u64>,
+    pub count: usize,
+    pub skip: usize,
+    pub reverse: bool,
 }
 
-#[derive(Hash, Clone, Copy, PartialEq, Eq)]
-struct DepositKey {
-    game: Public,
-    id: [u8; 32],
+struct GameReceipts {
+    burns: ReceiptLog<BurnInfo>,
+    deposits: ReceiptLog<DepositInfo>,
+    withdraws: ReceiptLog<WithdrawInfo>,
 }
 
-#[derive(Hash, Clone, Copy, PartialEq, Eq)]
-struct WithdrawKey {
-    game: Public,
-    id: [u8; 32],
+impl GameReceipts {
+    fn new() -> Self {
+        Self {
+            burns: ReceiptLog::new(),
+            deposits: ReceiptLog::new(),
+            withdraws: ReceiptLog::new(),
+        }
+    }
+}
+
+/// Query `log`, or an empty log if it's `None`, as a `ReceiptList`
+pub fn list_receipts<T: Copy>(
+    log: Option<&ReceiptLog<T>>,
+    query: ReceiptQuery,
+    filter: impl Fn(&T) -> bool,
+) -> ReceiptList<T> {
+    let Some(log) = log else {
+        return ReceiptList {
+            total: 0,
+            receipts: Vec::new(),
+        };
+    };
+    let (total, receipts) = log.query(
+        query.from,
+        query.to,
+        filter,
+        query.count,
+        query.skip,
+        query.reverse,
+    );
+    ReceiptList {
+        total,
+        receipts: receipts
+            .into_iter()
+            .map(|(id, &info)| ReceiptEntry {
+                id: hex::encode(id),
+                info,
+            })
+            .collect(),
+    }
 }
 
 pub struct ReceiptsComponent {
-    burns: HashMap<BurnKey, BurnInfo>,
-    deposits: HashMap<DepositKey, DepositInfo>,
-    withdraws: HashMap<WithdrawKey, WithdrawInfo>,
+    games: HashMap<Public, GameReceipts>,
 }
 
 impl ReceiptsComponent {
     pub fn new() -> Self {
         Self {
-            burns: HashMap::new(),
-            deposits: HashMap::new(),
-            withdraws: HashMap::new(),
+            games: HashMap::new(),
         }
     }
 
@@ -50,25 +91,24 @@ impl ReceiptsComponent {
             EventData::GameWithdraw(data) => {
                 self.process_game_withdraw(event.timestamp, &event.signature, data)
             }
-            EventData::VaultDeposit(_) | EventData::VaultWithdraw(_) => {
-                // Nothing yet, but let's keep them around in case
-                // we want to do something in the future with them
-            }
             _ => return false,
         }
         true
     }
 
+    fn game_mut(&mut self, game: Public) -> &mut GameReceipts {
+        self.games.entry(game).or_insert_with(GameReceipts::new)
+    }
+
     fn process_game_burn(&mut self, timestamp: u64, signature: &Signature, burn: &GameBurnEvent) {
-        self.burns
-            .entry(BurnKey {
-                game: burn.game,
-                id: burn.id,
-            })
-            .or_insert(BurnInfo {
-                signature: signature.clone(),
+        self.game_mut(burn.game).burns.insert(
+            burn.id,
+            timestamp,
+            BurnInfo {
+                signature: *signature,
                 timestamp,
-            });
+            },
+        );
     }
 
     fn process_game_deposit(
@@ -77,15 +117,14 @@ impl ReceiptsComponent {
         signature: &Signature,
         deposit: &GameDepositEvent,
     ) {
-        self.deposits
-            .entry(DepositKey {
-                game: deposit.game,
-                id: deposit.id,
-            })
-            .or_insert(DepositInfo {
-                signature: signature.clone(),
+        self.game_mut(deposit.game).deposits.insert(
+            deposit.id,
+            timestamp,
+            DepositInfo {
+                signature: *signature,
                 timestamp,
-            });
+            },
+        );
     }
 
     fn process_game_withdraw(
@@ -94,27 +133,83 @@ impl ReceiptsComponent {
         signature: &Signature,
         withdraw: &GameWithdrawEvent,
     ) {
-        self.withdraws
-            .entry(WithdrawKey {
-                game: withdraw.game,
-                id: withdraw.id,
-            })
-            .or_insert(WithdrawInfo {
-                signature: signature.clone(),
+        self.game_mut(withdraw.game).withdraws.insert(
+            withdraw.id,
+            timestamp,
+            WithdrawInfo {
+                signature: *signature,
                 timestamp,
                 withdraw_authority: withdraw.withdraw_authority,
-            });
+            },
+        );
     }
 
     pub fn get_burn_info(&self, game: Public, id: [u8; 32]) -> Option<BurnInfo> {
-        self.burns.get(&BurnKey { game, id }).copied()
+        self.games.get(&game)?.burns.get(&id).copied()
     }
 
     pub fn get_deposit_info(&self, game: Public, id: [u8; 32]) -> Option<DepositInfo> {
-        self.deposits.get(&DepositKey { game, id }).copied()
+        self.games.get(&game)?.deposits.get(&id).copied()
     }
 
     pub fn get_withdraw_info(&self, game: Public, id: [u8; 32]) -> Option<WithdrawInfo> {
-        self.withdraws.get(&WithdrawKey { game, id }).copied()
+        self.games.get(&game)?.withdraws.get(&id).copied()
+    }
+
+    pub fn list_burns(&self, game: Public, query: ReceiptQuery) -> ReceiptList<BurnInfo> {
+        list_receipts(self.games.get(&game).map(|g| &g.burns), query, |_| true)
+    }
+
+    pub fn list_deposits(&self, game: Public, query: ReceiptQuery) -> ReceiptList<DepositInfo> {
+        list_receipts(self.games.get(&game).map(|g| &g.deposits), query, |_| true)
+    }
+
+    /// List a game's withdrawals, optionally only those by `withdraw_authority`
+    pub fn list_withdraws(
+        &self,
+        game: Public,
+        query: ReceiptQuery,
+        withdraw_authority: Option<Public>,
+    ) -> ReceiptList<WithdrawInfo> {
+        list_receipts(self.games.get(&game).map(|g| &g.withdraws), query, |w| {
+            withdraw_authority.is_none_or(|a| w.withdraw_authority == a)
+        })
+    }
+
+    /// Count a game's burns, deposits and withdrawals on each UTC day
+    /// with `from <= timestamp < to`, skipping days without any.
+    /// Withdrawals are optionally only counted if they're by `withdraw_authority`.
+    pub fn daily_totals(
+        &self,
+        game: Public,
+        from: Option<u64>,
+        to: Option<u64>,
+        withdraw_authority: Option<Public>,
+    ) -> Vec<DailyReceipts> {
+        let Some(g) = self.games.get(&game) else {
+            return Vec::new();
+        };
+
+        let mut days: BTreeMap<u64, DailyReceipts> = BTreeMap::new();
+        fn day(days: &mut BTreeMap<u64, DailyReceipts>, day: u64) -> &mut DailyReceipts {
+            days.entry(day).or_insert(DailyReceipts {
+                day,
+                burns: 0,
+                deposits: 0,
+                withdrawals: 0,
+            })
+        }
+        for (d, count) in g.burns.daily_counts(from, to, |_| true) {
+            day(&mut days, d).burns = count;
+        }
+        for (d, count) in g.deposits.daily_counts(from, to, |_| true) {
+            day(&mut days, d).deposits = count;
+        }
+        let by_authority =
+            |w: &WithdrawInfo| withdraw_authority.is_none_or(|a| w.withdraw_authority == a);
+        for (d, count) in g.withdraws.daily_counts(from, to, by_authority) {
+            day(&mut days, d).withdrawals = count;
+        }
+        days.into_values().collect()
     }
 }
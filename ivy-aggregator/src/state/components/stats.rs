@@ -0,0 +1,250 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::Deserialize;
+
+use crate::state::constants::HIDDEN_SYNCS;
+use crate::state::types::StatsPoint;
+use crate::types::event::{Event, EventData};
+use crate::types::public::Public;
+use crate::util::{from_ivy_amount, from_sol_amount};
+
+use super::fees::FeesComponent;
+use super::games::GamesComponent;
+use super::prices::PricesComponent;
+use super::world::WorldComponent;
+
+// Hourly points kept, 90 days' worth
+const MAX_HOURLY_POINTS: usize = 90 * 24;
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsInterval {
+    Hour,
+    Day,
+}
+
+/// A series of points at a fixed interval, the last of which is in progress
+struct Series {
+    interval: u64,
+    max_len: usize,
+    points: VecDeque<StatsPoint>,
+    // Traders seen in the last point
+    traders: HashSet<Public>,
+}
+
+impl Series {
+    fn new(interval: u64, max_len: usize) -> Self {
+        Self {
+            interval,
+            max_len,
+            points: VecDeque::new(),
+            traders: HashSet::new(),
+        }
+    }
+
+    /// Start new points up to the one containing `timestamp`, filling any gap
+    /// with empty points. Timestamps before the last point are counted in it.
+    fn roll(&mut self, timestamp: u64) {
+        let start = timestamp - timestamp % self.interval;
+        let (next, tvl_usd, ivy_price) = match self.points.back() {
+            None => (start, 0.0, 0.0),
+            // Carry over TVL and price, which are levels rather than sums
+            Some(last) if last.timestamp < start => {
+                (last.timestamp + self.interval, last.tvl_usd, last.ivy_price)
+            }
+            Some(_) => return,
+        };
+        // Only fill in as many points as are kept
+        let oldest = start.saturating_sub(
+            self.interval
+                .saturating_mul(self.max_len.saturating_sub(1) as u64),
+        );
+        let mut next = next.max(oldest);
+        self.traders.clear();
+        while next <= start {
+            if self.points.len() >= self.max_len {
+                self.points.pop_front();
+            }
+            self.points
+                .push_back(StatsPoint::new(next, tvl_usd, ivy_price));
+            next += self.interval;
+        }
+    }
+
+    fn query(&self, from: Option<u64>, to: Option<u64>) -> Vec<StatsPoint> {
+        let from = from.unwrap_or(0);
+        let to = to.unwrap_or(u64::MAX);
+        let start = self.points.partition_point(|p| p.timestamp < from);
+        self.points
+            .range(start..)
+            .take_while(|p| p.timestamp < to)
+            .copied()
+            .collect()
+    }
+}
+
+/// The USD volume of sync swaps in each point of a series
+struct SyncVolumeSeries {
+    interval: u64,
+    // Start of the last point
+    last: u64,
+    // Point start -> volume
+    volumes: HashMap<u64, f32>,
+}
+
+impl SyncVolumeSeries {
+    fn new(interval: u64) -> Self {
+        Self {
+            interval,
+            last: 0,
+            volumes: HashMap::new(),
+        }
+    }
+
+    /// Move to the point containing `timestamp`, like `Series::roll`
+    fn roll(&mut self, timestamp: u64) -> u64 {
+        self.last = self.last.max(timestamp - timestamp % self.interval);
+        self.last
+    }
+}
+
+/// The USD volume of sync swaps, at the SOL prices of the time, in each point
+/// of the statistics. It's replayed from the event log on its own to revalue
+/// the statistics after a correction to past SOL prices.
+pub struct SyncVolumes {
+    hourly: SyncVolumeSeries,
+    daily: SyncVolumeSeries,
+}
+
+impl SyncVolumes {
+    pub fn new() -> Self {
+        Self {
+            hourly: SyncVolumeSeries::new(60 * 60),
+            daily: SyncVolumeSeries::new(24 * 60 * 60),
+        }
+    }
+
+    /// Record `event`, after the prices component has applied it
+    pub fn on_event(&mut self, event: &Event, prices: &PricesComponent) {
+        let volume_usd = match &event.data {
+            EventData::SyncSwap(swap) => {
+                from_sol_amount(swap.sol_amount) * prices.sol_at(event.timestamp)
+            }
+            // Events that start points, as in `StatsComponent::on_event`
+            EventData::GameSwap(_)
+            | EventData::GameCreate(_)
+            | EventData::SyncCreate(_)
+            | EventData::Comment(_)
+            | EventData::WorldSwap(_) => 0.0,
+            _ => return,
+        };
+        for series in [&mut self.hourly, &mut self.daily] {
+            let start = series.roll(event.timestamp);
+            if volume_usd != 0.0 {
+                *series.volumes.entry(start).or_default() += volume_usd;
+            }
+        }
+    }
+}
+
+/// Keeps hourly and daily time series of protocol statistics.
+/// They're rebuilt from the event log on startup, like the rest of the state.
+pub struct StatsComponent {
+    hourly: Series,
+    daily: Series,
+}
+
+impl StatsComponent {
+    pub fn new() -> Self {
+        Self {
+            hourly: Series::new(60 * 60, MAX_HOURLY_POINTS),
+            daily: Series::new(24 * 60 * 60, usize::MAX),
+        }
+    }
+
+    /// Record `event`, after all other components have applied it
+    pub fn on_event(
+        &mut self,
+        event: &Event,
+        games: &GamesComponent,
+        world: &WorldComponent,
+        prices: &PricesComponent,
+        fees: &FeesComponent,
+    ) -> bool {
+        let ivy_price = world.price();
+        let (volume_usd, trader) = match &event.data {
+            EventData::GameSwap(swap) => (from_ivy_amount(swap.ivy_amount) * ivy_price, swap.user),
+            EventData::SyncSwap(swap) => (
+                from_sol_amount(swap.sol_amount) * prices.sol_at(event.timestamp),
+                swap.user,
+            ),
+            _ => (0.0, Public::zero()),
+        };
+        let is_new_game = matches!(event.data, EventData::GameCreate(_));
+        let is_new_sync = match &event.data {
+            EventData::SyncCreate(d) => !HIDDEN_SYNCS.contains(&d.sync),
+            _ => false,
+        };
+        let is_comment = matches!(event.data, EventData::Comment(_));
+        let sync_volume_usd = match event.data {
+            EventData::SyncSwap(_) => volume_usd,
+            _ => 0.0,
+        };
+        let swap_fees = fees.last_fees();
+        if volume_usd == 0.0
+            && !is_new_game
+            && !is_new_sync
+            && !is_comment
+            && !matches!(event.data, EventData::WorldSwap(_))
+        {
+            return false;
+        }
+
+        let tvl_usd = from_ivy_amount(games.tvl_raw_ivy()) * ivy_price;
+        for series in [&mut self.hourly, &mut self.daily] {
+            series.roll(event.timestamp);
+            let is_new_trader = trader != Public::zero() && series.traders.insert(trader);
+            let point = series.points.back_mut().unwrap();
+            point.tvl_usd = tvl_usd;
+            point.ivy_price = ivy_price;
+            point.volume_usd += volume_usd;
+            point.sync_volume_usd += sync_volume_usd;
+            point.new_games += is_new_game as u64;
+            point.new_syncs += is_new_sync as u64;
+            point.comments += is_comment as u64;
+            point.active_traders += is_new_trader as u64;
+            point.ivy_fees_usd += swap_fees.ivy_fee_usd;
+            point.game_fees_usd += swap_fees.game_fee_usd;
+        }
+
+        // The events are persisted by the components they're for
+        false
+    }
+
+    /// Replace the volume of sync swaps in each point with the one in `volumes`
+    pub fn revalue(&mut self, volumes: &SyncVolumes) {
+        for (series, sync) in [
+            (&mut self.hourly, &volumes.hourly),
+            (&mut self.daily, &volumes.daily),
+        ] {
+            for point in &mut series.points {
+                let volume_usd = sync.volumes.get(&point.timestamp).copied().unwrap_or(0.0);
+                point.volume_usd += volume_usd - point.sync_volume_usd;
+                point.sync_volume_usd = volume_usd;
+            }
+        }
+    }
+
+    /// Get the points of a series that start within `[from, to)`
+    pub fn query(
+        &self,
+        interval: StatsInterval,
+        from: Option<u64>,
+        to: Option<u64>,
+    ) -> Vec<StatsPoint> {
+        match interval {
+            StatsInterval::Hour => self.hourly.query(from, to),
+            StatsInterval::Day => self.daily.query(from, to),
+        }
+    }
+}
@@ -1,29 +1,38 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 
 use serde::Serialize;
 use tokio::sync::{broadcast, watch};
 
-use crate::pf::{PaBuyEvent, PaSellEvent, PfMigrationEvent, PfTradeEvent};
+use crate::pf::{
+    PaBuyEvent, PaDepositEvent, PaSellEvent, PaWithdrawEvent, PfMigrationEvent, PfTradeEvent,
+};
 use crate::state::components::prices::PricesComponent;
 use crate::state::constants::{HIDDEN_SYNCS, MAX_CANDLES};
+use crate::state::types::{DepthLevel, LiquidityEvent, LiquidityInfo};
 use crate::types::asset::Asset;
 use crate::types::chart::Candle;
-use crate::types::charts::{ChartKind, Charts};
+use crate::types::charts::{CandleFolder, ChartKind, ChartQuote, Charts};
 use crate::types::event::{Event, EventData, HydrateEvent, SyncCreateEvent};
 use crate::types::public::Public;
-use crate::types::sync::Sync;
+use crate::types::signature::Signature;
+use crate::types::sync::{Sync, CURVE_TOKEN_SUPPLY, INITIAL_VIRTUAL_TOKEN_RESERVES};
 use crate::types::trade::Trade;
 use crate::util::{from_sol_amount, from_token_amount};
 
 use super::assets::AssetsComponent;
 
 // PF bonding curve parameters
-const INITIAL_VIRTUAL_TOKEN_RESERVES: usize = 1_073_000_000_000_000; // 1.073 billion TOKEN
 const INITIAL_VIRTUAL_SOL_RESERVES: usize = 30_000_000_000; // 30 SOL
 
 // 512 updates before receiver is deemed lagged :)
 const CHANNEL_BUFFER_SIZE: usize = 512;
 
+// Liquidity events kept per pool
+const MAX_LIQUIDITY_EVENTS: usize = 1024;
+
+// Price moves, in percent, that pool depth is measured at
+const DEPTH_LEVELS_PCT: [f32; 4] = [1.0, 2.0, 5.0, 10.0];
+
 #[derive(Debug, Clone, Copy, Serialize)]
 pub struct SyncUpdate {
     pub sol_reserves: u64, // virtual reserves pre-migration, pool reserves post-migration
@@ -38,20 +47,80 @@ pub struct SyncUpdate {
 // Raw amount = 1e6 * 1e9 = 1e15
 const SYNC_MAX_SUPPLY_TOKENS: f32 = 1_000_000.0;
 
+/// Orders curve progress percentages, which are never negative,
+/// the same way as the percentages themselves
+fn progress_key(pct: f32) -> u32 {
+    if pct > 0.0 {
+        pct.to_bits()
+    } else {
+        0
+    }
+}
+
 struct SyncMeta {
-    charts: Charts,
+    // Charts denominated in SOL. USD charts are valued from them when they're
+    // queried, so that corrections to past SOL prices apply to them right away.
+    sol_charts: Charts,
     // Broadcast channel for real-time updates
     update_tx: Option<broadcast::Sender<SyncUpdate>>,
+    // Liquidity deposits and withdrawals in the pool, oldest first
+    liquidity: VecDeque<LiquidityEvent>,
+    lp_supply: u64,
+    deposits: u64,
+    withdrawals: u64,
+}
+
+/// A change to a pool's liquidity, with the pool's state after it
+struct LiquidityChange {
+    pool: Public,
+    user: Public,
+    is_deposit: bool,
+    sol_amount: u64,
+    token_amount: u64,
+    lp_amount: u64,
+    sol_reserves: u64,
+    token_reserves: u64,
+    lp_supply: u64,
 }
 
 impl SyncMeta {
     fn new() -> Self {
         Self {
-            charts: Charts::new(MAX_CANDLES),
+            sol_charts: Charts::new(MAX_CANDLES),
             update_tx: None,
+            liquidity: VecDeque::new(),
+            lp_supply: 0,
+            deposits: 0,
+            withdrawals: 0,
         }
     }
 
+    /// Append a trade to the SOL charts
+    fn append_trade(
+        &mut self,
+        timestamp: u64,
+        sol_amount: u64,
+        token_amount: u64,
+        is_buy: bool,
+        user: Public,
+    ) {
+        let volume_sol = from_sol_amount(sol_amount);
+        let price_sol = volume_sol / from_token_amount(token_amount);
+        if let Err(e) = self
+            .sol_charts
+            .append(timestamp, price_sol, volume_sol, is_buy, Some(user))
+        {
+            eprintln!("Failed to append SOL chart data for sync trade: {:?}", e);
+        }
+    }
+
+    /// The change in the last 24h of the price in USD, at the SOL prices of the time
+    fn change_pct_24h(&self, prices: &PricesComponent) -> f32 {
+        self.sol_charts
+            .get_change_pct_24h_valued(|t| prices.sol_at(t))
+            .unwrap_or(0.0)
+    }
+
     /// Subscribe to sync updates (both curve and pool)
     fn subscribe(&mut self) -> broadcast::Receiver<SyncUpdate> {
         match &self.update_tx {
@@ -84,6 +153,10 @@ pub struct SyncComponent {
     address_to_index: HashMap<Public, usize>,
     pump_mint_to_index: HashMap<Public, usize>,
     pool_to_index: HashMap<Public, usize>,
+    // (create timestamp, index) of every sync
+    by_created: BTreeSet<(u64, usize)>,
+    // (progress key, create timestamp, index) of the syncs yet to migrate
+    by_progress: BTreeSet<(u32, u64, usize)>,
 
     // Trade stream for frontend updates
     trades_tx: watch::Sender<Option<Trade>>,
@@ -102,6 +175,8 @@ impl SyncComponent {
             address_to_index: HashMap::new(),
             pump_mint_to_index: HashMap::new(),
             pool_to_index: HashMap::new(),
+            by_created: BTreeSet::new(),
+            by_progress: BTreeSet::new(),
             trades_tx,
             assets_tx,
         }
@@ -114,15 +189,23 @@ impl SyncComponent {
             .map(|&index| self.metas[index].subscribe())
     }
 
-    /// Subscribe to chart updates for a specific sync
+    /// Subscribe to chart updates for a specific sync. Updates arrive in SOL,
+    /// and subscribers to the USD chart must value them at the SOL price.
+    /// Returns `None` if the sync doesn't exist or has no charts in `quote`.
     pub fn subscribe_to_sync_chart(
         &mut self,
         sync: &Public,
+        quote: ChartQuote,
         kind: ChartKind,
-    ) -> Option<broadcast::Receiver<Candle>> {
-        self.address_to_index
-            .get(sync)
-            .map(|&index| self.metas[index].charts.subscribe_to_kind(kind))
+        prices: &PricesComponent,
+    ) -> Option<(broadcast::Receiver<Candle>, CandleFolder)> {
+        let &index = self.address_to_index.get(sync)?;
+        let charts = &mut self.metas[index].sol_charts;
+        match quote {
+            ChartQuote::Usd => Some(charts.subscribe_to_kind_valued(kind, |t| prices.sol_at(t))),
+            ChartQuote::Sol => Some(charts.subscribe_to_kind(kind)),
+            ChartQuote::Ivy => None,
+        }
     }
 
     pub fn on_event(
@@ -137,9 +220,11 @@ impl SyncComponent {
                 true
             }
             EventData::PfTrade(d) => self.handle_pf_trade(event.timestamp, d, prices, assets),
-            EventData::PfMigration(d) => self.handle_pf_migration(d),
+            EventData::PfMigration(d) => self.handle_pf_migration(event.timestamp, d, assets),
             EventData::PaBuy(d) => self.handle_pa_buy(event.timestamp, d, prices, assets),
             EventData::PaSell(d) => self.handle_pa_sell(event.timestamp, d, prices, assets),
+            EventData::PaDeposit(d) => self.handle_pa_deposit(event, d),
+            EventData::PaWithdraw(d) => self.handle_pa_withdraw(event, d),
             EventData::Hydrate(d) => {
                 self.handle_hydrate(d);
                 true
@@ -167,12 +252,12 @@ impl SyncComponent {
 
         // Calculate initial price from virtual reserves
         let initial_sol_reserves = INITIAL_VIRTUAL_SOL_RESERVES as u64;
-        let initial_token_reserves = INITIAL_VIRTUAL_TOKEN_RESERVES as u64;
+        let initial_token_reserves = INITIAL_VIRTUAL_TOKEN_RESERVES;
 
         // Calculate initial price in USD using the bonding curve formula
         let sol_per_token =
             from_sol_amount(initial_sol_reserves) / from_token_amount(initial_token_reserves);
-        let initial_price_usd = sol_per_token * prices.sol();
+        let initial_price_usd = sol_per_token * prices.sol_at(timestamp);
         let initial_mkt_cap_usd = if initial_price_usd.is_normal() {
             initial_price_usd * SYNC_MAX_SUPPLY_TOKENS
         } else {
@@ -188,6 +273,9 @@ impl SyncComponent {
             metadata_url: d.metadata_url.clone(),
             icon_url: String::new(),    // removed from event; hydrate later
             description: String::new(), // removed from event; hydrate later
+            website: String::new(),
+            twitter: String::new(),
+            telegram: String::new(),
             game_url: d.game_url.clone(),
             is_migrated: false,
             pswap_pool: None,
@@ -196,6 +284,14 @@ impl SyncComponent {
             change_pct_24h: 0.0,
             sol_reserves: initial_sol_reserves,
             token_reserves: initial_token_reserves,
+            real_sol_reserves: 0,
+            real_token_reserves: CURVE_TOKEN_SUPPLY,
+            curve_progress_pct: 0.0,
+            creator_fees: 0,
+            curve_complete_timestamp: None,
+            migrate_timestamp: None,
+            first_pool_trade_timestamp: None,
+            holders: 0,
         };
 
         let idx = self.syncs.len();
@@ -203,10 +299,11 @@ impl SyncComponent {
 
         // Add initial candle to charts (similar to game.rs)
         // Using 0.0 for volume since this is just creation, not a trade
-        if initial_price_usd.is_normal() {
-            if let Err(e) = meta.charts.append(timestamp, initial_price_usd, 0.0) {
-                eprintln!("Failed to append chart data for sync create: {:?}", e);
-            }
+        if let Err(e) = meta
+            .sol_charts
+            .append(timestamp, sol_per_token, 0.0, true, None)
+        {
+            eprintln!("Failed to append SOL chart data for sync create: {:?}", e);
         }
 
         // Broadcast initial update
@@ -219,6 +316,9 @@ impl SyncComponent {
             pswap_pool: None,
         });
 
+        self.by_created.insert((timestamp, idx));
+        self.by_progress
+            .insert((progress_key(sync.curve_progress_pct), timestamp, idx));
         self.syncs.push(sync);
         self.metas.push(meta);
 
@@ -227,7 +327,7 @@ impl SyncComponent {
         self.pump_mint_to_index.insert(d.pump_mint, idx);
 
         // Update assets component
-        assets.on_sync_created(idx, initial_mkt_cap_usd, timestamp);
+        assets.on_sync_created(idx, &self.syncs[idx]);
     }
 
     fn handle_pf_trade(
@@ -260,11 +360,8 @@ impl SyncComponent {
             return false;
         }
 
-        // Store old market cap for asset update
-        let old_mkt_cap_usd = s.mkt_cap_usd;
-
         // Use the gold standard calculation method from handle_pa_buy
-        let sol_usd = prices.sol();
+        let sol_usd = prices.sol_at(timestamp);
         let volume_usd = from_sol_amount(d.sol_amount) * sol_usd;
         // how many USD per token?
         let price_usd =
@@ -274,19 +371,36 @@ impl SyncComponent {
             return false;
         }
 
-        if let Err(e) = meta.charts.append(timestamp, price_usd, volume_usd) {
-            eprintln!("Failed to append chart data for pf trade: {:?}", e);
-        }
+        meta.append_trade(timestamp, d.sol_amount, d.token_amount, d.is_buy, d.user);
 
         s.last_price_usd = price_usd;
         s.mkt_cap_usd = price_usd * SYNC_MAX_SUPPLY_TOKENS;
-        s.change_pct_24h = meta.charts.get_change_pct_24h().unwrap_or(0.0);
+        s.change_pct_24h = meta.change_pct_24h(prices);
 
         // Update virtual reserves (these come from the PfTradeEvent)
         s.sol_reserves = d.virtual_sol_reserves;
         s.token_reserves = d.virtual_token_reserves;
+        s.real_sol_reserves = d.real_sol_reserves;
+        s.real_token_reserves = d.real_token_reserves;
+        let old_progress = (
+            progress_key(s.curve_progress_pct),
+            s.create_timestamp,
+            index,
+        );
+        s.curve_progress_pct = s.migration_progress() * 100.0;
+        self.by_progress.remove(&old_progress);
+        self.by_progress.insert((
+            progress_key(s.curve_progress_pct),
+            s.create_timestamp,
+            index,
+        ));
+        s.creator_fees += d.creator_fee;
+        // The curve completes once its last real token is sold
+        if d.real_token_reserves == 0 && s.curve_complete_timestamp.is_none() {
+            s.curve_complete_timestamp = Some(timestamp);
+        }
 
-        let change_pct_24h = meta.charts.get_change_pct_24h().unwrap_or(0.0);
+        let change_pct_24h = s.change_pct_24h;
 
         // Broadcast update (using virtual reserves as sol/token reserves for pre-migration)
         meta.broadcast_update(SyncUpdate {
@@ -298,27 +412,34 @@ impl SyncComponent {
             pswap_pool: None,
         });
 
-        // Send to trades listener
-        _ = self.trades_tx.send(Some(Trade {
+        let trade = Trade {
             user: d.user,
             asset: s.address,
             symbol: s.symbol.clone(),
             icon_url: s.icon_url.clone(),
             volume_usd,
+            price_usd: s.last_price_usd,
             mkt_cap_usd: s.mkt_cap_usd,
             is_buy: d.is_buy,
-        }));
+        };
 
         // Update assets component
-        if old_mkt_cap_usd != s.mkt_cap_usd {
-            assets.on_sync_updated(index, old_mkt_cap_usd, s.mkt_cap_usd, s.create_timestamp);
-        }
+        assets.on_sync_updated(index, s);
+        assets.on_trade(index, true, timestamp, &trade);
+
+        // Send to trades listener
+        _ = self.trades_tx.send(Some(trade));
 
         // Persist this event
         true
     }
 
-    fn handle_pf_migration(&mut self, d: &PfMigrationEvent) -> bool {
+    fn handle_pf_migration(
+        &mut self,
+        timestamp: u64,
+        d: &PfMigrationEvent,
+        assets: &mut AssetsComponent,
+    ) -> bool {
         let Some(&index) = self.pump_mint_to_index.get(&d.mint) else {
             return false;
         };
@@ -329,9 +450,21 @@ impl SyncComponent {
             return false;
         }
 
+        if !s.is_migrated {
+            self.by_progress.remove(&(
+                progress_key(s.curve_progress_pct),
+                s.create_timestamp,
+                index,
+            ));
+        }
         s.is_migrated = true;
         s.pswap_pool = Some(d.pool);
+        s.curve_progress_pct = 100.0;
+        s.migrate_timestamp = Some(timestamp);
+        // Migrating needs a complete curve, though its last trade may have been missed
+        s.curve_complete_timestamp.get_or_insert(timestamp);
         self.pool_to_index.insert(d.pool, index);
+        assets.on_sync_updated(index, s);
         true
     }
 
@@ -365,31 +498,34 @@ impl SyncComponent {
             return false;
         }
 
-        // Store old market cap for asset update
-        let old_mkt_cap_usd = s.mkt_cap_usd;
-
         // In PA Buy, QUOTE -> BASE (ExactOut).
         // The migrated pools are always quote=WSOL, base=TOKEN.
-        let sol_usd = prices.sol();
-        let volume_usd = from_sol_amount(d.quote_amount_in) * prices.sol();
+        let sol_usd = prices.sol_at(timestamp);
+        let volume_usd = from_sol_amount(d.quote_amount_in) * sol_usd;
         // how many USD per output token?
         let price_usd =
             (from_sol_amount(d.quote_amount_in) * sol_usd) / from_token_amount(d.base_amount_out);
         if !price_usd.is_normal() || !volume_usd.is_normal() {
             return false;
         }
-        if let Err(e) = meta.charts.append(timestamp, price_usd, volume_usd) {
-            eprintln!("Failed to append chart data for pa buy: {:?}", e);
-        }
+        meta.append_trade(
+            timestamp,
+            d.quote_amount_in,
+            d.base_amount_out,
+            true,
+            d.user,
+        );
         s.last_price_usd = price_usd;
         s.mkt_cap_usd = price_usd * SYNC_MAX_SUPPLY_TOKENS;
-        s.change_pct_24h = meta.charts.get_change_pct_24h().unwrap_or(0.0);
+        s.change_pct_24h = meta.change_pct_24h(prices);
 
         // Update pool reserves (these come from the PaBuyEvent)
         s.sol_reserves = d.pool_quote_token_reserves;
         s.token_reserves = d.pool_base_token_reserves;
+        s.creator_fees += d.coin_creator_fee;
+        s.first_pool_trade_timestamp.get_or_insert(timestamp);
 
-        let change_pct_24h = meta.charts.get_change_pct_24h().unwrap_or(0.0);
+        let change_pct_24h = s.change_pct_24h;
 
         // Broadcast update
         meta.broadcast_update(SyncUpdate {
@@ -401,21 +537,23 @@ impl SyncComponent {
             pswap_pool: s.pswap_pool,
         });
 
-        // Send to trades listener
-        _ = self.trades_tx.send(Some(Trade {
+        let trade = Trade {
             user: d.user,
             asset: s.address,
             symbol: s.symbol.clone(),
             icon_url: s.icon_url.clone(),
             volume_usd,
+            price_usd: s.last_price_usd,
             mkt_cap_usd: s.mkt_cap_usd,
             is_buy: true,
-        }));
+        };
 
         // Update assets component
-        if old_mkt_cap_usd != s.mkt_cap_usd {
-            assets.on_sync_updated(index, old_mkt_cap_usd, s.mkt_cap_usd, s.create_timestamp);
-        }
+        assets.on_sync_updated(index, s);
+        assets.on_trade(index, true, timestamp, &trade);
+
+        // Send to trades listener
+        _ = self.trades_tx.send(Some(trade));
 
         // Persist this event
         true
@@ -440,6 +578,9 @@ impl SyncComponent {
         let is_new_sync = s.icon_url.is_empty() && s.description.is_empty();
         s.icon_url = d.icon_url.clone();
         s.description = d.description.clone();
+        s.website = d.website.clone();
+        s.twitter = d.twitter.clone();
+        s.telegram = d.telegram.clone();
 
         // Notify asset stream
         if is_new_sync {
@@ -470,9 +611,6 @@ impl SyncComponent {
             return false;
         }
 
-        // Store old market cap for asset update
-        let old_mkt_cap_usd = s.mkt_cap_usd;
-
         // In PA Sell, BASE -> QUOTE (ExactIn).
         // The migrated pools are always quote=WSOL, base=TOKEN.
         // Skip zero amounts
@@ -480,7 +618,7 @@ impl SyncComponent {
             return false;
         }
 
-        let sol_usd = prices.sol();
+        let sol_usd = prices.sol_at(timestamp);
         let volume_usd = from_sol_amount(d.quote_amount_out) * sol_usd;
         // how many USD per input token?
         let price_usd =
@@ -489,18 +627,24 @@ impl SyncComponent {
         if !price_usd.is_normal() || !volume_usd.is_normal() {
             return false;
         }
-        if let Err(e) = meta.charts.append(timestamp, price_usd, volume_usd) {
-            eprintln!("Failed to append chart data for pa sell: {:?}", e);
-        }
+        meta.append_trade(
+            timestamp,
+            d.quote_amount_out,
+            d.base_amount_in,
+            false,
+            d.user,
+        );
         s.last_price_usd = price_usd;
         s.mkt_cap_usd = price_usd * SYNC_MAX_SUPPLY_TOKENS;
-        s.change_pct_24h = meta.charts.get_change_pct_24h().unwrap_or(0.0);
+        s.change_pct_24h = meta.change_pct_24h(prices);
 
         // Update pool reserves (these come from the PaSellEvent)
         s.sol_reserves = d.pool_quote_token_reserves;
         s.token_reserves = d.pool_base_token_reserves;
+        s.creator_fees += d.coin_creator_fee;
+        s.first_pool_trade_timestamp.get_or_insert(timestamp);
 
-        let change_pct_24h = meta.charts.get_change_pct_24h().unwrap_or(0.0);
+        let change_pct_24h = s.change_pct_24h;
 
         // Broadcast update
         meta.broadcast_update(SyncUpdate {
@@ -512,26 +656,229 @@ impl SyncComponent {
             pswap_pool: s.pswap_pool,
         });
 
-        // Send to trades listener
-        _ = self.trades_tx.send(Some(Trade {
+        let trade = Trade {
             user: d.user,
             asset: s.address,
             symbol: s.symbol.clone(),
             icon_url: s.icon_url.clone(),
             volume_usd,
+            price_usd: s.last_price_usd,
             mkt_cap_usd: s.mkt_cap_usd,
             is_buy: false,
-        }));
+        };
 
         // Update assets component
-        if old_mkt_cap_usd != s.mkt_cap_usd {
-            assets.on_sync_updated(index, old_mkt_cap_usd, s.mkt_cap_usd, s.create_timestamp);
+        assets.on_sync_updated(index, s);
+        assets.on_trade(index, true, timestamp, &trade);
+
+        // Send to trades listener
+        _ = self.trades_tx.send(Some(trade));
+
+        true
+    }
+
+    fn handle_pa_deposit(&mut self, event: &Event, d: &PaDepositEvent) -> bool {
+        // The event has the pool's reserves and LP supply from before the deposit
+        let change = LiquidityChange {
+            pool: d.pool,
+            user: d.user,
+            is_deposit: true,
+            sol_amount: d.quote_amount_in,
+            token_amount: d.base_amount_in,
+            lp_amount: d.lp_token_amount_out,
+            sol_reserves: d
+                .pool_quote_token_reserves
+                .saturating_add(d.quote_amount_in),
+            token_reserves: d.pool_base_token_reserves.saturating_add(d.base_amount_in),
+            lp_supply: d.lp_mint_supply.saturating_add(d.lp_token_amount_out),
+        };
+        self.handle_pa_liquidity(event.timestamp, event.signature, change)
+    }
+
+    fn handle_pa_withdraw(&mut self, event: &Event, d: &PaWithdrawEvent) -> bool {
+        // The event has the pool's reserves and LP supply from before the withdrawal
+        let change = LiquidityChange {
+            pool: d.pool,
+            user: d.user,
+            is_deposit: false,
+            sol_amount: d.quote_amount_out,
+            token_amount: d.base_amount_out,
+            lp_amount: d.lp_token_amount_in,
+            sol_reserves: d
+                .pool_quote_token_reserves
+                .saturating_sub(d.quote_amount_out),
+            token_reserves: d.pool_base_token_reserves.saturating_sub(d.base_amount_out),
+            lp_supply: d.lp_mint_supply.saturating_sub(d.lp_token_amount_in),
+        };
+        self.handle_pa_liquidity(event.timestamp, event.signature, change)
+    }
+
+    fn handle_pa_liquidity(
+        &mut self,
+        timestamp: u64,
+        signature: Signature,
+        change: LiquidityChange,
+    ) -> bool {
+        let Some(&index) = self.pool_to_index.get(&change.pool) else {
+            return false;
+        };
+        let s = &mut self.syncs[index];
+
+        // Check if this sync is hidden
+        if HIDDEN_SYNCS.contains(&s.address) {
+            return false;
+        }
+
+        // PA is active only post migration
+        if !s.is_migrated {
+            return false;
+        }
+
+        // Skip zero amounts
+        if change.sol_amount == 0 && change.token_amount == 0 {
+            return false;
+        }
+
+        // Liquidity changes reserves but not price
+        s.sol_reserves = change.sol_reserves;
+        s.token_reserves = change.token_reserves;
+
+        let meta = &mut self.metas[index];
+        meta.lp_supply = change.lp_supply;
+        if change.is_deposit {
+            meta.deposits += 1;
+        } else {
+            meta.withdrawals += 1;
         }
+        if meta.liquidity.len() >= MAX_LIQUIDITY_EVENTS {
+            meta.liquidity.pop_front();
+        }
+        meta.liquidity.push_back(LiquidityEvent {
+            timestamp,
+            signature,
+            user: change.user,
+            is_deposit: change.is_deposit,
+            sol_amount: from_sol_amount(change.sol_amount),
+            token_amount: from_token_amount(change.token_amount),
+            lp_amount: change.lp_amount,
+            sol_reserves: from_sol_amount(change.sol_reserves),
+            token_reserves: from_token_amount(change.token_reserves),
+        });
+
+        // Broadcast update
+        meta.broadcast_update(SyncUpdate {
+            sol_reserves: s.sol_reserves,
+            token_reserves: s.token_reserves,
+            mkt_cap_usd: s.mkt_cap_usd,
+            change_pct_24h: s.change_pct_24h,
+            is_migrated: true,
+            pswap_pool: s.pswap_pool,
+        });
 
+        // Persist this event
         true
     }
 
     // --- Queries / helpers ---
+    /// Set the number of holders of a sync
+    pub fn set_holders(&mut self, sync: &Public, holders: u64, assets: &mut AssetsComponent) {
+        if let Some(&index) = self.address_to_index.get(sync) {
+            let sync = &mut self.syncs[index];
+            sync.holders = holders;
+            assets.on_sync_updated(index, sync);
+        }
+    }
+
+    pub fn last_price_for(&self, sync: &Public) -> Option<f32> {
+        self.address_to_index
+            .get(sync)
+            .map(|&index| self.syncs[index].last_price_usd)
+    }
+
+    /// Get the address of the sync for a Pump.fun mint, unless it's hidden
+    pub fn sync_address_for_mint(&self, mint: &Public) -> Option<Public> {
+        let &index = self.pump_mint_to_index.get(mint)?;
+        let address = self.syncs[index].address;
+        (!HIDDEN_SYNCS.contains(&address)).then_some(address)
+    }
+
+    /// List syncs, newest first, or with `near_migration`, the syncs yet to migrate
+    /// whose bonding curves are at least that percent complete, furthest along first
+    pub fn list_syncs(&self, near_migration: Option<f32>, count: usize, skip: usize) -> Vec<Sync> {
+        let indices: Box<dyn Iterator<Item = usize>> = match near_migration {
+            Some(min_pct) => Box::new(
+                self.by_progress
+                    .range((progress_key(min_pct), 0, 0)..)
+                    .rev()
+                    .map(|&(_, _, index)| index),
+            ),
+            None => Box::new(self.by_created.iter().rev().map(|&(_, index)| index)),
+        };
+        indices
+            .skip(skip)
+            .take(count)
+            .map(|index| self.syncs[index].clone())
+            .collect()
+    }
+
+    /// Get the liquidity and depth of a migrated sync's pool
+    pub fn get_liquidity(&self, sync: &Public, sol_usd: f32) -> Option<LiquidityInfo> {
+        let &index = self.address_to_index.get(sync)?;
+        let s = &self.syncs[index];
+        let pool = s.pswap_pool?;
+        let meta = &self.metas[index];
+        let sol_reserves = from_sol_amount(s.sol_reserves);
+        // For x * y = k, moving the price by a factor of m moves x by a factor of sqrt(m)
+        let depth = |sign: f32| -> Vec<DepthLevel> {
+            DEPTH_LEVELS_PCT
+                .iter()
+                .map(|&pct| {
+                    let factor = (1.0 + sign * pct / 100.0).sqrt();
+                    let sol_amount = sol_reserves * (factor - 1.0).abs();
+                    DepthLevel {
+                        price_change_pct: sign * pct,
+                        sol_amount,
+                        sol_amount_usd: sol_amount * sol_usd,
+                    }
+                })
+                .collect()
+        };
+        Some(LiquidityInfo {
+            pool,
+            sol_reserves,
+            token_reserves: from_token_amount(s.token_reserves),
+            // Both sides of the pool are worth the same
+            liquidity_usd: 2.0 * sol_reserves * sol_usd,
+            lp_supply: meta.lp_supply,
+            deposits: meta.deposits,
+            withdrawals: meta.withdrawals,
+            depth_up: depth(1.0),
+            depth_down: depth(-1.0),
+        })
+    }
+
+    /// Get a sync's pool liquidity deposits and withdrawals, newest first
+    pub fn query_liquidity_history(
+        &self,
+        sync: &Public,
+        count: usize,
+        skip: usize,
+    ) -> Vec<LiquidityEvent> {
+        self.address_to_index
+            .get(sync)
+            .map(|&index| {
+                self.metas[index]
+                    .liquidity
+                    .iter()
+                    .rev()
+                    .skip(skip)
+                    .take(count)
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     pub fn get_sync_by_address(&self, address: &Public) -> Option<Sync> {
         self.address_to_index
             .get(address)
@@ -541,13 +888,22 @@ impl SyncComponent {
     pub fn query_sync_chart(
         &self,
         sync: Public,
+        quote: ChartQuote,
         kind: ChartKind,
         count: usize,
         after_inclusive: u64,
+        prices: &PricesComponent,
     ) -> Vec<Candle> {
-        match self.address_to_index.get(&sync) {
-            Some(&index) => self.metas[index].charts.query(kind, count, after_inclusive),
-            None => Vec::new(),
+        let Some(&index) = self.address_to_index.get(&sync) else {
+            return Vec::new();
+        };
+        let charts = &self.metas[index].sol_charts;
+        match quote {
+            ChartQuote::Usd => {
+                charts.query_valued(kind, count, after_inclusive, |t| prices.sol_at(t))
+            }
+            ChartQuote::Sol => charts.query(kind, count, after_inclusive),
+            ChartQuote::Ivy => Vec::new(),
         }
     }
 }
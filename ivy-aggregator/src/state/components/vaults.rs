@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use crate::state::types::{ReceiptEntry, ReceiptList, VaultInfo, VaultReceipt};
+use crate::types::event::{Event, EventData};
+use crate::types::public::Public;
+use crate::types::receipt_log::ReceiptLog;
+
+use super::receipts::{list_receipts, ReceiptQuery};
+
+struct Vault {
+    deposits: ReceiptLog<VaultReceipt>,
+    withdrawals: ReceiptLog<VaultReceipt>,
+}
+
+fn entry(log: &ReceiptLog<VaultReceipt>, id: [u8; 32]) -> Option<ReceiptEntry<VaultReceipt>> {
+    log.get(&id).map(|&info| ReceiptEntry {
+        id: hex::encode(id),
+        info,
+    })
+}
+
+/// Tracks the deposit and withdrawal receipts of each vault.
+pub struct VaultsComponent {
+    vaults: HashMap<Public, Vault>,
+}
+
+impl VaultsComponent {
+    pub fn new() -> Self {
+        Self {
+            vaults: HashMap::new(),
+        }
+    }
+
+    pub fn on_event(&mut self, event: &Event) -> bool {
+        let receipt = VaultReceipt {
+            signature: event.signature,
+            timestamp: event.timestamp,
+        };
+        match &event.data {
+            EventData::VaultDeposit(data) => {
+                self.vault_mut(data.vault)
+                    .deposits
+                    .insert(data.id, event.timestamp, receipt);
+            }
+            EventData::VaultWithdraw(data) => {
+                self.vault_mut(data.vault)
+                    .withdrawals
+                    .insert(data.id, event.timestamp, receipt);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn vault_mut(&mut self, vault: Public) -> &mut Vault {
+        self.vaults.entry(vault).or_insert_with(|| Vault {
+            deposits: ReceiptLog::new(),
+            withdrawals: ReceiptLog::new(),
+        })
+    }
+
+    /// Get a summary of a vault's activity, if it has any
+    pub fn get_vault_info(&self, vault: Public) -> Option<VaultInfo> {
+        let v = self.vaults.get(&vault)?;
+        Some(VaultInfo {
+            vault,
+            deposit_count: v.deposits.len(),
+            withdrawal_count: v.withdrawals.len(),
+            last_deposit_timestamp: v.deposits.last_timestamp(),
+            last_withdrawal_timestamp: v.withdrawals.last_timestamp(),
+        })
+    }
+
+    pub fn get_deposit_info(
+        &self,
+        vault: Public,
+        id: [u8; 32],
+    ) -> Option<ReceiptEntry<VaultReceipt>> {
+        entry(&self.vaults.get(&vault)?.deposits, id)
+    }
+
+    pub fn get_withdraw_info(
+        &self,
+        vault: Public,
+        id: [u8; 32],
+    ) -> Option<ReceiptEntry<VaultReceipt>> {
+        entry(&self.vaults.get(&vault)?.withdrawals, id)
+    }
+
+    /// List a vault's deposits
+    pub fn get_deposits(&self, vault: Public, query: ReceiptQuery) -> ReceiptList<VaultReceipt> {
+        list_receipts(self.vaults.get(&vault).map(|v| &v.deposits), query, |_| {
+            true
+        })
+    }
+
+    /// List a vault's withdrawals
+    pub fn get_withdrawals(&self, vault: Public, query: ReceiptQuery) -> ReceiptList<VaultReceipt> {
+        list_receipts(
+            self.vaults.get(&vault).map(|v| &v.withdrawals),
+            query,
+            |_| true,
+        )
+    }
+}
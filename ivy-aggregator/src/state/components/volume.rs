@@ -1,28 +1,79 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Deserialize;
 
 use crate::types::leaderboard::Leaderboard;
 use crate::types::public::Public;
 use crate::util::{from_ivy_amount, from_sol_amount, mil_to_usd, usd_to_mil};
 use crate::volume::Volume;
 
-use crate::state::types::VlbEntry;
+use crate::state::types::{BoardRank, VlbEntry, VolumeRank};
 use crate::types::event::{Event, EventData, GameSwapEvent, SyncSwapEvent};
 
 use super::prices::PricesComponent;
 use super::world::WorldComponent;
 
+const WINDOW_COUNT: usize = 4;
+
+/// A time window to measure volume over. Windows are hourly,
+/// so a window includes the current hour and the full hours before it.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum VolumeWindow {
+    #[serde(rename = "24h")]
+    Day,
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    Month,
+    #[default]
+    #[serde(rename = "all")]
+    All,
+}
+
+impl VolumeWindow {
+    /// The windows that expire, and their lengths in hours
+    const EXPIRING: [(VolumeWindow, u64); 3] = [
+        (VolumeWindow::Day, 24),
+        (VolumeWindow::Week, 7 * 24),
+        (VolumeWindow::Month, 30 * 24),
+    ];
+
+    /// The length of the window in hours, or `None` if it never expires
+    pub fn hours(self) -> Option<u64> {
+        Self::EXPIRING
+            .iter()
+            .find(|&&(window, _)| window == self)
+            .map(|&(_, length)| length)
+    }
+}
+
+/// A volume leaderboard for each window
+type Boards = [Leaderboard<Public, u64>; WINDOW_COUNT];
+
+fn new_boards() -> Boards {
+    std::array::from_fn(|_| Leaderboard::new())
+}
+
 pub struct VolumeComponent {
-    pub volume_map: HashMap<Public, u64>,
-    pub address_to_volume_lb: HashMap<Public, Leaderboard<Public, u64>>,
     pub volume_24h: Volume,
+    // Protocol-wide volume per user
+    global: Boards,
+    // Volume per user, per asset
+    assets: HashMap<Public, Boards>,
+    // Hour -> volume per (asset, user) in that hour, for the longest window
+    hourly: BTreeMap<u64, HashMap<(Public, Public), u64>>,
+    // The first hour counted by each expiring window
+    window_starts: [u64; VolumeWindow::EXPIRING.len()],
 }
 
 impl VolumeComponent {
     pub fn new(minutes: usize) -> Self {
         Self {
-            volume_map: HashMap::new(),
-            address_to_volume_lb: HashMap::new(),
             volume_24h: Volume::new(minutes),
+            global: new_boards(),
+            assets: HashMap::new(),
+            hourly: BTreeMap::new(),
+            window_starts: [0; VolumeWindow::EXPIRING.len()],
         }
     }
 
@@ -46,63 +97,185 @@ impl VolumeComponent {
         let usdc_value_mil = usd_to_mil(usdc_value);
 
         self.volume_24h.append(usdc_value_mil, timestamp);
-
-        let user = swap.user;
-        if user != Public::zero() {
-            self.address_to_volume_lb
-                .entry(swap.game)
-                .or_insert_with(Leaderboard::new)
-                .increment(user, usdc_value_mil);
-            *self.volume_map.entry(user).or_default() += usdc_value_mil;
-        }
+        self.add_volume(timestamp, swap.game, swap.user, usdc_value_mil);
     }
 
     fn handle_sync_swap(&mut self, timestamp: u64, swap: &SyncSwapEvent, prices: &PricesComponent) {
-        let usdc_value = from_sol_amount(swap.sol_amount) * prices.sol();
+        let usdc_value = from_sol_amount(swap.sol_amount) * prices.sol_at(timestamp);
         let usdc_value_mil = usd_to_mil(usdc_value);
 
         self.volume_24h.append(usdc_value_mil, timestamp);
+        self.add_volume(timestamp, swap.sync, swap.user, usdc_value_mil);
+    }
+
+    fn add_volume(&mut self, timestamp: u64, asset: Public, user: Public, volume_mil: u64) {
+        let hour = timestamp / 3600;
+        self.expire(hour);
+        if user == Public::zero() {
+            return;
+        }
+
+        let boards = self.assets.entry(asset).or_insert_with(new_boards);
+        for (i, &start) in self.window_starts.iter().enumerate() {
+            // Out of order trades may be too old for a window
+            if hour >= start {
+                let window = VolumeWindow::EXPIRING[i].0 as usize;
+                boards[window].increment(user, volume_mil);
+                self.global[window].increment(user, volume_mil);
+            }
+        }
+        boards[VolumeWindow::All as usize].increment(user, volume_mil);
+        self.global[VolumeWindow::All as usize].increment(user, volume_mil);
+
+        if self.window_starts.iter().any(|&start| hour >= start) {
+            *self
+                .hourly
+                .entry(hour)
+                .or_default()
+                .entry((asset, user))
+                .or_default() += volume_mil;
+        }
+    }
+
+    /// Whether a window would move if it ended at `timestamp`
+    pub fn needs_expiry(&self, timestamp: u64) -> bool {
+        let hour = timestamp / 3600;
+        VolumeWindow::EXPIRING
+            .iter()
+            .zip(self.window_starts)
+            .any(|(&(_, length), start)| (hour + 1).saturating_sub(length) > start)
+    }
+
+    /// Move each expiring window to end at `timestamp`. Trades move them as they
+    /// come in, and queries with the current time, so that users who stopped
+    /// trading leave the windows even when no one trades.
+    pub fn expire_at(&mut self, timestamp: u64) {
+        self.expire(timestamp / 3600);
+    }
+
+    /// Move each expiring window to end at `hour`, removing the volume of hours it leaves
+    fn expire(&mut self, hour: u64) {
+        for (i, &(window, length)) in VolumeWindow::EXPIRING.iter().enumerate() {
+            let start = (hour + 1).saturating_sub(length);
+            if start <= self.window_starts[i] {
+                continue;
+            }
+            for (_, volumes) in self.hourly.range(self.window_starts[i]..start) {
+                for (&(asset, user), &volume_mil) in volumes {
+                    if let Some(boards) = self.assets.get_mut(&asset) {
+                        boards[window as usize].decrement(&user, volume_mil);
+                    }
+                    self.global[window as usize].decrement(&user, volume_mil);
+                }
+            }
+            self.window_starts[i] = start;
+        }
 
-        let user = swap.user;
-        if user != Public::zero() {
-            self.address_to_volume_lb
-                .entry(swap.sync)
-                .or_insert_with(Leaderboard::new)
-                .increment(user, usdc_value_mil);
-            *self.volume_map.entry(user).or_default() += usdc_value_mil;
+        // Drop the hours that no window counts anymore
+        let oldest = self.window_starts.iter().copied().min().unwrap_or_default();
+        while let Some(entry) = self.hourly.first_entry() {
+            if *entry.key() >= oldest {
+                break;
+            }
+            entry.remove();
         }
     }
 
-    pub fn query_volume_lb(&self, game: Public, count: usize, skip: usize) -> Vec<VlbEntry> {
-        match self.address_to_volume_lb.get(&game) {
+    pub fn query_volume_lb(
+        &self,
+        game: Public,
+        window: VolumeWindow,
+        count: usize,
+        skip: usize,
+    ) -> Vec<VlbEntry> {
+        match self.assets.get(&game) {
             None => Vec::new(),
-            Some(lb) => lb
-                .range(skip, count)
-                .map(|(&user, &mil)| VlbEntry {
-                    user,
-                    volume: mil_to_usd(mil),
-                })
-                .collect(),
+            Some(boards) => Self::entries(&boards[window as usize], count, skip),
         }
     }
 
-    pub fn get_volume(&self, user: Public) -> f32 {
-        self.volume_map
+    /// Query the protocol-wide volume leaderboard
+    pub fn query_global_volume_lb(
+        &self,
+        window: VolumeWindow,
+        count: usize,
+        skip: usize,
+    ) -> Vec<VlbEntry> {
+        Self::entries(&self.global[window as usize], count, skip)
+    }
+
+    fn entries(lb: &Leaderboard<Public, u64>, count: usize, skip: usize) -> Vec<VlbEntry> {
+        lb.range(skip, count)
+            .map(|(&user, &mil)| VlbEntry {
+                user,
+                volume: mil_to_usd(mil),
+            })
+            .collect()
+    }
+
+    /// Get a user's rank on an asset's volume leaderboard,
+    /// with `neighbors` entries above and below them
+    pub fn get_rank(
+        &self,
+        game: Public,
+        user: Public,
+        window: VolumeWindow,
+        neighbors: usize,
+    ) -> VolumeRank {
+        match self.assets.get(&game) {
+            Some(boards) => Self::rank(&boards[window as usize], user, neighbors),
+            None => VolumeRank {
+                rank: BoardRank::unranked(user, 0),
+                volume: 0.0,
+            },
+        }
+    }
+
+    /// Get a user's rank on the protocol-wide volume leaderboard,
+    /// with `neighbors` entries above and below them
+    pub fn get_global_rank(
+        &self,
+        user: Public,
+        window: VolumeWindow,
+        neighbors: usize,
+    ) -> VolumeRank {
+        Self::rank(&self.global[window as usize], user, neighbors)
+    }
+
+    fn rank(lb: &Leaderboard<Public, u64>, user: Public, neighbors: usize) -> VolumeRank {
+        let volume = lb.get(&user).map(|&mil| mil_to_usd(mil)).unwrap_or(0.0);
+        let Some((rank, start, entries)) = lb.neighbors(&user, neighbors) else {
+            return VolumeRank {
+                rank: BoardRank::unranked(user, lb.len()),
+                volume,
+            };
+        };
+        VolumeRank {
+            rank: BoardRank {
+                user,
+                rank: Some(rank),
+                total: lb.len(),
+                neighbors_start: start,
+                neighbors: entries
+                    .map(|(&user, &mil)| VlbEntry {
+                        user,
+                        volume: mil_to_usd(mil),
+                    })
+                    .collect(),
+            },
+            volume,
+        }
+    }
+
+    pub fn get_volume(&self, user: Public, window: VolumeWindow) -> f32 {
+        self.global[window as usize]
             .get(&user)
             .map(|&x| mil_to_usd(x))
             .unwrap_or_default()
     }
 
-    pub fn get_volume_multiple(&self, users: &[Public]) -> Vec<f32> {
-        users
-            .iter()
-            .map(|u| {
-                self.volume_map
-                    .get(u)
-                    .map(|&x| mil_to_usd(x))
-                    .unwrap_or_default()
-            })
-            .collect()
+    pub fn get_volume_multiple(&self, users: &[Public], window: VolumeWindow) -> Vec<f32> {
+        users.iter().map(|&u| self.get_volume(u, window)).collect()
     }
 
     pub fn volume_24h_usd(&self) -> f32 {
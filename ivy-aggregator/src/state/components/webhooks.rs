@@ -0,0 +1,214 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::applier::Cursor;
+use crate::state::types::{WebhookDelivery, WebhookInfo};
+use crate::types::event::{Event, EventData};
+use crate::types::public::Public;
+use crate::types::signature::Signature;
+use crate::util::unix_timestamp;
+
+// Deliveries remembered per game for the delivery log
+const MAX_LOGGED_DELIVERIES: usize = 256;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WebhookRegistration {
+    pub game: Public,
+    pub url: String,
+    /// Key used to sign payloads with HMAC-SHA256
+    pub secret: String,
+    pub created_at: u64,
+}
+
+/// What a webhook delivery is for, and whose delivery log it goes in
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookSource {
+    /// A receipt for a game's webhook
+    Game(Public),
+    /// An alert fired by the rule with this ID
+    Alert(u64),
+}
+
+/// Read a `WebhookSource`, or the game of a job persisted
+/// before alerts were delivered, when jobs only had a `game`
+fn deserialize_source<'de, D>(deserializer: D) -> Result<WebhookSource, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SourceOrGame {
+        Source(WebhookSource),
+        Game(Public),
+    }
+
+    Ok(match SourceOrGame::deserialize(deserializer)? {
+        SourceOrGame::Source(source) => source,
+        SourceOrGame::Game(game) => WebhookSource::Game(game),
+    })
+}
+
+/// A payload to POST to a webhook
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WebhookJob {
+    #[serde(alias = "game", deserialize_with = "deserialize_source")]
+    pub source: WebhookSource,
+    pub delivery_id: String,
+    pub url: String,
+    pub secret: String,
+    pub body: String,
+}
+
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    delivery_id: &'a str,
+    kind: &'static str,
+    game: Public,
+    /// The receipt ID, as hex
+    id: String,
+    signature: Signature,
+    timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    withdraw_authority: Option<Public>,
+}
+
+/// Keeps track of each game's webhook, and queues a delivery
+/// for every receipt applied after initialization.
+pub struct WebhooksComponent {
+    registrations: HashMap<Public, WebhookRegistration>,
+    store: Cursor<Vec<WebhookRegistration>>,
+    tx: mpsc::Sender<WebhookJob>,
+    // Replayed events have already been delivered
+    is_initialized: bool,
+    deliveries: HashMap<Public, VecDeque<WebhookDelivery>>,
+}
+
+impl WebhooksComponent {
+    pub fn new(mut store: Cursor<Vec<WebhookRegistration>>, tx: mpsc::Sender<WebhookJob>) -> Self {
+        let registrations = match store.read() {
+            Ok(registrations) => registrations.unwrap_or_default(),
+            Err(e) => {
+                eprintln!("warning: Can't read webhook registrations: {}", e);
+                Vec::new()
+            }
+        };
+        Self {
+            registrations: registrations.into_iter().map(|r| (r.game, r)).collect(),
+            store,
+            tx,
+            is_initialized: false,
+            deliveries: HashMap::new(),
+        }
+    }
+
+    pub fn on_event(&mut self, event: &Event) -> bool {
+        let (kind, game, id, withdraw_authority) = match &event.data {
+            EventData::Initialize(_) => {
+                self.is_initialized = true;
+                return false;
+            }
+            EventData::GameBurn(e) => ("burn", e.game, e.id, None),
+            EventData::GameDeposit(e) => ("deposit", e.game, e.id, None),
+            EventData::GameWithdraw(e) => ("withdraw", e.game, e.id, Some(e.withdraw_authority)),
+            _ => return false,
+        };
+        if !self.is_initialized {
+            return false;
+        }
+        let Some(registration) = self.registrations.get(&game) else {
+            return false;
+        };
+
+        let delivery_id = format!("{}:{}", kind, hex::encode(id));
+        let payload = WebhookPayload {
+            delivery_id: &delivery_id,
+            kind,
+            game,
+            id: hex::encode(id),
+            signature: event.signature,
+            timestamp: event.timestamp,
+            withdraw_authority,
+        };
+        let body = serde_json::to_string(&payload).unwrap();
+        _ = self.tx.send(WebhookJob {
+            source: WebhookSource::Game(game),
+            delivery_id,
+            url: registration.url.clone(),
+            secret: registration.secret.clone(),
+            body,
+        });
+
+        // The receipts component persists these events
+        false
+    }
+
+    fn persist(&mut self) {
+        let registrations: Vec<_> = self.registrations.values().cloned().collect();
+        if let Err(e) = self.store.write(&registrations) {
+            eprintln!("warning: Can't write webhook registrations: {}", e);
+        }
+    }
+
+    /// Register `url` as the webhook for `game`, replacing any existing one
+    pub fn register(&mut self, game: Public, url: String, secret: String) -> WebhookInfo {
+        let registration = WebhookRegistration {
+            game,
+            url,
+            secret,
+            created_at: unix_timestamp(),
+        };
+        let info = WebhookInfo {
+            game,
+            url: registration.url.clone(),
+            created_at: registration.created_at,
+        };
+        self.registrations.insert(game, registration);
+        self.persist();
+        info
+    }
+
+    /// Remove the webhook for `game`, returning whether there was one
+    pub fn unregister(&mut self, game: &Public) -> bool {
+        let removed = self.registrations.remove(game).is_some();
+        if removed {
+            self.persist();
+        }
+        removed
+    }
+
+    pub fn get_webhook(&self, game: &Public) -> Option<WebhookInfo> {
+        self.registrations.get(game).map(|r| WebhookInfo {
+            game: r.game,
+            url: r.url.clone(),
+            created_at: r.created_at,
+        })
+    }
+
+    /// Record a delivery attempt in the delivery log
+    pub fn on_delivery(&mut self, game: Public, delivery: WebhookDelivery) {
+        let log = self.deliveries.entry(game).or_default();
+        if log.len() >= MAX_LOGGED_DELIVERIES {
+            log.pop_front();
+        }
+        log.push_back(delivery);
+    }
+
+    /// Get the delivery log for `game`, newest first
+    pub fn get_deliveries(
+        &self,
+        game: &Public,
+        count: usize,
+        skip: usize,
+    ) -> (usize, Vec<WebhookDelivery>) {
+        match self.deliveries.get(game) {
+            Some(log) => (
+                log.len(),
+                log.iter().rev().skip(skip).take(count).cloned().collect(),
+            ),
+            None => (0, Vec::new()),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::sqrt_curve::SqrtCurve;
+use crate::state::types::{IvyQuote, VestingEntry};
 use crate::types::chart::Candle;
 use crate::types::charts::{ChartKind, Charts};
 use crate::types::event::{
@@ -12,6 +13,10 @@ use tokio::sync::broadcast;
 // 512 updates before receiver is deemed lagged :)
 const CHANNEL_BUFFER_SIZE: usize = 512;
 
+// How far the USDC reserve may fall short of the curve integral, in USDC,
+// before the curve is deemed insolvent (one raw unit, for float error)
+const SOLVENCY_TOLERANCE: f64 = 0.000_001;
+
 #[derive(Debug, Clone, Copy, Serialize)]
 pub struct WorldBalanceUpdate {
     pub ivy_sold: u64,
@@ -36,6 +41,7 @@ pub struct WorldComponent {
     pub data: WorldData,
     pub ivy_charts: Charts,
     pub ivy_price: f32,
+    vesting: Vec<VestingEntry>,
     // right now, we subscribe to every single world trade,
     // if in the future this becomes overwhelming, we can modify
     // it such that it only sends the updated balance if it's, say,
@@ -61,6 +67,7 @@ impl WorldComponent {
             },
             ivy_charts: Charts::new(max_candles),
             ivy_price: 0.0,
+            vesting: Vec::new(),
             update_tx: None,
         }
     }
@@ -108,7 +115,7 @@ impl WorldComponent {
                 true
             }
             EventData::WorldVesting(vest) => {
-                self.process_world_vesting(vest);
+                self.process_world_vesting(event.timestamp, vest);
                 true
             }
             _ => false,
@@ -139,7 +146,7 @@ impl WorldComponent {
 
         let ivy_price = SqrtCurve::current_price(
             from_ivy_amount(swap_data.ivy_sold) as f64,
-            (self.data.curve_input_scale_num as f64) / (self.data.curve_input_scale_den as f64),
+            self.input_scale(),
         ) as f32;
 
         if !ivy_price.is_normal() {
@@ -156,10 +163,13 @@ impl WorldComponent {
         };
         self.broadcast_update(update);
 
-        if let Err(e) = self
-            .ivy_charts
-            .append(timestamp, self.ivy_price, usdc_amount)
-        {
+        if let Err(e) = self.ivy_charts.append(
+            timestamp,
+            self.ivy_price,
+            usdc_amount,
+            swap_data.is_buy,
+            Some(swap_data.user),
+        ) {
             eprintln!(
                 "warning: Could not append to IVY chart (sig: {}, time: {}): {}",
                 signature, timestamp, e
@@ -167,8 +177,84 @@ impl WorldComponent {
         }
     }
 
-    fn process_world_vesting(&mut self, vesting_data: &WorldVestingEvent) {
+    fn process_world_vesting(&mut self, timestamp: u64, vesting_data: &WorldVestingEvent) {
         self.data.ivy_vested = vesting_data.ivy_vested;
+        self.vesting.push(VestingEntry {
+            timestamp,
+            amount: from_ivy_amount(vesting_data.ivy_amount),
+            total: from_ivy_amount(vesting_data.ivy_vested),
+        });
+    }
+
+    fn input_scale(&self) -> f64 {
+        (self.data.curve_input_scale_num as f64) / (self.data.curve_input_scale_den as f64)
+    }
+
+    /// IVY sold by the curve, in whole tokens, as f64 for curve math
+    fn supply(&self) -> f64 {
+        self.data.ivy_sold as f64 / 1_000_000_000.0
+    }
+
+    /// Releases of IVY from vesting, oldest first
+    pub fn vesting_history(&self) -> &[VestingEntry] {
+        &self.vesting
+    }
+
+    /// The USDC the curve should hold for the IVY it's sold,
+    /// and whether its reserve covers that
+    pub fn reserve_check(&self) -> (f64, bool) {
+        let expected = SqrtCurve::integrate(0.0, self.supply(), self.input_scale());
+        let reserve = self.data.usdc_balance as f64 / 1_000_000.0;
+        (expected, reserve + SOLVENCY_TOLERANCE >= expected)
+    }
+
+    /// Quote buying IVY from the curve with `usdc` USDC
+    pub fn quote_buy(&self, usdc: f64) -> Result<IvyQuote, &'static str> {
+        if !usdc.is_finite() || usdc <= 0.0 {
+            return Err("Buy amount must be positive");
+        }
+        let scale = self.input_scale();
+        let supply = self.supply();
+        let new_supply = SqrtCurve::right_bound(supply, usdc, scale);
+        if new_supply > self.data.ivy_curve_max as f64 / 1_000_000_000.0 {
+            return Err("Buy exceeds the curve's maximum supply");
+        }
+        Ok(self.quote(
+            usdc,
+            new_supply - supply,
+            new_supply,
+            usdc / (new_supply - supply),
+        ))
+    }
+
+    /// Quote selling `ivy` IVY to the curve
+    pub fn quote_sell(&self, ivy: f64) -> Result<IvyQuote, &'static str> {
+        if !ivy.is_finite() || ivy <= 0.0 {
+            return Err("Sell amount must be positive");
+        }
+        let supply = self.supply();
+        if ivy > supply {
+            return Err("Sell exceeds the IVY sold by the curve");
+        }
+        let usdc = SqrtCurve::integrate(supply - ivy, supply, self.input_scale());
+        Ok(self.quote(ivy, usdc, supply - ivy, usdc / ivy))
+    }
+
+    fn quote(&self, amount_in: f64, amount_out: f64, new_supply: f64, avg_price: f64) -> IvyQuote {
+        let price = SqrtCurve::current_price(self.supply(), self.input_scale());
+        let price_after = SqrtCurve::current_price(new_supply, self.input_scale());
+        let price_impact_pct = if price > 0.0 {
+            (price_after - price) / price * 100.0
+        } else {
+            0.0
+        };
+        IvyQuote {
+            amount_in: amount_in as f32,
+            amount_out: amount_out as f32,
+            avg_price: avg_price as f32,
+            price_after: price_after as f32,
+            price_impact_pct: price_impact_pct as f32,
+        }
     }
 
     pub fn price(&self) -> f32 {
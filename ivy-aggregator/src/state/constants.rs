@@ -1,9 +1,6 @@
 use crate::types::public::Public;
 
 pub const MAX_CANDLES: usize = 4096;
-pub const MAX_HOT_ASSETS: usize = 1024;
-pub const MAX_HOT_ASSET_AGE: u64 = 86_400;
-pub const MIN_HOT_ASSET_COUNT: usize = 50;
 pub const MAX_FEATURED_ASSETS: usize = 5;
 
 pub const HIDDEN_GAMES: [Public; 25] = [
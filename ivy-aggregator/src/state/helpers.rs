@@ -1,16 +1,12 @@
-/// Normalize a string for searching: trim, lowercase, ASCII only, no spaces.
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Normalize a string for searching: compatibility-decompose (NFKD),
+/// strip accents, lowercase, and drop whitespace and control characters.
 pub fn normalize_string(s: &str) -> String {
-    s.trim()
-        .chars()
-        .filter(|c| c.is_ascii())
-        .filter(|c| !c.is_whitespace())
-        .map(|c| c.to_ascii_lowercase())
+    s.nfkd()
+        .filter(|&c| !is_combining_mark(c))
+        .flat_map(|c| c.to_lowercase())
+        .filter(|c| !c.is_whitespace() && !c.is_control())
         .collect()
 }
-
-/// Calculate the hot score of a game based on market cap and age.
-pub fn calculate_hot_score(mkt_cap_usd: f32, age_seconds: u64) -> f32 {
-    let age_in_hours = (age_seconds as f32) / 3600.0;
-    // Score formula: market_cap / (age_in_hours + 2)^1.8
-    mkt_cap_usd / (age_in_hours + 2.0).powf(1.8)
-}
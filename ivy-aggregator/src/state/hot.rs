@@ -0,0 +1,260 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::state::types::HotBreakdown;
+use crate::types::public::Public;
+use crate::util::order_key;
+
+// Rebase `t_ref` before scale factors get large enough to lose precision
+const MAX_EXPONENT: f64 = 64.0;
+// Traders remembered per asset when counting unique traders
+const RECENT_TRADER_COUNT: usize = 32;
+
+#[derive(Clone, Copy)]
+enum Signal {
+    /// Trade volume, in USD
+    Volume,
+    /// Number of trades
+    Trades,
+    /// Number of trades by traders that haven't traded the asset recently
+    Traders,
+    /// Change in market cap, as a log return in percent
+    Momentum,
+    /// Number of comments
+    Comments,
+    /// 1 when the asset is created
+    Creation,
+}
+
+const SIGNAL_COUNT: usize = 6;
+
+/// Weights and decay of the hot ranking
+#[derive(Clone, Copy, Debug)]
+pub struct HotConfig {
+    /// Time for a signal to decay to half its value, in seconds
+    pub half_life: u64,
+    pub volume_weight: f64,
+    pub trades_weight: f64,
+    pub traders_weight: f64,
+    pub momentum_weight: f64,
+    pub comments_weight: f64,
+    pub creation_weight: f64,
+}
+
+impl Default for HotConfig {
+    fn default() -> Self {
+        Self {
+            half_life: 6 * 60 * 60,
+            volume_weight: 0.01,
+            trades_weight: 1.0,
+            traders_weight: 3.0,
+            momentum_weight: 0.5,
+            comments_weight: 2.0,
+            creation_weight: 20.0,
+        }
+    }
+}
+
+impl HotConfig {
+    /// Read the config from `HOT_*` environment variables,
+    /// using the default for each one that's missing or invalid
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            match std::env::var(name) {
+                Ok(v) => v.parse().unwrap_or_else(|_| {
+                    eprintln!("warning: Can't parse {}, using default", name);
+                    default
+                }),
+                Err(_) => default,
+            }
+        }
+        let default = Self::default();
+        let config = Self {
+            half_life: var("HOT_HALF_LIFE", default.half_life),
+            volume_weight: var("HOT_VOLUME_WEIGHT", default.volume_weight),
+            trades_weight: var("HOT_TRADES_WEIGHT", default.trades_weight),
+            traders_weight: var("HOT_TRADERS_WEIGHT", default.traders_weight),
+            momentum_weight: var("HOT_MOMENTUM_WEIGHT", default.momentum_weight),
+            comments_weight: var("HOT_COMMENTS_WEIGHT", default.comments_weight),
+            creation_weight: var("HOT_CREATION_WEIGHT", default.creation_weight),
+        };
+        if config.half_life == 0 {
+            eprintln!("warning: HOT_HALF_LIFE must be positive, using default");
+            return Self {
+                half_life: default.half_life,
+                ..config
+            };
+        }
+        config
+    }
+
+    fn weights(&self) -> [f64; SIGNAL_COUNT] {
+        [
+            self.volume_weight,
+            self.trades_weight,
+            self.traders_weight,
+            self.momentum_weight,
+            self.comments_weight,
+            self.creation_weight,
+        ]
+    }
+
+    /// Time constant of the decay, in seconds
+    fn tau(&self) -> f64 {
+        self.half_life as f64 / std::f64::consts::LN_2
+    }
+}
+
+struct HotEntry {
+    // Signals, scaled by `e^((t - t_ref) / tau)`
+    signals: [f64; SIGNAL_COUNT],
+    // Weighted sum of `signals`
+    score: f64,
+    recent_traders: VecDeque<Public>,
+    last_mkt_cap_usd: f32,
+}
+
+/// Ranks assets by a weighted sum of exponentially decaying signals
+/// (volume, trades, unique traders, momentum, comments, creation).
+///
+/// Rather than decaying every score as time passes, we store each signal
+/// scaled up by `e^((t - t_ref) / tau)` at the time `t` it was added (forward decay).
+/// All scores then decay at the same rate, so their order never changes with time,
+/// and an asset's score only needs updating when it has an event.
+pub struct HotRanking<K> {
+    config: HotConfig,
+    weights: [f64; SIGNAL_COUNT],
+    tau: f64,
+    t_ref: u64,
+    entries: HashMap<K, HotEntry>,
+    // (score, key), ascending
+    ranked: BTreeSet<(u64, K)>,
+}
+
+impl<K: Copy + Hash + Ord> HotRanking<K> {
+    pub fn new(config: HotConfig) -> Self {
+        Self {
+            config,
+            weights: config.weights(),
+            tau: config.tau(),
+            t_ref: 0,
+            entries: HashMap::new(),
+            ranked: BTreeSet::new(),
+        }
+    }
+
+    pub fn on_created(&mut self, key: K, timestamp: u64, mkt_cap_usd: f32) {
+        self.entries.entry(key).or_insert_with(|| HotEntry {
+            signals: [0.0; SIGNAL_COUNT],
+            score: 0.0,
+            recent_traders: VecDeque::new(),
+            last_mkt_cap_usd: mkt_cap_usd,
+        });
+        self.ranked.insert((order_key(0.0), key));
+        self.add(key, timestamp, &[(Signal::Creation, 1.0)]);
+    }
+
+    pub fn on_trade(
+        &mut self,
+        key: K,
+        timestamp: u64,
+        user: Public,
+        volume_usd: f32,
+        mkt_cap_usd: f32,
+    ) {
+        let Some(entry) = self.entries.get_mut(&key) else {
+            return;
+        };
+
+        let mut signals = vec![(Signal::Volume, volume_usd as f64), (Signal::Trades, 1.0)];
+        if user != Public::zero() && !entry.recent_traders.contains(&user) {
+            if entry.recent_traders.len() >= RECENT_TRADER_COUNT {
+                entry.recent_traders.pop_front();
+            }
+            entry.recent_traders.push_back(user);
+            signals.push((Signal::Traders, 1.0));
+        }
+        if entry.last_mkt_cap_usd > 0.0 && mkt_cap_usd > 0.0 {
+            let log_return = (mkt_cap_usd as f64 / entry.last_mkt_cap_usd as f64).ln();
+            signals.push((Signal::Momentum, log_return * 100.0));
+        }
+        entry.last_mkt_cap_usd = mkt_cap_usd;
+
+        self.add(key, timestamp, &signals);
+    }
+
+    pub fn on_comment(&mut self, key: K, timestamp: u64) {
+        self.add(key, timestamp, &[(Signal::Comments, 1.0)]);
+    }
+
+    fn add(&mut self, key: K, timestamp: u64, signals: &[(Signal, f64)]) {
+        if !self.entries.contains_key(&key) {
+            return;
+        }
+        if (timestamp.saturating_sub(self.t_ref) as f64) / self.tau > MAX_EXPONENT {
+            self.rebase(timestamp);
+        }
+        let scale = ((timestamp as f64 - self.t_ref as f64) / self.tau).exp();
+
+        let entry = self.entries.get_mut(&key).unwrap();
+        self.ranked.remove(&(order_key(entry.score), key));
+        for &(signal, amount) in signals {
+            if amount.is_finite() {
+                entry.signals[signal as usize] += amount * scale;
+            }
+        }
+        entry.score = entry
+            .signals
+            .iter()
+            .zip(self.weights)
+            .map(|(s, w)| s * w)
+            .sum();
+        self.ranked.insert((order_key(entry.score), key));
+    }
+
+    /// Move `t_ref` to `timestamp`, scaling all stored signals down accordingly
+    fn rebase(&mut self, timestamp: u64) {
+        let factor = (-((timestamp - self.t_ref) as f64) / self.tau).exp();
+        self.t_ref = timestamp;
+        self.ranked.clear();
+        for (&key, entry) in self.entries.iter_mut() {
+            for signal in entry.signals.iter_mut() {
+                *signal *= factor;
+            }
+            entry.score *= factor;
+            self.ranked.insert((order_key(entry.score), key));
+        }
+    }
+
+    /// Iterate over all keys, hottest first
+    pub fn iter(&self) -> impl Iterator<Item = K> + '_ {
+        self.ranked.iter().rev().map(|&(_, key)| key)
+    }
+
+    /// A key that sorts hotter assets first
+    pub fn sort_key(&self, key: &K) -> u64 {
+        self.entries
+            .get(key)
+            .map_or(u64::MAX, |entry| u64::MAX - order_key(entry.score))
+    }
+
+    /// Explain the score of `key` as of `now`
+    pub fn breakdown(&self, key: &K, now: u64) -> Option<HotBreakdown> {
+        let entry = self.entries.get(key)?;
+        let factor = (-(now as f64 - self.t_ref as f64) / self.tau).exp();
+        let weighted = |signal: Signal| {
+            (entry.signals[signal as usize] * self.weights[signal as usize] * factor) as f32
+        };
+        Some(HotBreakdown {
+            score: (entry.score * factor) as f32,
+            rank: self.ranked.range((order_key(entry.score), *key)..).count() - 1,
+            volume: weighted(Signal::Volume),
+            trades: weighted(Signal::Trades),
+            unique_traders: weighted(Signal::Traders),
+            momentum: weighted(Signal::Momentum),
+            comments: weighted(Signal::Comments),
+            creation: weighted(Signal::Creation),
+            half_life: self.config.half_life,
+        })
+    }
+}
@@ -1,20 +1,36 @@
 // ivy-aggregator/src/state/mod.rs (UPDATED)
+use std::path::PathBuf;
 use std::sync::{mpsc, RwLock};
 
 pub mod components;
 pub mod constants;
 pub mod helpers;
+pub mod hot;
 pub mod types;
 
-use crate::types::public::Public;
-use crate::types::{asset::Asset, event::Event, trade::Trade};
+use crate::applier::Cursor;
+use crate::types::jsonl::JsonReader;
+use crate::types::event::{Event, EventData};
+use crate::types::{asset::Asset, trade::Trade};
 use components::{
-    assets::AssetsComponent, comments::CommentsComponent, games::GamesComponent,
-    hydrate::HydrateComponent, pnl::PnlComponent, prices::PricesComponent,
-    receipts::ReceiptsComponent, sync::SyncComponent, volume::VolumeComponent,
+    alerts::AlertsComponent,
+    assets::AssetsComponent,
+    comments::CommentsComponent,
+    fees::FeesComponent,
+    games::GamesComponent,
+    hydrate::HydrateComponent,
+    pnl::{CostBasis, PnlComponent},
+    prices::{PriceConfig, PricesComponent},
+    receipts::ReceiptsComponent,
+    stats::{StatsComponent, SyncVolumes},
+    sync::SyncComponent,
+    vaults::VaultsComponent,
+    volume::VolumeComponent,
+    webhooks::{WebhookJob, WebhooksComponent},
     world::WorldComponent,
 };
 use constants::MAX_CANDLES;
+use hot::HotConfig;
 use tokio::sync::{broadcast, watch};
 
 // 64 new assets before receiver is deemed lagged
@@ -22,53 +38,189 @@ const ASSETS_CHANNEL_BUFFER_SIZE: usize = 64;
 
 pub type State = RwLock<StateData>;
 
+/// Where the state is built from, kept to revalue it
+#[derive(Clone)]
+pub struct StateConfig {
+    pub events_path: PathBuf,
+    pub webhooks_path: String,
+    pub alerts_path: String,
+    pub hydration_queue_path: String,
+    pub sol_corrections_path: String,
+    pub hot: HotConfig,
+    pub cost_basis: CostBasis,
+    pub prices: PriceConfig,
+}
+
 pub struct StateData {
+    pub alerts: AlertsComponent,
     pub assets: AssetsComponent,
     pub assets_rx: broadcast::Receiver<Asset>,
     pub comments: CommentsComponent,
+    pub fees: FeesComponent,
     pub games: GamesComponent,
     pub hydrator: HydrateComponent,
     pub pnl: PnlComponent,
     pub prices: PricesComponent,
     pub receipts: ReceiptsComponent,
+    pub stats: StatsComponent,
     pub syncs: SyncComponent,
     pub trades_rx: watch::Receiver<Option<Trade>>,
+    pub vaults: VaultsComponent,
     pub volume: VolumeComponent,
+    pub webhooks: WebhooksComponent,
     pub world: WorldComponent,
+    config: StateConfig,
+}
+
+/// The components that aggregate USD figures valued at the prices of the time,
+/// replayed from the event log on their own to revalue them, along with the
+/// components they take prices from
+struct Revaluation {
+    prices: PricesComponent,
+    pnl: PnlComponent,
+    volume: VolumeComponent,
+    world: WorldComponent,
+    sync_volumes: SyncVolumes,
+}
+
+impl Revaluation {
+    fn new(config: &StateConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            prices: PricesComponent::new(
+                config.prices.clone(),
+                Cursor::new(&config.sol_corrections_path)?,
+            ),
+            pnl: PnlComponent::new(config.cost_basis),
+            volume: VolumeComponent::new(60 * 24),
+            world: WorldComponent::new(MAX_CANDLES),
+            sync_volumes: SyncVolumes::new(),
+        })
+    }
+
+    /// Apply `event` in the order of `StateData::on_event`
+    fn on_event(&mut self, event: &Event) {
+        self.prices.on_event(event);
+        self.pnl.on_event(event, &self.world, &self.prices);
+        self.volume.on_event(event, &self.world, &self.prices);
+        self.world.on_event(event);
+        self.sync_volumes.on_event(event, &self.prices);
+    }
 }
 
 impl StateData {
-    pub fn new(hydrator_tx: mpsc::Sender<(Public, String)>) -> StateData {
+    pub fn new(
+        hydrator_tx: mpsc::Sender<()>,
+        webhook_tx: mpsc::Sender<WebhookJob>,
+        config: StateConfig,
+    ) -> Result<StateData, Box<dyn std::error::Error>> {
+        let webhook_store = Cursor::new(&config.webhooks_path)?;
+        let alert_store = Cursor::new(&config.alerts_path)?;
+        let correction_store = Cursor::new(&config.sol_corrections_path)?;
+        let hydration_store = Cursor::new(&config.hydration_queue_path)?;
         let (trades_tx, trades_rx) = watch::channel(None);
         let (assets_tx, assets_rx) = broadcast::channel(ASSETS_CHANNEL_BUFFER_SIZE);
-        StateData {
-            assets: AssetsComponent::new(),
+        Ok(StateData {
+            alerts: AlertsComponent::new(alert_store, trades_rx.clone(), webhook_tx.clone()),
+            assets: AssetsComponent::new(config.hot),
             assets_rx,
             comments: CommentsComponent::new(),
+            fees: FeesComponent::new(),
             games: GamesComponent::new(MAX_CANDLES, trades_tx.clone(), assets_tx.clone()),
-            hydrator: HydrateComponent::new(hydrator_tx),
-            pnl: PnlComponent::new(),
-            prices: PricesComponent::new(),
+            hydrator: HydrateComponent::new(hydration_store, hydrator_tx.clone()),
+            pnl: PnlComponent::new(config.cost_basis),
+            prices: PricesComponent::new(config.prices.clone(), correction_store),
             receipts: ReceiptsComponent::new(),
+            stats: StatsComponent::new(),
             syncs: SyncComponent::new(trades_tx, assets_tx),
             trades_rx,
+            vaults: VaultsComponent::new(),
             volume: VolumeComponent::new(60 * 24),
+            webhooks: WebhooksComponent::new(webhook_store, webhook_tx.clone()),
             world: WorldComponent::new(MAX_CANDLES),
+            config,
+        })
+    }
+
+    /// Revalue the USD aggregates (PnL, volume and the volume of the statistics)
+    /// at the SOL prices of the time, with the current corrections, by replaying
+    /// the event log into new aggregates. Charts are valued when they're queried,
+    /// and the rest of the state is left as is. The log is replayed without the
+    /// lock, and only the events logged meanwhile are replayed under the write
+    /// lock, before the new aggregates are swapped in.
+    /// Returns how many events were replayed.
+    pub fn revalue(state: &State) -> Result<usize, Box<dyn std::error::Error>> {
+        // The applier logs events under the write lock, so the log
+        // ends with a whole event while the read lock is held
+        let (config, logged) = {
+            let data = state.read().unwrap();
+            let logged = std::fs::metadata(&data.config.events_path)?.len();
+            (data.config.clone(), logged)
+        };
+        let mut replay = Revaluation::new(&config)?;
+        let mut reader = JsonReader::<Event>::new(&config.events_path)?;
+        let mut count = 0;
+        while reader.position() < logged {
+            let Some(event) = reader.read()? else {
+                break;
+            };
+            replay.on_event(&event);
+            count += 1;
+        }
+
+        let mut data = state.write().unwrap();
+        while let Some(event) = reader.read()? {
+            replay.on_event(&event);
+            count += 1;
         }
+        let old = (
+            std::mem::replace(&mut data.pnl, replay.pnl),
+            std::mem::replace(&mut data.volume, replay.volume),
+        );
+        data.stats.revalue(&replay.sync_volumes);
+        drop(data);
+        // Free the old aggregates after releasing the lock
+        drop(old);
+        Ok(count)
     }
 
     pub fn on_event(&mut self, event: &Event) -> bool {
         let mut used = false;
-        used |= self.comments.on_event(event);
+        used |= self.comments.on_event(event, &mut self.assets);
         used |= self.games.on_event(event, &self.world, &mut self.assets);
+        // Fees are taken at the rates in effect before the world applies the event
+        used |= self.fees.on_event(event, &self.world, &self.games);
         used |= self.hydrator.on_event(event);
-        used |= self.pnl.on_event(event, &self.world, &self.prices);
         used |= self.prices.on_event(event);
         used |= self.receipts.on_event(event);
         used |= self.syncs.on_event(event, &self.prices, &mut self.assets);
+        // PnL follows the prices of trades sent by games and syncs
+        used |= self.pnl.on_event(event, &self.world, &self.prices);
+        self.update_holders(event);
+        used |= self.vaults.on_event(event);
         used |= self.volume.on_event(event, &self.world, &self.prices);
+        used |= self.webhooks.on_event(event);
         used |= self.world.on_event(event);
+        used |= self
+            .stats
+            .on_event(event, &self.games, &self.world, &self.prices, &self.fees);
+        // Alerts see the state after everything else has applied the event
+        used |= self.alerts.on_event(event, &self.syncs);
 
         used
     }
+
+    /// Update the holder count of the asset swapped in `event`, from the positions PnL keeps
+    fn update_holders(&mut self, event: &Event) {
+        match &event.data {
+            EventData::GameSwap(swap) => {
+                let holders = self.pnl.holder_count(&swap.game);
+                self.games.set_holders(&swap.game, holders, &mut self.assets);
+            }
+            EventData::SyncSwap(swap) => {
+                let holders = self.pnl.holder_count(&swap.sync);
+                self.syncs.set_holders(&swap.sync, holders, &mut self.assets);
+            }
+            _ => {}
+        }
+    }
 }
@@ -1,9 +1,11 @@
 use serde::Serialize;
 
+use crate::state::components::pnl::CostBasis;
 use crate::types::asset::Asset;
 use crate::types::chart::Candle;
 use crate::types::public::Public;
 use crate::types::signature::Signature;
+use crate::types::source::PriceSource;
 
 #[derive(Serialize)]
 pub struct VlbEntry {
@@ -11,6 +13,42 @@ pub struct VlbEntry {
     pub volume: f32,
 }
 
+/// A user's rank on a leaderboard, with the entries around it
+#[derive(Serialize)]
+pub struct BoardRank<T> {
+    pub user: Public,
+    /// 0-based rank, if the user is on the leaderboard
+    pub rank: Option<usize>,
+    /// Number of users on the leaderboard
+    pub total: usize,
+    /// Rank of the first neighbor
+    pub neighbors_start: usize,
+    /// Entries ranked around the user, including the user
+    pub neighbors: Vec<T>,
+}
+
+/// A user's rank on a volume leaderboard, with their volume
+/// alongside the entries ranked around them
+#[derive(Serialize)]
+pub struct VolumeRank {
+    #[serde(flatten)]
+    pub rank: BoardRank<VlbEntry>,
+    /// The user's volume in the window, in USD
+    pub volume: f32,
+}
+
+impl<T> BoardRank<T> {
+    pub fn unranked(user: Public, total: usize) -> Self {
+        Self {
+            user,
+            rank: None,
+            total,
+            neighbors_start: 0,
+            neighbors: Vec::new(),
+        }
+    }
+}
+
 #[derive(Clone, Serialize)]
 pub struct Comment {
     pub index: u64,
@@ -44,6 +82,45 @@ pub struct WithdrawInfo {
     pub withdraw_authority: Public,
 }
 
+#[derive(Clone, Copy, Serialize)]
+pub struct VaultReceipt {
+    pub signature: Signature,
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ReceiptEntry<T> {
+    /// The receipt ID, as hex
+    pub id: String,
+    #[serde(flatten)]
+    pub info: T,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ReceiptList<T> {
+    pub total: usize,
+    pub receipts: Vec<ReceiptEntry<T>>,
+}
+
+/// Number of receipts of each kind on a UTC day
+#[derive(Clone, Copy, Serialize)]
+pub struct DailyReceipts {
+    /// Start of the day, as a Unix timestamp
+    pub day: u64,
+    pub burns: usize,
+    pub deposits: usize,
+    pub withdrawals: usize,
+}
+
+#[derive(Clone, Copy, Serialize)]
+pub struct VaultInfo {
+    pub vault: Public,
+    pub deposit_count: usize,
+    pub withdrawal_count: usize,
+    pub last_deposit_timestamp: Option<u64>,
+    pub last_withdrawal_timestamp: Option<u64>,
+}
+
 #[derive(Clone, Serialize)]
 pub struct ChartResponse {
     pub candles: Vec<Candle>,
@@ -61,6 +138,131 @@ pub struct IvyInfo {
     pub ivy_change_24h: f32,
 }
 
+/// A release of IVY from vesting
+#[derive(Serialize, Clone, Copy)]
+pub struct VestingEntry {
+    pub timestamp: u64,
+    /// IVY released
+    pub amount: f32,
+    /// IVY released so far, including this release
+    pub total: f32,
+}
+
+/// The price of a hypothetical swap on the IVY curve
+#[derive(Serialize, Clone, Copy)]
+pub struct IvyQuote {
+    /// USDC paid for a buy, or IVY paid for a sell
+    pub amount_in: f32,
+    /// IVY received for a buy, or USDC received for a sell
+    pub amount_out: f32,
+    /// Average price paid, in USDC per IVY
+    pub avg_price: f32,
+    pub price_after: f32,
+    /// Change from the current price to the price after the swap
+    pub price_impact_pct: f32,
+}
+
+#[derive(Serialize, Clone)]
+pub struct IvySupply {
+    /// IVY sold by the curve plus IVY vested, less IVY burned by game swap fees
+    pub circulating_supply: f32,
+    pub ivy_sold: f32,
+    pub ivy_curve_max: f32,
+    /// Fraction of the curve's supply that's been sold
+    pub curve_fill: f32,
+    pub ivy_vested: f32,
+    pub ivy_burned: f32,
+    pub vesting_history: Vec<VestingEntry>,
+    /// USDC held by the curve
+    pub usdc_reserve: f32,
+    /// USDC the curve should hold for the IVY sold, the integral of its price
+    pub usdc_expected: f32,
+    /// `usdc_reserve` over `usdc_expected`
+    pub reserve_ratio: f32,
+    pub solvent: bool,
+    pub ivy_price: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buy_quote: Option<IvyQuote>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sell_quote: Option<IvyQuote>,
+}
+
+/// Protocol statistics over an hour or a day
+#[derive(Serialize, Clone, Copy)]
+pub struct StatsPoint {
+    /// Start of the interval, as a Unix timestamp
+    pub timestamp: u64,
+    /// Total value locked in games at the end of the interval
+    pub tvl_usd: f32,
+    /// Volume of game and sync swaps
+    pub volume_usd: f32,
+    pub new_games: u64,
+    pub new_syncs: u64,
+    /// Unique users who swapped games or syncs
+    pub active_traders: u64,
+    pub comments: u64,
+    /// IVY price at the end of the interval
+    pub ivy_price: f32,
+    /// IVY fees burned by game swaps
+    pub ivy_fees_usd: f32,
+    /// Game token fees paid to game treasuries by game swaps
+    pub game_fees_usd: f32,
+    /// Volume of sync swaps, counted in `volume_usd`, kept to revalue it
+    #[serde(skip)]
+    pub sync_volume_usd: f32,
+}
+
+impl StatsPoint {
+    pub fn new(timestamp: u64, tvl_usd: f32, ivy_price: f32) -> Self {
+        Self {
+            timestamp,
+            tvl_usd,
+            volume_usd: 0.0,
+            new_games: 0,
+            new_syncs: 0,
+            active_traders: 0,
+            comments: 0,
+            ivy_price,
+            ivy_fees_usd: 0.0,
+            game_fees_usd: 0.0,
+            sync_volume_usd: 0.0,
+        }
+    }
+}
+
+/// Fees taken by game swaps, valued in USD at the time of each swap
+#[derive(Serialize, Clone, Copy)]
+pub struct FeeTotals {
+    /// IVY burned
+    pub ivy_fees: f32,
+    pub ivy_fees_usd: f32,
+    /// Game tokens paid to the game's treasury, for a single game
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game_fees: Option<f32>,
+    pub game_fees_usd: f32,
+}
+
+/// Fees taken by game swaps over each window
+#[derive(Serialize, Clone, Copy)]
+pub struct FeesResponse {
+    #[serde(rename = "24h")]
+    pub day: FeeTotals,
+    #[serde(rename = "7d")]
+    pub week: FeeTotals,
+    #[serde(rename = "30d")]
+    pub month: FeeTotals,
+    pub all: FeeTotals,
+}
+
+/// Fees taken by a game's swaps over a day
+#[derive(Serialize, Clone, Copy)]
+pub struct FeePoint {
+    /// Start of the day, as a Unix timestamp
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub fees: FeeTotals,
+}
+
 #[derive(Clone, Serialize)]
 pub struct GlobalInfo {
     pub games_listed: u64,
@@ -69,12 +271,50 @@ pub struct GlobalInfo {
     pub featured_assets: Vec<Asset>,
 }
 
+#[derive(Serialize, Clone, Copy)]
+pub struct HolderEntry {
+    pub user: Public,
+    pub position: f32,
+    /// Fraction of all holdings held by this user
+    pub share: f32,
+}
+
+#[derive(Serialize, Clone)]
+pub struct HoldersResponse {
+    pub total: usize,
+    /// Fraction of all holdings held by the 10 largest holders
+    pub top10_share: f32,
+    pub holders: Vec<HolderEntry>,
+}
+
+/// The hot score of an asset and each signal's weighted contribution to it
+#[derive(Serialize, Clone, Copy)]
+pub struct HotBreakdown {
+    pub score: f32,
+    /// Position in the hot list, starting from 0
+    pub rank: usize,
+    pub volume: f32,
+    pub trades: f32,
+    pub unique_traders: f32,
+    pub momentum: f32,
+    pub comments: f32,
+    pub creation: f32,
+    /// Time for the score to decay to half its value, in seconds
+    pub half_life: u64,
+}
+
 #[derive(Serialize, Clone, Copy)]
 pub struct PnlEntry {
     pub user: Public,
     pub in_usd: f32,
     pub out_usd: f32,
     pub position: f32,
+    /// Cost basis of the position
+    pub cost_usd: f32,
+    /// Proceeds of sells minus the cost basis of what was sold
+    pub realized_usd: f32,
+    /// Value of the position at the current price minus its cost basis
+    pub unrealized_usd: f32,
 }
 
 #[derive(Serialize, Clone, Copy)]
@@ -82,5 +322,135 @@ pub struct PnlResponse {
     pub in_usd: f32,
     pub out_usd: f32,
     pub position: f32,
+    /// Cost basis of the position
+    pub cost_usd: f32,
+    /// Proceeds of sells minus the cost basis of what was sold
+    pub realized_usd: f32,
+    /// Value of the position at `price` minus its cost basis
+    pub unrealized_usd: f32,
     pub price: f32,
+    pub cost_basis: CostBasis,
+}
+
+#[derive(Serialize, Clone)]
+pub struct WebhookInfo {
+    pub game: Public,
+    pub url: String,
+    pub created_at: u64,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryOutcome {
+    Delivered,
+    Retrying,
+    Failed,
+}
+
+/// A single attempt to deliver a webhook
+#[derive(Serialize, Clone)]
+pub struct WebhookDelivery {
+    /// Identifies the receipt being delivered, the same across retries
+    pub delivery_id: String,
+    pub url: String,
+    /// Attempt number, starting from 1
+    pub attempt: u32,
+    pub timestamp: u64,
+    /// HTTP status code of the response, if one was received
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub outcome: DeliveryOutcome,
+    /// When the next attempt is scheduled, if retrying
+    pub next_attempt: Option<u64>,
+}
+
+/// A fired alert, as delivered to webhooks and streams
+#[derive(Serialize, Clone)]
+pub struct Alert {
+    pub rule_id: u64,
+    pub client: String,
+    /// The kind of the rule's condition
+    pub kind: &'static str,
+    pub asset: Public,
+    /// The value that triggered the alert: a price, market cap or trade volume in USD
+    pub value: Option<f32>,
+    /// The rule's threshold, if it has one
+    pub threshold: Option<f32>,
+    pub signature: Signature,
+    pub timestamp: u64,
+}
+
+/// A deposit or withdrawal of liquidity in a sync's PumpSwap pool
+#[derive(Serialize, Clone, Copy)]
+pub struct LiquidityEvent {
+    pub timestamp: u64,
+    pub signature: Signature,
+    pub user: Public,
+    pub is_deposit: bool,
+    pub sol_amount: f32,
+    pub token_amount: f32,
+    /// Raw LP tokens minted or burned
+    pub lp_amount: u64,
+    /// Pool reserves after the change
+    pub sol_reserves: f32,
+    pub token_reserves: f32,
+}
+
+/// SOL to swap through a pool to move its price by `price_change_pct`, before fees
+#[derive(Serialize, Clone, Copy)]
+pub struct DepthLevel {
+    pub price_change_pct: f32,
+    pub sol_amount: f32,
+    pub sol_amount_usd: f32,
+}
+
+#[derive(Serialize, Clone)]
+pub struct LiquidityInfo {
+    pub pool: Public,
+    pub sol_reserves: f32,
+    pub token_reserves: f32,
+    /// Value of both sides of the pool
+    pub liquidity_usd: f32,
+    /// Raw LP token supply
+    pub lp_supply: u64,
+    pub deposits: u64,
+    pub withdrawals: u64,
+    /// SOL to buy up to each price above the current one
+    pub depth_up: Vec<DepthLevel>,
+    /// SOL received for selling down to each price below the current one
+    pub depth_down: Vec<DepthLevel>,
+}
+
+#[derive(Serialize, Clone, Copy)]
+pub struct SolPricePoint {
+    pub timestamp: u64,
+    pub price: f32,
+}
+
+/// The outcome of revaluing the USD aggregates from the event log
+#[derive(Serialize)]
+pub struct RevalueInfo {
+    /// Events replayed
+    pub events: usize,
+    pub duration_ms: u64,
+}
+
+/// The latest SOL price from a source
+#[derive(Serialize, Clone, Copy)]
+pub struct SourcePrice {
+    pub source: PriceSource,
+    pub price: f32,
+    pub timestamp: u64,
+    pub stale: bool,
+}
+
+/// The SOL price, the median of the sources that aren't stale when it was taken
+#[derive(Serialize, Clone)]
+pub struct SolPriceInfo {
+    pub price: f32,
+    /// When the price was last updated
+    pub timestamp: u64,
+    /// Whether every source is stale
+    pub stale: bool,
+    pub sources: Vec<SourcePrice>,
 }
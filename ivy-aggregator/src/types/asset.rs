@@ -12,4 +12,5 @@ pub struct Asset {
     pub create_timestamp: u64,
     pub mkt_cap_usd: f32,
     pub price: f32,
+    pub holders: u64,
 }
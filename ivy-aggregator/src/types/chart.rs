@@ -1,3 +1,4 @@
+use crate::types::public::Public;
 use serde::Serialize;
 use std::collections::VecDeque;
 use std::error::Error;
@@ -7,6 +8,9 @@ use tokio::sync::broadcast;
 // 512 updates before receiver is deemed lagged :)
 const CHANNEL_BUFFER_SIZE: usize = 512;
 
+// Registers in a `TraderSketch`, each 4 bits wide
+const SKETCH_REGISTERS: usize = 32;
+
 #[derive(Debug)]
 pub enum ChartError {
     ChronologyViolation,
@@ -26,6 +30,74 @@ impl fmt::Display for ChartError {
 
 impl Error for ChartError {}
 
+/// A HyperLogLog sketch estimating the number of distinct traders
+/// in a candle. It takes 16 bytes regardless of how many traders it has seen,
+/// at the cost of a standard error of about 18% (1.04 / sqrt(32)) on large
+/// counts. Small counts are estimated by linear counting, which is closer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraderSketch {
+    registers: [u8; SKETCH_REGISTERS / 2],
+}
+
+impl TraderSketch {
+    fn get(&self, index: usize) -> u8 {
+        (self.registers[index / 2] >> ((index % 2) * 4)) & 0xf
+    }
+
+    fn set(&mut self, index: usize, value: u8) {
+        let shift = (index % 2) * 4;
+        let byte = &mut self.registers[index / 2];
+        *byte = (*byte & !(0xf << shift)) | ((value & 0xf) << shift);
+    }
+
+    pub fn insert(&mut self, user: &Public) {
+        // Public keys are mostly uniform, but mix them anyway (splitmix64)
+        // so that program-derived or vanity addresses don't skew the sketch
+        let mut h = u64::from_le_bytes(user.0[..8].try_into().unwrap());
+        h = (h ^ (h >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94d049bb133111eb);
+        h ^= h >> 31;
+
+        let index = (h as usize) % SKETCH_REGISTERS;
+        // The low 5 bits pick the register, so skip them
+        let rank = ((h >> 5).leading_zeros() - 4).min(15) as u8;
+        if rank > self.get(index) {
+            self.set(index, rank);
+        }
+    }
+
+    pub fn merge(&mut self, other: &TraderSketch) {
+        for index in 0..SKETCH_REGISTERS {
+            let value = other.get(index);
+            if value > self.get(index) {
+                self.set(index, value);
+            }
+        }
+    }
+
+    pub fn estimate(&self) -> u32 {
+        let m = SKETCH_REGISTERS as f64;
+        let mut sum = 0.0;
+        let mut zeros = 0;
+        for index in 0..SKETCH_REGISTERS {
+            let value = self.get(index);
+            sum += 2f64.powi(-(value as i32));
+            if value == 0 {
+                zeros += 1;
+            }
+        }
+
+        let raw = 0.697 * m * m / sum;
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            // Small range correction: linear counting
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u32
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize)]
 pub struct Candle {
     pub open_time: u64,
@@ -34,7 +106,113 @@ pub struct Candle {
     pub low: f32,
     pub close: f32,
     pub volume: f32,
+    pub buy_volume: f32,
+    pub sell_volume: f32,
+    /// Volume-weighted average price, or the close if there's no volume
+    pub vwap: f32,
     pub trades: u32,
+    /// Estimated number of distinct traders
+    pub unique_traders: u32,
+    // Sum of volume / price over all trades, used to derive `vwap`
+    #[serde(skip)]
+    base_volume: f32,
+    #[serde(skip)]
+    traders: TraderSketch,
+}
+
+impl Candle {
+    /// Create a candle starting at `open_time` with `open`, containing a single trade
+    fn new(
+        open_time: u64,
+        open: f32,
+        price: f32,
+        volume: f32,
+        is_buy: bool,
+        user: Option<Public>,
+    ) -> Candle {
+        let mut candle = Candle {
+            open_time,
+            open,
+            high: open,
+            low: open,
+            close: open,
+            volume: 0.0,
+            buy_volume: 0.0,
+            sell_volume: 0.0,
+            vwap: open,
+            trades: 0,
+            unique_traders: 0,
+            base_volume: 0.0,
+            traders: TraderSketch::default(),
+        };
+        candle.add_trade(price, volume, is_buy, user);
+        candle
+    }
+
+    /// Add a trade to this candle
+    fn add_trade(&mut self, price: f32, volume: f32, is_buy: bool, user: Option<Public>) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += volume;
+        if is_buy {
+            self.buy_volume += volume;
+        } else {
+            self.sell_volume += volume;
+        }
+        if price > 0.0 {
+            self.base_volume += volume / price;
+        }
+        self.trades = self.trades.saturating_add(1);
+        if let Some(user) = user {
+            self.traders.insert(&user);
+        }
+        self.refresh();
+    }
+
+    /// This candle, with its start moved to `open_time`
+    pub fn starting_at(mut self, open_time: u64) -> Candle {
+        self.open_time = open_time;
+        self
+    }
+
+    /// Convert the candle's prices and volumes into another quote, `rate` of it per unit of
+    /// this one. The base volume, in tokens, is the same in both.
+    pub fn valued(mut self, rate: f32) -> Candle {
+        self.open *= rate;
+        self.high *= rate;
+        self.low *= rate;
+        self.close *= rate;
+        self.volume *= rate;
+        self.buy_volume *= rate;
+        self.sell_volume *= rate;
+        self.refresh();
+        self
+    }
+
+    /// Merge a later candle into this one, as if both were a single candle
+    pub fn merge(&mut self, next: &Candle) {
+        self.high = self.high.max(next.high);
+        self.low = self.low.min(next.low);
+        self.close = next.close;
+        self.volume += next.volume;
+        self.buy_volume += next.buy_volume;
+        self.sell_volume += next.sell_volume;
+        self.base_volume += next.base_volume;
+        self.trades = self.trades.saturating_add(next.trades);
+        self.traders.merge(&next.traders);
+        self.refresh();
+    }
+
+    /// Recompute the fields derived from the hidden accumulators
+    fn refresh(&mut self) {
+        self.vwap = if self.base_volume > 0.0 {
+            self.volume / self.base_volume
+        } else {
+            self.close
+        };
+        self.unique_traders = self.traders.estimate();
+    }
 }
 
 pub struct Chart {
@@ -64,6 +242,11 @@ impl Chart {
         }
     }
 
+    /// The interval of this chart's candles, in seconds
+    pub fn interval(&self) -> u64 {
+        self.interval
+    }
+
     /// Subscribe to real-time candle updates.
     /// Returns a receiver that will receive a `Candle` every time the chart is updated.
     /// The candle will either be an updated version of the latest candle (same `open_time`)
@@ -113,8 +296,16 @@ impl Chart {
 
     /// Gets the change in 24h
     pub fn get_change_pct_24h(&self) -> Option<f32> {
-        let price_24h = self.candles[self.index_24h?].open;
-        let price_now = self.candles.back()?.close;
+        self.get_change_pct_24h_valued(|_| 1.0)
+    }
+
+    /// Gets the change in 24h in another quote, `rate_at(t)` of it per unit
+    /// of this chart's quote at time `t`
+    pub fn get_change_pct_24h_valued(&self, rate_at: impl Fn(u64) -> f32) -> Option<f32> {
+        let candle_24h = &self.candles[self.index_24h?];
+        let price_24h = candle_24h.open * rate_at(candle_24h.open_time);
+        let last = self.candles.back()?;
+        let price_now = last.close * rate_at(last.open_time + self.interval - 1);
         Some(match price_24h {
             0.0 => f32::INFINITY,
             _ => ((price_now - price_24h) / price_24h) * 100.0,
@@ -134,7 +325,17 @@ impl Chart {
             }
         }
     }
-    pub fn append(&mut self, timestamp: u64, price: f32, volume: f32) -> Result<(), ChartError> {
+
+    /// Append a trade to the chart. `user` is the trader,
+    /// or `None` if this isn't a real trade (e.g. the initial price of an asset).
+    pub fn append(
+        &mut self,
+        timestamp: u64,
+        price: f32,
+        volume: f32,
+        is_buy: bool,
+        user: Option<Public>,
+    ) -> Result<(), ChartError> {
         // Validate inputs
         if price.is_nan() || volume.is_nan() {
             return Err(ChartError::NotANumberProvided);
@@ -145,15 +346,7 @@ impl Chart {
         // Process the trade data
         if self.candles.is_empty() {
             // First candle ever
-            let new_candle = Candle {
-                open_time: candle_start,
-                open: price,
-                high: price,
-                low: price,
-                close: price,
-                volume,
-                trades: 1,
-            };
+            let new_candle = Candle::new(candle_start, price, price, volume, is_buy, user);
 
             self.candles.push_back(new_candle);
 
@@ -168,26 +361,15 @@ impl Chart {
                 return Err(ChartError::ChronologyViolation);
             } else if candle_start == last_candle.open_time {
                 // Update existing candle
-                last_candle.high = last_candle.high.max(price);
-                last_candle.low = last_candle.low.min(price);
-                last_candle.close = price;
-                last_candle.volume += volume;
-                last_candle.trades = last_candle.trades.saturating_add(1);
+                last_candle.add_trade(price, volume, is_buy, user);
 
                 // Broadcast the updated candle (ignore send errors if no subscribers)
                 let last_candle = *last_candle;
                 self.broadcast(last_candle);
             } else {
                 // Create new candle
-                let new_candle = Candle {
-                    open_time: candle_start,
-                    open: last_candle.close,
-                    high: last_candle.close.max(price),
-                    low: last_candle.close.min(price),
-                    close: price,
-                    volume,
-                    trades: 1,
-                };
+                let new_candle =
+                    Candle::new(candle_start, last_candle.close, price, volume, is_buy, user);
 
                 // Remove oldest candle if at capacity
                 if self.candles.len() >= self.max_candles {
@@ -1,91 +1,320 @@
 use crate::types::chart::{Candle, Chart, ChartError};
+use crate::types::public::Public;
 use serde::Deserialize;
 use tokio::sync::broadcast;
 
-// First, add this derive to the ChartKind enum
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+// Longest fixed interval we're willing to derive
+const MAX_INTERVAL_SECONDS: u64 = SECONDS_PER_DAY * 365;
+// Longest calendar interval we're willing to derive
+const MAX_INTERVAL_MONTHS: u32 = 12;
+
+/// Intervals (in seconds) of the charts that are actually maintained on
+/// every trade. Any other interval is derived on request from the coarsest
+/// of these that evenly divides it, and so has as much history as that chart.
+const BASE_INTERVALS: [u64; 5] = [
+    1,               // 1 second
+    60,              // 1 minute
+    60 * 15,         // 15 minutes
+    60 * 60,         // 1 hour
+    SECONDS_PER_DAY, // 1 day
+];
+
+// Candles kept by the 1 second chart, which is only meant for recent activity
+const SECOND_CANDLES: usize = 15 * 60;
+
+/// A chart interval, parsed from strings like `1s`, `5m`, `4h`, `1d`, `1w` or `1M`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
-#[repr(u8)]
+#[serde(try_from = "String")]
 pub enum ChartKind {
-    #[serde(rename = "1m")]
-    M1 = 0,
-    #[serde(rename = "5m")]
-    M5,
-    #[serde(rename = "15m")]
-    M15,
-    #[serde(rename = "1h")]
-    H1,
-    #[serde(rename = "1d")]
-    D1,
-    #[serde(rename = "1w")]
-    W1,
+    /// A fixed interval of this many seconds
+    Seconds(u64),
+    /// This many calendar months (UTC)
+    Months(u32),
 }
 
 impl std::str::FromStr for ChartKind {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "1m" => Ok(ChartKind::M1),
-            "5m" => Ok(ChartKind::M5),
-            "15m" => Ok(ChartKind::M15),
-            "1h" => Ok(ChartKind::H1),
-            "1d" => Ok(ChartKind::D1),
-            "1w" => Ok(ChartKind::W1),
-            _ => Err(format!("Unknown chart kind: {}", s)),
+        let unit_start = s
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(|| format!("Unknown chart kind: {}", s))?;
+        let (n, unit) = s.split_at(unit_start);
+        let n: u64 = match n.parse() {
+            Ok(v) if v > 0 => v,
+            _ => return Err(format!("Unknown chart kind: {}", s)),
+        };
+        let seconds = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 60 * 60,
+            "d" => SECONDS_PER_DAY,
+            "w" => SECONDS_PER_DAY * 7,
+            "M" => {
+                if n > MAX_INTERVAL_MONTHS as u64 {
+                    return Err(format!("Chart interval too long: {}", s));
+                }
+                return Ok(ChartKind::Months(n as u32));
+            }
+            _ => return Err(format!("Unknown chart kind: {}", s)),
+        };
+        match n.checked_mul(seconds) {
+            Some(v) if v <= MAX_INTERVAL_SECONDS => Ok(ChartKind::Seconds(v)),
+            _ => Err(format!("Chart interval too long: {}", s)),
         }
     }
 }
 
+impl TryFrom<String> for ChartKind {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 impl ChartKind {
-    pub const COUNT: usize = 6;
+    /// Index into `BASE_INTERVALS` of the chart this kind is served from
+    fn base_index(&self) -> usize {
+        match self {
+            ChartKind::Seconds(n) => BASE_INTERVALS
+                .iter()
+                .rposition(|base| n % base == 0)
+                .unwrap_or(0),
+            ChartKind::Months(_) => BASE_INTERVALS.len() - 1,
+        }
+    }
+
+    /// The start of the candle containing `timestamp`
+    fn bucket_start(&self, timestamp: u64) -> u64 {
+        match *self {
+            ChartKind::Seconds(n) => (timestamp / n) * n,
+            ChartKind::Months(n) => month_bucket_start(timestamp, n),
+        }
+    }
+
+    /// The maximum number of base candles that fit in one candle of this kind
+    fn max_base_candles(&self, base_interval: u64) -> usize {
+        match *self {
+            ChartKind::Seconds(n) => (n / base_interval) as usize,
+            ChartKind::Months(n) => (31 * SECONDS_PER_DAY * n as u64 / base_interval) as usize,
+        }
+    }
+}
+
+/// The currency a chart is denominated in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartQuote {
+    #[default]
+    Usd,
+    Sol,
+    Ivy,
+}
+
+/// Folds base candle updates into candles of a derived interval,
+/// so that subscribers to a derived chart receive candles of their own interval.
+pub struct CandleFolder {
+    kind: ChartKind,
+    // The current derived candle, excluding the newest base candle
+    committed: Option<Candle>,
+    // The newest base candle, which may still be updated
+    last: Option<Candle>,
+}
+
+impl CandleFolder {
+    /// Fold a base candle update, returning the updated derived candle
+    pub fn fold(&mut self, candle: Candle) -> Candle {
+        let start = self.kind.bucket_start(candle.open_time);
+        if let Some(last) = self.last {
+            if last.open_time != candle.open_time {
+                // The newest base candle is final, move it into the committed part
+                self.committed = match self.committed {
+                    Some(mut c) if c.open_time == start => {
+                        c.merge(&last);
+                        Some(c)
+                    }
+                    _ if self.kind.bucket_start(last.open_time) == start => {
+                        Some(last.starting_at(start))
+                    }
+                    _ => None,
+                };
+            }
+        }
+        self.last = Some(candle);
+
+        match self.committed {
+            Some(mut c) => {
+                c.merge(&candle);
+                c
+            }
+            None => candle.starting_at(start),
+        }
+    }
 }
 
 pub struct Charts {
-    pub charts: [Chart; ChartKind::COUNT],
+    charts: [Chart; BASE_INTERVALS.len()],
 }
 
 impl Charts {
     pub fn new(max_candles: usize) -> Self {
         Self {
-            charts: [
-                Chart::new(60 * 1, max_candles),           // 1 minute
-                Chart::new(60 * 5, max_candles),           // 5 minutes
-                Chart::new(60 * 15, max_candles),          // 15 minutes
-                Chart::new(60 * 60, max_candles),          // 1 hour
-                Chart::new(60 * 60 * 24, max_candles),     // 1 day
-                Chart::new(60 * 60 * 24 * 7, max_candles), // 1 week
-            ],
+            charts: BASE_INTERVALS.map(|interval| match interval {
+                1 => Chart::new(interval, SECOND_CANDLES.min(max_candles)),
+                _ => Chart::new(interval, max_candles),
+            }),
         }
     }
 
-    pub fn append(&mut self, timestamp: u64, price: f32, volume: f32) -> Result<(), ChartError> {
-        self.charts[ChartKind::M1 as usize].append(timestamp, price, volume)?;
-        self.charts[ChartKind::M5 as usize].append(timestamp, price, volume)?;
-        self.charts[ChartKind::M15 as usize].append(timestamp, price, volume)?;
-        self.charts[ChartKind::H1 as usize].append(timestamp, price, volume)?;
-        self.charts[ChartKind::D1 as usize].append(timestamp, price, volume)?;
-        self.charts[ChartKind::W1 as usize].append(timestamp, price, volume)?;
+    pub fn append(
+        &mut self,
+        timestamp: u64,
+        price: f32,
+        volume: f32,
+        is_buy: bool,
+        user: Option<Public>,
+    ) -> Result<(), ChartError> {
+        for chart in &mut self.charts {
+            chart.append(timestamp, price, volume, is_buy, user)?;
+        }
 
         Ok(())
     }
 
     pub fn query(&self, kind: ChartKind, count: usize, after_inclusive: u64) -> Vec<Candle> {
-        self.charts[kind as usize].query(count, after_inclusive)
+        self.query_valued(kind, count, after_inclusive, |_| 1.0)
+    }
+
+    /// Query the chart in another quote, `rate_at(t)` of it per unit of this chart's
+    /// quote at time `t`. Each base candle is valued at the rate when it closes.
+    pub fn query_valued(
+        &self,
+        kind: ChartKind,
+        count: usize,
+        after_inclusive: u64,
+        rate_at: impl Fn(u64) -> f32,
+    ) -> Vec<Candle> {
+        let base = &self.charts[kind.base_index()];
+        let value = |candle: Candle| candle.valued(rate_at(candle.open_time + base.interval() - 1));
+        if kind == ChartKind::Seconds(base.interval()) {
+            return base
+                .query(count, after_inclusive)
+                .into_iter()
+                .map(value)
+                .collect();
+        }
+
+        // Fetch enough base candles to fill `count` derived candles,
+        // plus one more, as the oldest one might be incomplete
+        let limit = count
+            .saturating_add(1)
+            .saturating_mul(kind.max_base_candles(base.interval()));
+        let base_candles = base.query(limit, kind.bucket_start(after_inclusive));
+        let truncated = base_candles.len() == limit;
+
+        let mut result: Vec<Candle> = Vec::new();
+        for candle in base_candles.into_iter().map(value) {
+            let start = kind.bucket_start(candle.open_time);
+            match result.last_mut() {
+                Some(last) if last.open_time == start => last.merge(&candle),
+                _ => result.push(candle.starting_at(start)),
+            }
+        }
+
+        if truncated && !result.is_empty() {
+            // Some of the first candle's base candles were cut off
+            result.remove(0);
+        }
+        result.retain(|c| c.open_time >= after_inclusive);
+        let excess = result.len().saturating_sub(count);
+        result.drain(..excess);
+        result
     }
 
     /// Gets the change percent in the last 24h, if we have it
     pub fn get_change_pct_24h(&self) -> Option<f32> {
-        for c in &self.charts {
-            match c.get_change_pct_24h() {
-                Some(v) => return Some(v),
-                None => {}
+        self.charts.iter().find_map(|c| c.get_change_pct_24h())
+    }
+
+    /// Gets the change percent in the last 24h in another quote, as in `query_valued`
+    pub fn get_change_pct_24h_valued(&self, rate_at: impl Fn(u64) -> f32) -> Option<f32> {
+        self.charts
+            .iter()
+            .find_map(|c| c.get_change_pct_24h_valued(&rate_at))
+    }
+
+    /// Subscribe to updates for a specific chart kind.
+    /// Updates arrive as base candles, and must be passed
+    /// through the returned `CandleFolder` before use.
+    pub fn subscribe_to_kind(
+        &mut self,
+        kind: ChartKind,
+    ) -> (broadcast::Receiver<Candle>, CandleFolder) {
+        self.subscribe_to_kind_valued(kind, |_| 1.0)
+    }
+
+    /// Subscribe to updates for a specific chart kind in another quote, as in
+    /// `query_valued`. The folder is seeded in that quote, but updates arrive in
+    /// this chart's quote, and must be valued before they're folded.
+    pub fn subscribe_to_kind_valued(
+        &mut self,
+        kind: ChartKind,
+        rate_at: impl Fn(u64) -> f32,
+    ) -> (broadcast::Receiver<Candle>, CandleFolder) {
+        let base = &mut self.charts[kind.base_index()];
+        let interval = base.interval();
+
+        // Seed the folder with the base candles of the current derived candle
+        let mut folder = CandleFolder {
+            kind,
+            committed: None,
+            last: None,
+        };
+        if let Some(newest) = base.query(1, 0).first() {
+            let start = kind.bucket_start(newest.open_time);
+            for candle in base.query(kind.max_base_candles(interval), start) {
+                folder.fold(candle.valued(rate_at(candle.open_time + interval - 1)));
             }
         }
-        None
-    }
 
-    /// Subscribe to updates for a specific chart kind
-    pub fn subscribe_to_kind(&mut self, kind: ChartKind) -> broadcast::Receiver<Candle> {
-        self.charts[kind as usize].subscribe()
+        (base.subscribe(), folder)
     }
 }
+
+/// Start (UTC) of the `months`-month bucket containing `timestamp`,
+/// where buckets are aligned to January of year 0.
+fn month_bucket_start(timestamp: u64, months: u32) -> u64 {
+    let (year, month) = civil_from_days((timestamp / SECONDS_PER_DAY) as i64);
+    let index = year * 12 + (month - 1);
+    let index = index - index.rem_euclid(months as i64);
+    let days = days_from_civil(index.div_euclid(12), index.rem_euclid(12) + 1);
+    (days.max(0) as u64) * SECONDS_PER_DAY
+}
+
+/// Convert days since the unix epoch into a (year, month) pair
+fn civil_from_days(days: i64) -> (i64, i64) {
+    // See http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month)
+}
+
+/// Convert the first day of a (year, month) into days since the unix epoch
+fn days_from_civil(year: i64, month: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
@@ -2,7 +2,7 @@
 use borsh::BorshDeserialize;
 use crate::pf;  // Import Pump.fun events and discriminators
 use crate::types::public::Public;
-use crate::types::source::Source;
+use crate::types::source::{PriceSource, Source};
 use crate::types::signature::Signature;
 use serde::ser::SerializeStruct;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
@@ -105,6 +105,8 @@ pub enum EventData {
     PfMigration(pf::PfMigrationEvent),
     PaBuy(pf::PaBuyEvent),
     PaSell(pf::PaSellEvent),
+    PaDeposit(pf::PaDepositEvent),
+    PaWithdraw(pf::PaWithdrawEvent),
     SolPrice(SolPriceEvent),
     Initialize(InitializeEvent),
     Hydrate(HydrateEvent),
@@ -219,6 +221,16 @@ impl EventData {
                     .map_err(|e| format!("Failed to deserialize PaSellEvent: {}", e))?;
                 return Ok(Some(EventData::PaSell(event)));
             }
+            pf::PA_DEPOSIT_EVENT_TAG => {
+                let event = pf::PaDepositEvent::deserialize_reader(&mut event_data)
+                    .map_err(|e| format!("Failed to deserialize PaDepositEvent: {}", e))?;
+                return Ok(Some(EventData::PaDeposit(event)));
+            }
+            pf::PA_WITHDRAW_EVENT_TAG => {
+                let event = pf::PaWithdrawEvent::deserialize_reader(&mut event_data)
+                    .map_err(|e| format!("Failed to deserialize PaWithdrawEvent: {}", e))?;
+                return Ok(Some(EventData::PaWithdraw(event)));
+            }
             _ => {
                 return Ok(None);
             }
@@ -246,6 +258,8 @@ impl EventData {
             EventData::PfMigration(..) => Source::Pf,
             EventData::PaBuy(..) => Source::Pa,
             EventData::PaSell(..) => Source::Pa,
+            EventData::PaDeposit(..) => Source::Pa,
+            EventData::PaWithdraw(..) => Source::Pa,
             EventData::SolPrice(_) => Source::Fx,
             EventData::Initialize(_) => Source::Misc,
             EventData::Hydrate(_) => Source::Misc,
@@ -272,6 +286,8 @@ pub struct Event {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SolPriceEvent {
     pub price: f64,
+    #[serde(default)]
+    pub source: PriceSource,
 }
 impl_event_type!(SolPriceEvent, "solPriceEvent", SolPrice);
 
@@ -287,6 +303,12 @@ pub struct HydrateEvent {
     pub description: String,
     #[serde(rename = "iconUrl")]
     pub icon_url: String,
+    #[serde(default)]
+    pub website: String,
+    #[serde(default)]
+    pub twitter: String,
+    #[serde(default)]
+    pub telegram: String,
 }
 impl_event_type!(HydrateEvent, "hydrateEvent", Hydrate);
 
@@ -528,6 +550,8 @@ impl Serialize for Event {
             EventData::PfMigration(e) => ("pfMigrationEvent", serde_json::to_value(e)),
             EventData::PaBuy(e) => ("paBuyEvent", serde_json::to_value(e)),
             EventData::PaSell(e) => ("paSellEvent", serde_json::to_value(e)),
+            EventData::PaDeposit(e) => ("paDepositEvent", serde_json::to_value(e)),
+            EventData::PaWithdraw(e) => ("paWithdrawEvent", serde_json::to_value(e)),
             EventData::SolPrice(e) => (SolPriceEvent::NAME, serde_json::to_value(e)),
             EventData::Initialize(e) => (InitializeEvent::NAME, serde_json::to_value(e)),
             EventData::Hydrate(e) => (HydrateEvent::NAME, serde_json::to_value(e)),
@@ -668,6 +692,16 @@ fn deserialize_event_data<E: de::Error>(
             .map(|e| EventData::PaSell(e))
             .map_err(E::custom);
     }
+    if name == "paDepositEvent" {
+        return serde_json::from_value::<pf::PaDepositEvent>(data)
+            .map(|e| EventData::PaDeposit(e))
+            .map_err(E::custom);
+    }
+    if name == "paWithdrawEvent" {
+        return serde_json::from_value::<pf::PaWithdrawEvent>(data)
+            .map(|e| EventData::PaWithdraw(e))
+            .map_err(E::custom);
+    }
     if name == SolPriceEvent::NAME {
         return serde_json::from_value::<SolPriceEvent>(data)
             .map(|e| e.into_event_data())
@@ -16,6 +16,10 @@ pub struct Game {
     pub game_url: String,
     pub icon_url: String,
     pub description: String,
+    // Links from the metadata, empty if it has none
+    pub website: String,
+    pub twitter: String,
+    pub telegram: String,
     pub metadata_url: String,
     pub create_timestamp: u64,
     #[serde(serialize_with = "serialize_u64_as_string")]
@@ -26,11 +30,10 @@ pub struct Game {
     pub starting_ivy_balance: u64,
     #[serde(skip)]
     pub starting_game_balance: u64,
-    #[serde(skip)]
-    pub normalized_name: String,
     pub last_price_usd: f32,
     pub mkt_cap_usd: f32,
     pub change_pct_24h: f32,
+    pub holders: u64,
 }
 
 impl Game {
@@ -44,6 +47,7 @@ impl Game {
             create_timestamp: self.create_timestamp,
             mkt_cap_usd: self.mkt_cap_usd,
             price: self.last_price_usd,
+            holders: self.holders,
         }
     }
 }
@@ -10,6 +10,8 @@ use serde::{de::DeserializeOwned, Serialize};
 /// A reader for a file containing newline-separated JSON objects.
 pub struct JsonReader<T: DeserializeOwned> {
     reader: BufReader<File>,
+    // Bytes read so far
+    position: u64,
     _phantom: PhantomData<T>,
 }
 
@@ -23,6 +25,7 @@ impl<T: DeserializeOwned> JsonReader<T> {
     pub fn new<P: AsRef<Path>>(path: P) -> io::Result<JsonReader<T>> {
         Ok(JsonReader {
             reader: BufReader::new(File::open(path)?),
+            position: 0,
             _phantom: PhantomData,
         })
     }
@@ -34,6 +37,7 @@ impl<T: DeserializeOwned> JsonReader<T> {
                 Ok(bytes) => bytes,
                 Err(e) => return Err(serde_json::Error::io(e)),
             };
+            self.position += bytes_read as u64;
 
             if bytes_read == 0 {
                 // EOF reached
@@ -49,6 +53,11 @@ impl<T: DeserializeOwned> JsonReader<T> {
         let item: T = serde_json::from_str(&line)?;
         Ok(Some(item))
     }
+
+    /// How many bytes of the file were read
+    pub fn position(&self) -> u64 {
+        self.position
+    }
 }
 
 impl<T: Serialize> JsonWriter<T> {
@@ -1,13 +1,43 @@
 use std::cmp::Reverse;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::HashMap;
 use std::hash::Hash;
 
+// Index of a missing node
+const NIL: usize = usize::MAX;
+
+#[derive(Debug)]
+struct Node<K, V> {
+    entry: (Reverse<V>, K),
+    priority: u64,
+    left: usize,
+    right: usize,
+    // Number of nodes in this subtree
+    size: usize,
+}
+
+/// Keys ranked by descending value (then ascending key), stored in a treap
+/// augmented with subtree sizes, so that lookups by rank and ranks of keys
+/// are O(log n) as well as updates.
 #[derive(Debug)]
 pub struct Leaderboard<K, V> {
     // Key -> Value mapping for O(1) lookups
     values: HashMap<K, V>,
-    // (value, key) sorted set for O(log n) operations
-    sorted: BTreeSet<(Reverse<V>, K)>,
+    nodes: Vec<Node<K, V>>,
+    // Indices of nodes that were removed, for reuse
+    free: Vec<usize>,
+    root: usize,
+    // State of the xorshift generator for node priorities
+    seed: u64,
+}
+
+impl<K, V> Default for Leaderboard<K, V>
+where
+    K: Clone + Hash + Eq + Ord,
+    V: Clone + Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<K, V> Leaderboard<K, V>
@@ -18,32 +48,226 @@ where
     pub fn new() -> Self {
         Self {
             values: HashMap::new(),
-            sorted: BTreeSet::new(),
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: NIL,
+            seed: 0x9E37_79B9_7F4A_7C15,
         }
     }
 
     pub fn update(&mut self, key: K, value: V) {
         // Remove old entry if it exists
-        if let Some(old_value) = self.values.get(&key) {
-            self.sorted
-                .remove(&(Reverse(old_value.clone()), key.clone()));
+        if let Some(old_value) = self.values.get(&key).cloned() {
+            self.remove_entry(&(Reverse(old_value), key.clone()));
         }
 
         // Insert new entries
         self.values.insert(key.clone(), value.clone());
-        self.sorted.insert((Reverse(value), key));
+        self.insert_entry((Reverse(value), key));
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let value = self.values.remove(key)?;
+        self.remove_entry(&(Reverse(value.clone()), key.clone()));
+        Some(value)
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.nodes.clear();
+        self.free.clear();
+        self.root = NIL;
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
     }
 
     pub fn get(&self, key: &K) -> Option<&V> {
         self.values.get(key)
     }
 
+    /// The 0-based rank of `key`, if it's on the leaderboard
+    pub fn rank_of(&self, key: &K) -> Option<usize> {
+        let value = self.values.get(key)?;
+        Some(self.count_before(&(Reverse(value.clone()), key.clone())))
+    }
+
+    /// The rank of `key`, the rank `n` ranks above it, and the entries
+    /// from there to `n` ranks below it
+    pub fn neighbors(
+        &self,
+        key: &K,
+        n: usize,
+    ) -> Option<(usize, usize, impl Iterator<Item = (&K, &V)>)> {
+        let rank = self.rank_of(key)?;
+        let start = rank.saturating_sub(n);
+        Some((rank, start, self.range(start, rank - start + 1 + n)))
+    }
+
+    /// Iterate over `count` entries in rank order, starting at rank `skip`.
+    /// Finding the entry at rank `skip` is O(log n), and each next entry is amortized O(1).
     pub fn range(&self, skip: usize, count: usize) -> impl Iterator<Item = (&K, &V)> {
-        self.sorted
-            .iter()
-            .skip(skip)
-            .take(count)
-            .map(|(Reverse(value), key)| (key, value))
+        // Descend to the node at rank `skip`, keeping the nodes
+        // that come after it on the way down
+        let mut stack = Vec::new();
+        let mut node = self.root;
+        let mut index = skip;
+        while node != NIL {
+            let left_size = self.size(self.nodes[node].left);
+            if index < left_size {
+                stack.push(node);
+                node = self.nodes[node].left;
+            } else if index == left_size {
+                stack.push(node);
+                break;
+            } else {
+                index -= left_size + 1;
+                node = self.nodes[node].right;
+            }
+        }
+        Iter {
+            lb: self,
+            stack,
+            remaining: count,
+        }
+    }
+
+    fn size(&self, node: usize) -> usize {
+        match node {
+            NIL => 0,
+            node => self.nodes[node].size,
+        }
+    }
+
+    fn recompute_size(&mut self, node: usize) {
+        let n = &self.nodes[node];
+        let size = 1 + self.size(n.left) + self.size(n.right);
+        self.nodes[node].size = size;
+    }
+
+    fn next_priority(&mut self) -> u64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        self.seed
+    }
+
+    /// Number of entries ordered before `entry`
+    fn count_before(&self, entry: &(Reverse<V>, K)) -> usize {
+        let mut count = 0;
+        let mut node = self.root;
+        while node != NIL {
+            let n = &self.nodes[node];
+            if n.entry < *entry {
+                count += self.size(n.left) + 1;
+                node = n.right;
+            } else {
+                node = n.left;
+            }
+        }
+        count
+    }
+
+    /// Split the subtree at `node` into its first `k` nodes and the rest
+    fn split(&mut self, node: usize, k: usize) -> (usize, usize) {
+        if node == NIL {
+            return (NIL, NIL);
+        }
+        let left_size = self.size(self.nodes[node].left);
+        if k <= left_size {
+            let (l, r) = self.split(self.nodes[node].left, k);
+            self.nodes[node].left = r;
+            self.recompute_size(node);
+            (l, node)
+        } else {
+            let (l, r) = self.split(self.nodes[node].right, k - left_size - 1);
+            self.nodes[node].right = l;
+            self.recompute_size(node);
+            (node, r)
+        }
+    }
+
+    /// Join two subtrees, where every node of `a` comes before every node of `b`
+    fn merge(&mut self, a: usize, b: usize) -> usize {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        if self.nodes[a].priority > self.nodes[b].priority {
+            let right = self.merge(self.nodes[a].right, b);
+            self.nodes[a].right = right;
+            self.recompute_size(a);
+            a
+        } else {
+            let left = self.merge(a, self.nodes[b].left);
+            self.nodes[b].left = left;
+            self.recompute_size(b);
+            b
+        }
+    }
+
+    fn insert_entry(&mut self, entry: (Reverse<V>, K)) {
+        let rank = self.count_before(&entry);
+        let node = Node {
+            entry,
+            priority: self.next_priority(),
+            left: NIL,
+            right: NIL,
+            size: 1,
+        };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        let (l, r) = self.split(self.root, rank);
+        let l = self.merge(l, index);
+        self.root = self.merge(l, r);
+    }
+
+    fn remove_entry(&mut self, entry: &(Reverse<V>, K)) {
+        let rank = self.count_before(entry);
+        let (l, r) = self.split(self.root, rank);
+        let (removed, r) = self.split(r, 1);
+        if removed != NIL {
+            self.free.push(removed);
+        }
+        self.root = self.merge(l, r);
+    }
+}
+
+struct Iter<'a, K, V> {
+    lb: &'a Leaderboard<K, V>,
+    // Nodes still to visit, next on top
+    stack: Vec<usize>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.stack.pop()?;
+        self.remaining -= 1;
+        let nodes = &self.lb.nodes;
+        let mut next = nodes[node].right;
+        while next != NIL {
+            self.stack.push(next);
+            next = nodes[next].left;
+        }
+        let (Reverse(value), key) = &nodes[node].entry;
+        Some((key, value))
     }
 }
 
@@ -60,4 +284,19 @@ where
         let current = self.get(&key).cloned().unwrap_or_default();
         self.update(key, current + delta);
     }
+
+    /// Subtract `delta` from `key`'s value, removing `key` once it reaches zero
+    pub fn decrement(&mut self, key: &K, delta: V)
+    where
+        V: Default + std::ops::Sub<Output = V>,
+    {
+        let Some(current) = self.get(key).cloned() else {
+            return;
+        };
+        if current > delta {
+            self.update(key.clone(), current - delta);
+        } else {
+            self.remove(key);
+        }
+    }
 }
@@ -6,6 +6,8 @@ pub mod game;
 pub mod jsonl;
 pub mod leaderboard;
 pub mod public;
+pub mod receipt_log;
+pub mod search;
 pub mod signature;
 pub mod source;
 pub mod sync;
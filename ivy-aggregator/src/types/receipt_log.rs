@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A log of receipts keyed by their 32-byte ID,
+/// kept sorted by timestamp so time windows can be sliced cheaply.
+pub struct ReceiptLog<T> {
+    // (id, timestamp, info), sorted by timestamp
+    entries: Vec<([u8; 32], u64, T)>,
+    id_to_index: HashMap<[u8; 32], usize>,
+}
+
+impl<T> ReceiptLog<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            id_to_index: HashMap::new(),
+        }
+    }
+
+    /// Insert a receipt, keeping the first one seen for each ID
+    pub fn insert(&mut self, id: [u8; 32], timestamp: u64, info: T) {
+        if self.id_to_index.contains_key(&id) {
+            return;
+        }
+        if self
+            .entries
+            .last()
+            .is_none_or(|&(_, last, _)| last <= timestamp)
+        {
+            // Common case: events arrive in order
+            self.id_to_index.insert(id, self.entries.len());
+            self.entries.push((id, timestamp, info));
+            return;
+        }
+        let index = self.entries.partition_point(|&(_, t, _)| t <= timestamp);
+        self.entries.insert(index, (id, timestamp, info));
+        for (i, (id, _, _)) in self.entries.iter().enumerate().skip(index) {
+            self.id_to_index.insert(*id, i);
+        }
+    }
+
+    pub fn get(&self, id: &[u8; 32]) -> Option<&T> {
+        self.id_to_index
+            .get(id)
+            .map(|&index| &self.entries[index].2)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn last_timestamp(&self) -> Option<u64> {
+        self.entries.last().map(|&(_, timestamp, _)| timestamp)
+    }
+
+    /// Indices of the receipts with `from <= timestamp < to`
+    fn window(&self, from: Option<u64>, to: Option<u64>) -> Range<usize> {
+        let start = match from {
+            Some(from) => self.entries.partition_point(|&(_, t, _)| t < from),
+            None => 0,
+        };
+        let end = match to {
+            Some(to) => self.entries.partition_point(|&(_, t, _)| t < to),
+            None => self.entries.len(),
+        };
+        start..end.max(start)
+    }
+
+    /// Query the receipts with `from <= timestamp < to` matching `filter`,
+    /// oldest first unless `reverse` is set. Also returns the number of matching receipts.
+    pub fn query(
+        &self,
+        from: Option<u64>,
+        to: Option<u64>,
+        filter: impl Fn(&T) -> bool,
+        count: usize,
+        skip: usize,
+        reverse: bool,
+    ) -> (usize, Vec<([u8; 32], &T)>) {
+        let window = &self.entries[self.window(from, to)];
+        let matching = || {
+            window
+                .iter()
+                .filter(|(_, _, info)| filter(info))
+                .map(|(id, _, info)| (*id, info))
+        };
+        let total = matching().count();
+        let receipts = if reverse {
+            matching().rev().skip(skip).take(count).collect()
+        } else {
+            matching().skip(skip).take(count).collect()
+        };
+        (total, receipts)
+    }
+
+    /// Count the receipts with `from <= timestamp < to` matching `filter`
+    /// on each UTC day, as (day start timestamp, count), skipping empty days
+    pub fn daily_counts(
+        &self,
+        from: Option<u64>,
+        to: Option<u64>,
+        filter: impl Fn(&T) -> bool,
+    ) -> Vec<(u64, usize)> {
+        let mut counts: Vec<(u64, usize)> = Vec::new();
+        for (_, timestamp, info) in &self.entries[self.window(from, to)] {
+            if !filter(info) {
+                continue;
+            }
+            let day = timestamp - timestamp % SECONDS_PER_DAY;
+            match counts.last_mut() {
+                Some((last_day, count)) if *last_day == day => *count += 1,
+                _ => counts.push((day, 1)),
+            }
+        }
+        counts
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use crate::state::helpers::normalize_string;
+
+// Length of the grams indexed. Queries shorter than this have no trigrams,
+// so they're matched with a linear scan instead.
+const NGRAM: usize = 3;
+
+type Trigram = [char; NGRAM];
+
+/// How well an entry matches a query, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchTier {
+    ExactSymbol,
+    ExactName,
+    Prefix,
+    Contains,
+    Fuzzy,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SearchMatch<T> {
+    pub item: T,
+    pub tier: MatchTier,
+    /// Edit distance between the query and the closest part of the entry
+    pub distance: usize,
+}
+
+struct Entry<T> {
+    item: T,
+    name: String,
+    symbol: String,
+}
+
+/// An inverted trigram index over normalized names and symbols,
+/// supporting typo-tolerant search.
+pub struct SearchIndex<T> {
+    entries: Vec<Entry<T>>,
+    postings: HashMap<Trigram, Vec<u32>>,
+}
+
+impl<T: Copy> SearchIndex<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            postings: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, item: T, name: &str, symbol: &str) {
+        let id = self.entries.len() as u32;
+        let name = normalize_string(name);
+        let symbol = normalize_string(symbol);
+
+        let mut grams = trigrams(&name);
+        grams.extend(trigrams(&symbol));
+        grams.sort_unstable();
+        grams.dedup();
+        for trigram in grams {
+            self.postings.entry(trigram).or_default().push(id);
+        }
+
+        self.entries.push(Entry { item, name, symbol });
+    }
+
+    /// Find all entries matching `query`, in no particular order
+    pub fn search(&self, query: &str) -> Vec<SearchMatch<T>> {
+        let query = normalize_string(query);
+        let chars: Vec<char> = query.chars().collect();
+        if chars.is_empty() {
+            return Vec::new();
+        }
+
+        // Allow more typos the longer the query is
+        let max_distance = match chars.len() {
+            0..=3 => 0,
+            4..=7 => 1,
+            _ => 2,
+        };
+
+        // Each edit destroys at most `NGRAM` of the query's trigrams,
+        // so a match must share at least this many with the query
+        let mut query_trigrams = trigrams(&query);
+        query_trigrams.sort_unstable();
+        query_trigrams.dedup();
+        if query_trigrams.len() <= NGRAM * max_distance {
+            // A match might share no trigram with the query (e.g. short queries
+            // or a typo in the middle of one), so the index can't find them all
+            return self
+                .entries
+                .iter()
+                .filter_map(|entry| classify(entry, &query, &chars, max_distance))
+                .collect();
+        }
+        let min_shared = query_trigrams.len() - NGRAM * max_distance;
+
+        let mut shared: HashMap<u32, usize> = HashMap::new();
+        for trigram in &query_trigrams {
+            if let Some(ids) = self.postings.get(trigram) {
+                for &id in ids {
+                    *shared.entry(id).or_default() += 1;
+                }
+            }
+        }
+
+        shared
+            .into_iter()
+            .filter(|&(_, count)| count >= min_shared)
+            .filter_map(|(id, _)| {
+                classify(&self.entries[id as usize], &query, &chars, max_distance)
+            })
+            .collect()
+    }
+}
+
+fn classify<T: Copy>(
+    entry: &Entry<T>,
+    query: &str,
+    chars: &[char],
+    max_distance: usize,
+) -> Option<SearchMatch<T>> {
+    let tier = if entry.symbol == query {
+        MatchTier::ExactSymbol
+    } else if entry.name == query {
+        MatchTier::ExactName
+    } else if entry.name.starts_with(query) || entry.symbol.starts_with(query) {
+        MatchTier::Prefix
+    } else if entry.name.contains(query) || entry.symbol.contains(query) {
+        MatchTier::Contains
+    } else if max_distance > 0 {
+        let distance =
+            substring_distance(chars, &entry.name).min(substring_distance(chars, &entry.symbol));
+        if distance > max_distance {
+            return None;
+        }
+        return Some(SearchMatch {
+            item: entry.item,
+            tier: MatchTier::Fuzzy,
+            distance,
+        });
+    } else {
+        return None;
+    };
+
+    Some(SearchMatch {
+        item: entry.item,
+        tier,
+        distance: 0,
+    })
+}
+
+fn trigrams(s: &str) -> Vec<Trigram> {
+    let chars: Vec<char> = s.chars().collect();
+    chars.windows(NGRAM).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+/// The smallest edit distance between `pattern` and any substring of `text`
+fn substring_distance(pattern: &[char], text: &str) -> usize {
+    // Row j holds the distance between pattern[..j] and the best
+    // substring of text ending at the current character
+    let mut prev: Vec<usize> = (0..=pattern.len()).collect();
+    let mut cur = vec![0; pattern.len() + 1];
+    let mut best = pattern.len();
+
+    for c in text.chars() {
+        cur[0] = 0;
+        for j in 1..=pattern.len() {
+            let substitution = prev[j - 1] + usize::from(pattern[j - 1] != c);
+            cur[j] = substitution.min(prev[j] + 1).min(cur[j - 1] + 1);
+        }
+        best = best.min(cur[pattern.len()]);
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    best
+}
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Source {
     Ivy,
@@ -6,3 +8,20 @@ pub enum Source {
     Fx,
     Misc,
 }
+
+/// Where a SOL price comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceSource {
+    /// The ivy-backend `/sol-price` endpoint
+    #[default]
+    Backend,
+    /// A SOL/USDC PumpSwap pool, from its trades
+    Pool,
+    /// A file with a fixed price, for tests
+    File,
+}
+
+impl PriceSource {
+    pub const ALL: [PriceSource; 3] = [PriceSource::Backend, PriceSource::Pool, PriceSource::File];
+}
@@ -2,6 +2,12 @@ use serde::Serialize;
 
 use crate::types::{asset::Asset, public::Public};
 
+/// Virtual token reserves of a new pump.fun bonding curve (1.073 billion TOKEN)
+pub const INITIAL_VIRTUAL_TOKEN_RESERVES: u64 = 1_073_000_000_000_000;
+/// Tokens sold from the bonding curve by the time it completes (793.1 million TOKEN),
+/// which are its real token reserves when it's new
+pub const CURVE_TOKEN_SUPPLY: u64 = 793_100_000_000_000;
+
 #[derive(Clone, Serialize)]
 pub struct Sync {
     pub name: String,
@@ -13,6 +19,10 @@ pub struct Sync {
     pub icon_url: String,
     pub game_url: String,
     pub description: String,
+    // Links from the metadata, empty if it has none
+    pub website: String,
+    pub twitter: String,
+    pub telegram: String,
 
     pub is_migrated: bool,
     pub pswap_pool: Option<Public>,
@@ -23,9 +33,35 @@ pub struct Sync {
 
     pub sol_reserves: u64,
     pub token_reserves: u64,
+
+    // Real reserves of the bonding curve, frozen once it completes
+    pub real_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    /// Bonding curve completion, from 0 to 100
+    pub curve_progress_pct: f32,
+    /// SOL paid to the creator in fees, on the bonding curve and in the pool
+    pub creator_fees: u64,
+
+    /// When the last token was sold from the bonding curve
+    pub curve_complete_timestamp: Option<u64>,
+    /// When the bonding curve migrated to a PumpSwap pool
+    pub migrate_timestamp: Option<u64>,
+    /// When the PumpSwap pool was first traded
+    pub first_pool_trade_timestamp: Option<u64>,
+
+    pub holders: u64,
 }
 
 impl Sync {
+    /// How far along the bonding curve this sync is, from 0 to 1
+    pub fn migration_progress(&self) -> f32 {
+        if self.is_migrated {
+            return 1.0;
+        }
+        let sold = INITIAL_VIRTUAL_TOKEN_RESERVES.saturating_sub(self.token_reserves);
+        (sold as f64 / CURVE_TOKEN_SUPPLY as f64).min(1.0) as f32
+    }
+
     pub fn to_asset(&self) -> Asset {
         Asset {
             name: self.name.clone(),
@@ -36,6 +72,7 @@ impl Sync {
             create_timestamp: self.create_timestamp,
             mkt_cap_usd: self.mkt_cap_usd,
             price: self.last_price_usd,
+            holders: self.holders,
         }
     }
 }
@@ -9,6 +9,7 @@ pub struct Trade {
     pub symbol: String,
     pub icon_url: String,
     pub volume_usd: f32,
+    pub price_usd: f32,
     pub mkt_cap_usd: f32,
     pub is_buy: bool,
 }
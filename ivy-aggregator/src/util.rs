@@ -34,6 +34,16 @@ pub fn mil_to_usd(v: u64) -> f32 {
     (v as f32) / 1000.0
 }
 
+/// Map a float to a `u64` with the same ordering
+pub fn order_key(v: f64) -> u64 {
+    let bits = v.to_bits();
+    if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits | (1 << 63)
+    }
+}
+
 /// Return the current unix timestamp
 pub fn unix_timestamp() -> u64 {
     SystemTime::now()
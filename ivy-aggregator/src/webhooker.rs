@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use ureq::http::Uri;
+use ureq::Agent;
+
+use crate::applier::Cursor;
+use crate::net::public_agent;
+use crate::state::components::webhooks::{WebhookJob, WebhookSource};
+use crate::state::types::{DeliveryOutcome, WebhookDelivery};
+use crate::state::State;
+use crate::util::unix_timestamp;
+
+// Delay before the first retry, doubled after each failed attempt
+const BASE_RETRY_DELAY: u64 = 10;
+// Longest delay between attempts, in seconds
+const MAX_RETRY_DELAY: u64 = 60 * 60;
+// Attempts before a delivery is given up on
+const MAX_ATTEMPTS: u32 = 12;
+// Time allowed for a webhook to respond
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// Longest time to wait for new jobs when nothing is due
+const IDLE_WAIT: Duration = Duration::from_secs(60);
+// Deliveries attempted at once
+const MAX_CONCURRENT: usize = 16;
+// Deliveries attempted at once to the same host
+const MAX_CONCURRENT_PER_HOST: usize = 2;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct PendingDelivery {
+    job: WebhookJob,
+    /// Number of attempts made so far
+    attempts: u32,
+    /// Unix timestamp of the next attempt
+    next_attempt: u64,
+}
+
+/// Compute the signature sent in the `X-Ivy-Signature` header:
+/// HMAC-SHA256 of `"{timestamp}.{body}"`, keyed by the webhook secret, as hex.
+pub fn sign_payload(secret: &str, timestamp: u64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// The host deliveries to `url` are limited per
+fn host_of(url: &str) -> String {
+    url.parse::<Uri>()
+        .ok()
+        .and_then(|uri| uri.host().map(str::to_ascii_lowercase))
+        .unwrap_or_default()
+}
+
+/// Split due deliveries into those to attempt now, oldest first, without going
+/// over the limits on concurrent deliveries, and those left for the next round
+fn take_batch(mut due: Vec<PendingDelivery>) -> (Vec<PendingDelivery>, Vec<PendingDelivery>) {
+    due.sort_by_key(|p| p.next_attempt);
+    let mut per_host: HashMap<String, usize> = HashMap::new();
+    let mut batch = Vec::new();
+    let mut rest = Vec::new();
+    for delivery in due {
+        let host_count = per_host.entry(host_of(&delivery.job.url)).or_default();
+        if batch.len() < MAX_CONCURRENT && *host_count < MAX_CONCURRENT_PER_HOST {
+            *host_count += 1;
+            batch.push(delivery);
+        } else {
+            rest.push(delivery);
+        }
+    }
+    (batch, rest)
+}
+
+/// Worker that POSTs webhook payloads, retrying failures with exponential backoff.
+/// Due deliveries are attempted concurrently, in rounds, and each round's
+/// attempts are recorded and persisted at once.
+/// Pending deliveries are persisted, so they survive restarts.
+pub struct Webhooker {
+    rx: Receiver<WebhookJob>,
+    state: Arc<State>,
+    outbox: Cursor<Vec<PendingDelivery>>,
+    agent: Agent,
+}
+
+impl Webhooker {
+    pub fn new(
+        rx: Receiver<WebhookJob>,
+        state: Arc<State>,
+        outbox_path: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            rx,
+            state,
+            outbox: Cursor::new(outbox_path)?,
+            agent: public_agent(REQUEST_TIMEOUT),
+        })
+    }
+
+    pub fn run(mut self) {
+        let mut pending = match self.outbox.read() {
+            Ok(pending) => pending.unwrap_or_default(),
+            Err(e) => {
+                eprintln!("Webhooker: can't read outbox: {}", e);
+                Vec::new()
+            }
+        };
+
+        loop {
+            // Wait for a new job or the next due delivery
+            let now = unix_timestamp();
+            let wait = pending
+                .iter()
+                .map(|p| Duration::from_secs(p.next_attempt.saturating_sub(now)))
+                .min()
+                .unwrap_or(IDLE_WAIT)
+                .min(IDLE_WAIT);
+            match self.rx.recv_timeout(wait) {
+                Ok(job) => {
+                    pending.push(PendingDelivery {
+                        job,
+                        attempts: 0,
+                        next_attempt: 0,
+                    });
+                    // Pick up any other queued jobs before persisting
+                    pending.extend(self.rx.try_iter().map(|job| PendingDelivery {
+                        job,
+                        attempts: 0,
+                        next_attempt: 0,
+                    }));
+                    self.persist(&pending);
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+
+            let now = unix_timestamp();
+            let (due, waiting): (Vec<_>, Vec<_>) =
+                pending.into_iter().partition(|p| p.next_attempt <= now);
+            if due.is_empty() {
+                pending = waiting;
+                continue;
+            }
+            // Deliveries left out of this round are still due, so the next starts right away
+            let (batch, rest) = take_batch(due);
+            pending = waiting;
+            pending.extend(rest);
+
+            let agent = &self.agent;
+            let results: Vec<_> = thread::scope(|s| {
+                let handles: Vec<_> = batch
+                    .into_iter()
+                    .map(|delivery| s.spawn(move || attempt(agent, delivery)))
+                    .collect();
+                handles.into_iter().map(|h| h.join().unwrap()).collect()
+            });
+
+            let mut state = self.state.write().unwrap();
+            for (source, record, retry) in results {
+                match source {
+                    WebhookSource::Game(game) => state.webhooks.on_delivery(game, record),
+                    WebhookSource::Alert(rule_id) => state.alerts.on_delivery(rule_id, record),
+                }
+                pending.extend(retry);
+            }
+            drop(state);
+            self.persist(&pending);
+        }
+    }
+
+    fn persist(&mut self, pending: &Vec<PendingDelivery>) {
+        if let Err(e) = self.outbox.write(pending) {
+            eprintln!("Webhooker: can't write outbox: {}", e);
+        }
+    }
+}
+
+/// Attempt a delivery, returning the record of the attempt for the delivery log
+/// of its source, and the delivery if it should be retried
+fn attempt(
+    agent: &Agent,
+    mut delivery: PendingDelivery,
+) -> (WebhookSource, WebhookDelivery, Option<PendingDelivery>) {
+    let job = &delivery.job;
+    let timestamp = unix_timestamp();
+    let signature = sign_payload(&job.secret, timestamp, &job.body);
+    let result = agent
+        .post(&job.url)
+        .header("Content-Type", "application/json")
+        .header("X-Ivy-Delivery", &job.delivery_id)
+        .header("X-Ivy-Timestamp", timestamp.to_string())
+        .header("X-Ivy-Signature", format!("sha256={}", signature))
+        .send(&job.body);
+
+    delivery.attempts += 1;
+    let (status_code, error) = match result {
+        // Redirects aren't followed, as they could point anywhere
+        Ok(resp) if resp.status().is_redirection() => {
+            let code = resp.status().as_u16();
+            (
+                Some(code),
+                Some(format!("HTTP status {} (redirects aren't followed)", code)),
+            )
+        }
+        Ok(resp) => (Some(resp.status().as_u16()), None),
+        Err(ureq::Error::StatusCode(code)) => (Some(code), Some(format!("HTTP status {}", code))),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    let (outcome, next_attempt) = match error {
+        None => (DeliveryOutcome::Delivered, None),
+        Some(_) if delivery.attempts >= MAX_ATTEMPTS => (DeliveryOutcome::Failed, None),
+        Some(_) => {
+            let delay = BASE_RETRY_DELAY
+                .saturating_mul(1 << (delivery.attempts - 1).min(32))
+                .min(MAX_RETRY_DELAY);
+            (DeliveryOutcome::Retrying, Some(timestamp + delay))
+        }
+    };
+
+    let record = WebhookDelivery {
+        delivery_id: job.delivery_id.clone(),
+        url: job.url.clone(),
+        attempt: delivery.attempts,
+        timestamp,
+        status_code,
+        error,
+        outcome,
+        next_attempt,
+    };
+    let source = job.source;
+    let retry = next_attempt.map(|next_attempt| {
+        delivery.next_attempt = next_attempt;
+        delivery
+    });
+    (source, record, retry)
+}